@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::{suggest, DraftOptions, Draw, History, Library, Power, PowerTiers};
+
+const RUNS: usize = 2000;
+const ROUNDS: usize = 25;
+const TOLERANCE: f64 = 0.01;
+
+#[derive(Clone, Debug)]
+pub struct Calibration {
+    pub target: BTreeMap<Power, f64>,
+    pub before: BTreeMap<Power, f64>,
+    pub after: BTreeMap<Power, f64>,
+    pub weights: BTreeMap<Power, f64>,
+}
+
+/// Parses a target like `Good=1/4, Great=0.1` into the share of drawn marks
+/// each power should make up.
+pub fn parse_target(s: &str, tiers: &PowerTiers) -> Result<BTreeMap<Power, f64>, String> {
+    let mut target = BTreeMap::new();
+
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((name, share)) = part.split_once('=') else {
+            return Err(format!("expected Power=share, got {part:?}"));
+        };
+        let name = name.trim();
+        let power = tiers
+            .power(name)
+            .ok_or_else(|| match suggest(name, tiers.names()) {
+                Some(s) => format!("unknown power {name:?}, did you mean '{s}'?"),
+                None => format!("unknown power {name:?}"),
+            })?;
+
+        let share = share.trim();
+        let bad_share = || format!("bad share {share:?} for {name}");
+        let share = match share.split_once('/') {
+            Some((n, d)) => {
+                let n: f64 = n.trim().parse().map_err(|_| bad_share())?;
+                let d: f64 = d.trim().parse().map_err(|_| bad_share())?;
+                n / d
+            }
+            None => share.parse().map_err(|_| bad_share())?,
+        };
+        if !(0.0..=1.0).contains(&share) {
+            return Err(bad_share());
+        }
+
+        target.insert(power, share);
+    }
+
+    if target.is_empty() {
+        return Err("no target given".to_string());
+    }
+    if target.values().sum::<f64>() > 1.0 {
+        return Err("target shares add up to more than 1".to_string());
+    }
+
+    Ok(target)
+}
+
+/// Runs the draft many times and returns the share of drawn marks per power.
+pub fn simulate(
+    lib: &Library,
+    draws: &[Draw],
+    options: &DraftOptions,
+    history: &History,
+    rng: &mut impl Rng,
+) -> BTreeMap<Power, f64> {
+    let mut shares = BTreeMap::new();
+    let mut total = 0;
+
+    for _ in 0..RUNS {
+        for mark in lib.exec_draw(draws.to_vec(), options, history, rng) {
+            *shares.entry(mark.power).or_insert(0.0) += 1.0;
+            total += 1;
+        }
+    }
+
+    for share in shares.values_mut() {
+        *share /= total as f64;
+    }
+
+    shares
+}
+
+pub fn calibrate(
+    lib: &Library,
+    draws: &[Draw],
+    options: &DraftOptions,
+    history: &History,
+    target: BTreeMap<Power, f64>,
+    rng: &mut impl Rng,
+) -> Calibration {
+    // an empty draft is calibrated as a handful of unconstrained draws
+    let draws = match draws {
+        [] => vec![Draw::default(); 4],
+        d => d.to_vec(),
+    };
+
+    let mut lib = lib.clone();
+    let before = simulate(&lib, &draws, options, history, rng);
+    let mut after = before.clone();
+
+    for _ in 0..ROUNDS {
+        let share = |p| after.get(p).copied().unwrap_or(0.0);
+        if target.iter().all(|(p, t)| (share(p) - t).abs() < TOLERANCE) {
+            break;
+        }
+
+        for (p, t) in &target {
+            let factor = match share(p) {
+                s if s > 0.0 => (t / s).clamp(0.25, 4.0),
+                _ if *t > 0.0 => 2.0,
+                _ => 1.0,
+            };
+            *lib.power_weights.entry(p.clone()).or_insert(1.0) *= factor;
+        }
+
+        after = simulate(&lib, &draws, options, history, rng);
+    }
+
+    for w in lib.power_weights.values_mut() {
+        *w = (*w * 100.0).round() / 100.0;
+    }
+
+    Calibration {
+        target,
+        before,
+        after,
+        weights: lib.power_weights,
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+static CHANGELOG: &str = include_str!("changelog.toml");
+
+/// Per-user settings kept between runs in `$XDG_CONFIG_HOME/upheaval-draft`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// The version of the app the what's-new overlay was last shown for.
+    #[serde(default)]
+    pub last_seen_version: Option<String>,
+    /// The name of the theme picked with F2.
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// A Discord webhook URL results are posted to.
+    #[serde(default)]
+    pub webhook: Option<String>,
+    /// Write saves on a single line instead of indented, which makes them
+    /// smaller but hard to diff.
+    #[serde(default)]
+    pub compact_saves: bool,
+    /// Ask for a name when a draft is executed, results without one are
+    /// listed by number.
+    #[serde(default)]
+    pub name_drafts: bool,
+    /// Libraries and saves opened or saved lately, latest first, offered when
+    /// the app is started without a file.
+    #[serde(default)]
+    pub recent: Vec<PathBuf>,
+}
+
+/// How many recent files are kept, one for each digit key.
+const MAX_RECENT: usize = 9;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub version: String,
+    pub notes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Changelog {
+    releases: Vec<Release>,
+}
+
+/// The directory the config and other per-user files are kept in.
+pub fn dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("upheaval-draft"))
+}
+
+pub fn path() -> Option<PathBuf> {
+    Some(dir()?.join("config.toml"))
+}
+
+/// Orders versions like `0.10.1` numerically instead of as strings.
+fn version_key(version: &str) -> Vec<u64> {
+    version.split('.').map(|n| n.parse().unwrap_or(0)).collect()
+}
+
+impl Config {
+    /// Loads the config, falling back to the defaults if it is missing or
+    /// unreadable so a broken config never keeps the app from starting.
+    pub fn load() -> Config {
+        path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// Release notes for the versions since the one last seen. Someone who
+    /// never saw the overlay only gets the notes of the current version.
+    pub fn unseen_releases(&self) -> Vec<Release> {
+        let current = version_key(env!("CARGO_PKG_VERSION"));
+        let seen = self.last_seen_version.as_deref().map(version_key);
+
+        let Changelog { releases } =
+            toml::from_str(CHANGELOG).expect("the embedded changelog is valid");
+        releases
+            .into_iter()
+            .filter(|r| {
+                let v = version_key(&r.version);
+                v <= current
+                    && match &seen {
+                        Some(seen) => &v > seen,
+                        None => v == current,
+                    }
+            })
+            .collect()
+    }
+
+    /// Puts `path` at the top of the recent files and saves the config.
+    pub fn remember(path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut config = Config::load();
+        config.recent.retain(|p| *p != path);
+        config.recent.insert(0, path);
+        config.recent.truncate(MAX_RECENT);
+        if let Err(e) = config.save() {
+            log::warn!("Could not save the config: {e}");
+        }
+    }
+
+    /// Records that the current version's notes have been shown.
+    pub fn mark_seen(&mut self) {
+        self.last_seen_version = Some(env!("CARGO_PKG_VERSION").to_string());
+    }
+}
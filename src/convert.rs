@@ -0,0 +1,130 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, format_err};
+
+use crate::{format, load_library_file, Library, PowerTiers, TiersToml};
+
+const USAGE: &str = "usage: upheaval-draft convert [--lenient] [--compact] INPUT OUTPUT";
+
+/// `convert INPUT OUTPUT`: reads a library or save and writes it in the
+/// format OUTPUT's extension picks, a library csv or any save format. Giving
+/// the same file twice rewrites it pretty-printed.
+pub fn run(args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut lenient = false;
+    let mut compact = false;
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--lenient" => lenient = true,
+            "--compact" => compact = true,
+            a if a.starts_with("--") => bail!("Unknown option {a}\n{USAGE}"),
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    let [input, output] = files.as_slice() else {
+        bail!("{USAGE}");
+    };
+
+    let mut save = load_library_file(input, lenient)?;
+    save.version = crate::migrate::VERSION;
+
+    if output.extension().is_some_and(|ext| ext == "csv") {
+        if !save.results.list().is_empty() {
+            eprintln!(
+                "{} results are left out, a library csv only holds marks",
+                save.results.list().len()
+            );
+        }
+        write_library_csv(&save.library, output)?;
+    } else {
+        format::of(output)?.save(output, &save, compact)?;
+    }
+
+    eprintln!("Converted {} to {}", input.display(), output.display());
+    Ok(())
+}
+
+/// Writes the marks of `library` in the layout library csvs are read in,
+/// with the tiers and `ignore_case` next to it in NAME.tiers.toml unless they
+/// are the defaults.
+fn write_library_csv(library: &Library, path: &PathBuf) -> anyhow::Result<()> {
+    let marks: Vec<_> = library.list.iter().map(|(m, _)| m).collect();
+    let tag_columns = marks.iter().map(|m| m.tags.len()).max().unwrap_or(0);
+    let has_upgrades = marks.iter().any(|m| m.upgrade.is_some());
+    let has_weights = marks.iter().any(|m| m.weight != 1.0);
+    let has_repeats = marks.iter().any(|m| m.repeatable);
+    let excludes_columns = marks.iter().map(|m| m.excludes.len()).max().unwrap_or(0);
+    let requires_columns = marks.iter().map(|m| m.requires.len()).max().unwrap_or(0);
+    let mut extra: Vec<&str> = marks
+        .iter()
+        .flat_map(|m| m.extra.keys().map(String::as_str))
+        .collect();
+    extra.sort();
+    extra.dedup();
+
+    let mut writer = csv::Writer::from_path(path)?;
+
+    let mut header = vec!["NAME", "POWER", "CATEGORY"];
+    header.extend(std::iter::repeat_n("TAG", tag_columns));
+    header.push("DESCRIPTION");
+    if has_upgrades {
+        header.push("UPGRADE");
+    }
+    if has_weights {
+        header.push("WEIGHT");
+    }
+    if has_repeats {
+        header.push("REPEAT");
+    }
+    header.extend(std::iter::repeat_n("EXCLUDES", excludes_columns));
+    header.extend(std::iter::repeat_n("REQUIRES", requires_columns));
+    header.extend(&extra);
+    writer.write_record(&header)?;
+
+    for mark in marks {
+        let mut row = vec![
+            mark.name.clone(),
+            mark.power.name().to_string(),
+            mark.category.clone(),
+        ];
+        let mut tags: Vec<_> = mark.tags.iter().cloned().collect();
+        tags.resize(tag_columns, String::new());
+        row.extend(tags);
+        row.push(mark.description.clone());
+        if has_upgrades {
+            row.push(mark.upgrade.clone().unwrap_or_default());
+        }
+        if has_weights {
+            row.push(mark.weight.to_string());
+        }
+        if has_repeats {
+            row.push(if mark.repeatable { "yes" } else { "" }.to_string());
+        }
+        let mut excludes: Vec<_> = mark.excludes.iter().cloned().collect();
+        excludes.resize(excludes_columns, String::new());
+        row.extend(excludes);
+        let mut requires: Vec<_> = mark.requires.iter().cloned().collect();
+        requires.resize(requires_columns, String::new());
+        row.extend(requires);
+        row.extend(
+            extra
+                .iter()
+                .map(|column| mark.extra.get(*column).cloned().unwrap_or_default()),
+        );
+        writer.write_record(&row)?;
+    }
+    writer.flush()?;
+
+    if library.tiers != PowerTiers::default() || library.ignore_case || library.script.is_some() {
+        let tiers_path = path.with_extension("tiers.toml");
+        let tiers = toml::to_string(&TiersToml {
+            tiers: library.tiers.clone(),
+            ignore_case: library.ignore_case,
+            script: library.script.clone(),
+        })
+        .map_err(|e| format_err!("Could not write {}: {e}", tiers_path.display()))?;
+        fs::write(&tiers_path, tiers)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// How many lines the log keeps, older ones are dropped.
+const CAPACITY: usize = 500;
+
+static LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Keeps log messages in memory for the TUI's debug log pane, since writing
+/// them to stderr would draw over the alternate screen.
+struct PaneLogger;
+
+impl Log for PaneLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let Ok(mut lines) = LINES.lock() else {
+            return;
+        };
+        if lines.len() == CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(format!(
+            "{:<5} {}: {}",
+            record.level(),
+            record.target(),
+            record.args()
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+pub fn init() {
+    if log::set_logger(&PaneLogger).is_ok() {
+        log::set_max_level(LevelFilter::Debug);
+    }
+}
+
+/// The last `n` lines logged, oldest first.
+pub fn last(n: usize) -> Vec<String> {
+    let Ok(lines) = LINES.lock() else {
+        return Vec::new();
+    };
+    lines
+        .iter()
+        .skip(lines.len().saturating_sub(n))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,277 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Write as _,
+    io::{self, IsTerminal},
+    path::PathBuf,
+};
+
+use anyhow::bail;
+use crossterm::style::{Attribute, ContentStyle};
+use ratatui::{
+    style::{Modifier, Stylize},
+    text::{Line, Span},
+};
+
+use crate::{load_library_file, theme, ui::power_str, Library, Mark, PowerTiers};
+
+const USAGE: &str = "usage: upheaval-draft diff [--color | --no-color] OLD NEW";
+
+/// What changed between two versions of a library, marks are matched by name.
+pub struct LibraryDiff {
+    added: Vec<Mark>,
+    removed: Vec<Mark>,
+    /// The old and the new version of marks that changed.
+    changed: Vec<(Mark, Mark)>,
+    /// The new library's tiers, to color powers with.
+    tiers: PowerTiers,
+    tiers_changed: bool,
+}
+
+impl LibraryDiff {
+    pub fn new(old: &Library, new: &Library) -> LibraryDiff {
+        let find = |lib: &Library, name: &str| {
+            lib.list
+                .iter()
+                .map(|(m, _)| m)
+                .find(|m| m.name == name)
+                .cloned()
+        };
+
+        let mut diff = LibraryDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            tiers: new.tiers.clone(),
+            tiers_changed: old.tiers != new.tiers,
+        };
+        for (mark, _) in &new.list {
+            match find(old, &mark.name) {
+                None => diff.added.push(mark.clone()),
+                Some(old) if !field_changes(&old, mark, &new.tiers).is_empty() => {
+                    diff.changed.push((old, mark.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for (mark, _) in &old.list {
+            if find(new, &mark.name).is_none() {
+                diff.removed.push(mark.clone());
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && !self.tiers_changed
+    }
+
+    /// The report, one line per added or removed mark and a line per changed
+    /// field of the changed ones.
+    pub fn lines(&self) -> Vec<Line<'static>> {
+        let theme = theme::current();
+        let power_changes = self
+            .changed
+            .iter()
+            .filter(|(old, new)| old.power != new.power)
+            .count();
+        let mut lines = vec![Line::from(vec![
+            Span::styled(format!("{} added", self.added.len()), theme.success),
+            Span::raw(", "),
+            Span::styled(format!("{} removed", self.removed.len()), theme.error),
+            Span::raw(", "),
+            Span::styled(format!("{} changed", self.changed.len()), theme.warning),
+            Span::raw(format!(" ({power_changes} in power)")),
+        ])];
+        if self.tiers_changed {
+            lines.push(Line::from(Span::styled(
+                "The power tiers changed",
+                theme.warning,
+            )));
+        }
+
+        let mark_line = |sign: Span<'static>, mark: &Mark| {
+            Line::from(vec![
+                sign,
+                Span::raw(" "),
+                Span::raw(mark.name.clone()).bold(),
+                Span::raw(" "),
+                power_str(&mark.power, &self.tiers),
+            ])
+        };
+        if !self.added.is_empty() {
+            lines.push(Line::default());
+            lines.extend(
+                self.added
+                    .iter()
+                    .map(|m| mark_line(Span::styled("+", theme.success), m)),
+            );
+        }
+        if !self.removed.is_empty() {
+            lines.push(Line::default());
+            lines.extend(
+                self.removed
+                    .iter()
+                    .map(|m| mark_line(Span::styled("-", theme.error), m)),
+            );
+        }
+        for (old, new) in &self.changed {
+            lines.push(Line::default());
+            lines.push(mark_line(Span::styled("~", theme.warning), new));
+            for mut change in field_changes(old, new, &self.tiers) {
+                change.spans.insert(0, Span::raw("    "));
+                lines.push(change);
+            }
+        }
+        lines
+    }
+}
+
+/// A line for each field that differs between two versions of a mark.
+fn field_changes(old: &Mark, new: &Mark, tiers: &PowerTiers) -> Vec<Line<'static>> {
+    let theme = theme::current();
+    let mut lines = Vec::new();
+    let change = |field: &str, old: Span<'static>, new: Span<'static>| {
+        Line::from(vec![
+            Span::raw(format!("{field}: ")),
+            old,
+            Span::raw(" → "),
+            new,
+        ])
+    };
+    let text = |s: &str| match s {
+        "" => Span::styled("<none>", theme.muted),
+        s => Span::raw(s.to_string()),
+    };
+
+    if old.power != new.power {
+        lines.push(change(
+            "power",
+            power_str(&old.power, tiers),
+            power_str(&new.power, tiers),
+        ));
+    }
+    if old.category != new.category {
+        lines.push(change("category", text(&old.category), text(&new.category)));
+    }
+    if old.tags != new.tags {
+        let mut line = Line::raw("tags:");
+        for tag in new.tags.difference(&old.tags) {
+            line.spans
+                .push(Span::styled(format!(" +{tag}"), theme.success));
+        }
+        for tag in old.tags.difference(&new.tags) {
+            line.spans
+                .push(Span::styled(format!(" -{tag}"), theme.error));
+        }
+        lines.push(line);
+    }
+    for (field, old, new) in [
+        ("excludes", &old.excludes, &new.excludes),
+        ("requires", &old.requires, &new.requires),
+    ] {
+        if old != new {
+            let mut line = Line::raw(format!("{field}:"));
+            for x in new.difference(old) {
+                line.spans
+                    .push(Span::styled(format!(" +{x}"), theme.success));
+            }
+            for x in old.difference(new) {
+                line.spans.push(Span::styled(format!(" -{x}"), theme.error));
+            }
+            lines.push(line);
+        }
+    }
+    if old.upgrade != new.upgrade {
+        lines.push(change(
+            "upgrade",
+            text(old.upgrade.as_deref().unwrap_or_default()),
+            text(new.upgrade.as_deref().unwrap_or_default()),
+        ));
+    }
+    if old.weight != new.weight {
+        lines.push(change(
+            "weight",
+            Span::raw(old.weight.to_string()),
+            Span::raw(new.weight.to_string()),
+        ));
+    }
+    if old.repeatable != new.repeatable {
+        let yes_no = |b| Span::raw(if b { "yes" } else { "no" });
+        lines.push(change(
+            "repeatable",
+            yes_no(old.repeatable),
+            yes_no(new.repeatable),
+        ));
+    }
+    let columns: BTreeSet<_> = old.extra.keys().chain(new.extra.keys()).collect();
+    for column in columns {
+        let (a, b) = (old.extra.get(column), new.extra.get(column));
+        if a != b {
+            lines.push(change(
+                column,
+                text(a.map_or("", String::as_str)),
+                text(b.map_or("", String::as_str)),
+            ));
+        }
+    }
+    // descriptions are long, so both versions get a line of their own
+    if old.description != new.description {
+        lines.push(Line::raw("description:"));
+        lines.push(Line::from(Span::styled(
+            format!("  - {}", old.description),
+            theme.error,
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("  + {}", new.description),
+            theme.success,
+        )));
+    }
+    lines
+}
+
+/// `diff OLD NEW`: prints what changed between two library files. Colored
+/// when printing to a terminal, returns whether the libraries are the same.
+pub fn run(args: impl Iterator<Item = String>) -> anyhow::Result<bool> {
+    let mut color = io::stdout().is_terminal();
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--color" => color = true,
+            "--no-color" => color = false,
+            a if a.starts_with("--") => bail!("Unknown option {a}\n{USAGE}"),
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    let [old, new] = files.as_slice() else {
+        bail!("{USAGE}");
+    };
+
+    let diff = LibraryDiff::new(
+        &load_library_file(old, false)?.library,
+        &load_library_file(new, false)?.library,
+    );
+    println!("--- {}\n+++ {}", old.display(), new.display());
+    for line in diff.lines() {
+        println!("{}", if color { ansi(&line) } else { line.to_string() });
+    }
+    Ok(diff.is_empty())
+}
+
+/// A line with its colors as terminal escape codes.
+fn ansi(line: &Line) -> String {
+    let mut out = String::new();
+    for span in &line.spans {
+        let style = line.style.patch(span.style);
+        let mut ansi = ContentStyle::new();
+        ansi.foreground_color = style.fg.map(Into::into);
+        ansi.background_color = style.bg.map(Into::into);
+        if style.add_modifier.contains(Modifier::BOLD) {
+            ansi.attributes.set(Attribute::Bold);
+        }
+        let _ = write!(out, "{}", ansi.apply(&*span.content));
+    }
+    out
+}
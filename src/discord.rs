@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use anyhow::format_err;
+use ratatui::style::Color;
+use serde_json::json;
+
+use crate::{
+    config::{self, Config},
+    export, DraftResult, PowerTiers,
+};
+
+/// How long posting may take before giving up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts the `n`th result to the webhook in the config as a Discord embed.
+pub fn post_result(n: usize, result: &DraftResult, tiers: &PowerTiers) -> anyhow::Result<()> {
+    let url = Config::load().webhook.ok_or_else(|| {
+        let path = config::path().map_or("the config".to_string(), |p| p.display().to_string());
+        format_err!("No webhook to post to, add webhook = \"https://discord.com/api/webhooks/...\" to {path}")
+    })?;
+
+    ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .build()
+        .post(&url)
+        .set("Content-Type", "application/json")
+        .send_string(&message(n, result, tiers).to_string())?;
+    Ok(())
+}
+
+fn message(n: usize, result: &DraftResult, tiers: &PowerTiers) -> serde_json::Value {
+    let title = match &result.options.player {
+        Some(player) => format!("{} — {player}", result.title(n)),
+        None => result.title(n),
+    };
+
+    // embeds can't color text, but ansi code blocks can
+    let mut lines = vec!["```ansi".to_string()];
+    for mark in &result.marks {
+        let color = tiers.get(&mark.power).map_or(37, |t| ansi_code(&t.color));
+        let star = if result.wishlisted.contains(&mark.name) {
+            " ★"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "\u{1b}[1;{color}m{}\u{1b}[0m {}{star}",
+            mark.name,
+            mark.power.name()
+        ));
+    }
+    lines.push("```".to_string());
+    if !result.note.is_empty() {
+        lines.extend(result.note.lines().map(|l| format!("> {l}")));
+    }
+
+    // the strip down the side takes the color of the strongest mark
+    let strongest = result
+        .marks
+        .iter()
+        .filter_map(|m| tiers.get(&m.power))
+        .max_by_key(|t| t.rank);
+    let color = strongest.and_then(|t| rgb(&t.color));
+
+    json!({
+        "embeds": [{
+            "title": title,
+            "description": lines.join("\n"),
+            "color": color,
+        }]
+    })
+}
+
+/// A tier color as a 24 bit rgb number.
+fn rgb(color: &str) -> Option<u32> {
+    let hex = export::css_color(color)?;
+    u32::from_str_radix(hex.trim_start_matches('#'), 16).ok()
+}
+
+/// The closest of the eight foreground colors Discord's ansi blocks support.
+fn ansi_code(color: &str) -> u8 {
+    match color.parse::<Color>() {
+        Ok(Color::Red | Color::LightRed) => 31,
+        Ok(Color::Green | Color::LightGreen) => 32,
+        Ok(Color::Yellow | Color::LightYellow) => 33,
+        Ok(Color::Blue | Color::LightBlue) => 34,
+        Ok(Color::Magenta | Color::LightMagenta) => 35,
+        Ok(Color::Cyan | Color::LightCyan) => 36,
+        Ok(Color::Black | Color::DarkGray | Color::Gray) => 30,
+        Ok(Color::White | Color::Reset) | Err(_) => 37,
+        // anything else goes by which of its channels are clearly lit
+        Ok(_) => {
+            let Some(rgb) = rgb(color) else {
+                return 37;
+            };
+            let lit = |shift: u32, bit| if (rgb >> shift) & 0xff >= 128 { bit } else { 0 };
+            match lit(16, 1) | lit(8, 2) | lit(0, 4) {
+                0 => 30,
+                7 => 37,
+                i => 30 + i,
+            }
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    fmt::Write as _,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use anyhow::Context;
+
+use ratatui::style::Color;
+use serde::Serialize;
+
+use crate::{lines, DraftResult, Library, Mark, Power, PowerTiers};
+
+/// What the results can be exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One row per drafted mark, for spreadsheets.
+    Csv,
+    /// A standalone page with the library and every draft, for sharing.
+    Html,
+    /// The selected result on its own, to import into another save.
+    Json,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Html => "html",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// One drafted mark, results are flattened into one row per slot so a whole
+/// campaign can be analysed in a spreadsheet.
+#[derive(Serialize)]
+struct Row<'a> {
+    draft: usize,
+    slot: usize,
+    mark: &'a str,
+    power: &'a str,
+    category: &'a str,
+    draw: String,
+    player: &'a str,
+    /// Seconds since the unix epoch, empty for results saved before drafts
+    /// were timestamped.
+    timestamp: Option<u64>,
+}
+
+/// Writes every drafted mark of `results` to a csv at `path`, returns how
+/// many rows were written.
+pub fn write_csv(results: &[DraftResult], path: &Path) -> anyhow::Result<usize> {
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut rows = 0;
+
+    for (draft, result) in results.iter().enumerate() {
+        for (slot, mark) in result.marks.iter().enumerate() {
+            writer.serialize(Row {
+                draft,
+                slot: slot + 1,
+                mark: &mark.name,
+                power: mark.power.name(),
+                category: &mark.category,
+                draw: result
+                    .draws
+                    .get(slot)
+                    .map(lines::describe_draw)
+                    .unwrap_or_default(),
+                player: result.options.player.as_deref().unwrap_or_default(),
+                timestamp: result.timestamp,
+            })?;
+            rows += 1;
+        }
+    }
+
+    writer.flush()?;
+    Ok(rows)
+}
+
+/// Writes a single result with its marks, draws, seed and timestamp, so
+/// drafts made on different machines can be merged with [`read_result`].
+pub fn write_result(result: &DraftResult, path: &Path) -> anyhow::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut f, result)?;
+    Ok(())
+}
+
+/// Reads a result written by [`write_result`].
+pub fn read_result(path: &Path) -> anyhow::Result<DraftResult> {
+    let f = File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+    let f = BufReader::new(f);
+    serde_json::from_reader(f)
+        .with_context(|| format!("{} is not an exported result", path.display()))
+}
+
+/// A mark as markdown, for pasting into chat or session notes.
+pub fn mark_text(mark: &Mark) -> String {
+    let mut text = format!("**{}** ({}", mark.name, mark.power.name());
+    if !mark.category.is_empty() {
+        let _ = write!(text, ", {}", mark.category);
+    }
+    text.push(')');
+    if !mark.tags.is_empty() {
+        let tags: String = mark
+            .tags
+            .iter()
+            .map(String::as_str)
+            .intersperse(", ")
+            .collect();
+        let _ = write!(text, "\nTags: {tags}");
+    }
+    if !mark.description.is_empty() {
+        let _ = write!(text, "\n{}", mark.description);
+    }
+    text
+}
+
+/// The `n`th result as markdown, for pasting into chat or session notes.
+pub fn result_text(n: usize, result: &DraftResult) -> String {
+    let mut text = match &result.options.player {
+        Some(player) => format!("**{}** — {player}", result.title(n)),
+        None => format!("**{}**", result.title(n)),
+    };
+    for mark in &result.marks {
+        let star = if result.wishlisted.contains(&mark.name) {
+            " ★"
+        } else {
+            ""
+        };
+        let _ = write!(text, "\n- {} ({}){star}", mark.name, mark.power.name());
+    }
+    for line in result.note.lines() {
+        let _ = write!(text, "\n> {line}");
+    }
+    text
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; color: #222; }
+h1, h2 { border-bottom: 1px solid #ccc; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.3em 0.6em; border-bottom: 1px solid #eee; vertical-align: top; }
+.badge { display: inline-block; padding: 0.1em 0.5em; border-radius: 0.8em; font-size: 0.85em; \
+white-space: nowrap; background: #333; }
+.used { color: #999; text-decoration: line-through; }
+.draft { margin-bottom: 1.5em; }
+.note { white-space: pre-wrap; border-left: 3px solid #ccc; padding-left: 0.8em; color: #555; }
+.description { color: #555; font-size: 0.9em; }
+";
+
+/// Writes a standalone html page with the library, which marks are still
+/// free, and every result grouped by the player it is assigned to.
+pub fn write_html(lib: &Library, results: &[DraftResult], path: &Path) -> anyhow::Result<()> {
+    fs::write(path, html(lib, results))?;
+    Ok(())
+}
+
+fn html(lib: &Library, results: &[DraftResult]) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>Draft report</title>\n");
+    let _ = writeln!(out, "<style>\n{STYLE}</style>\n</head>\n<body>");
+    out.push_str("<h1>Draft report</h1>\n");
+
+    let free = lib.list.iter().filter(|(_, free)| *free).count();
+    let _ = writeln!(
+        out,
+        "<p>{} marks, {free} still free, {} drafts.</p>",
+        lib.list.len(),
+        results.len()
+    );
+
+    // players of the roster first, then anyone only named in a result
+    let mut players: Vec<&str> = lib.players.iter().map(|p| p.name.as_str()).collect();
+    for player in results.iter().filter_map(|r| r.options.player.as_deref()) {
+        if !players.contains(&player) {
+            players.push(player);
+        }
+    }
+    let sections = players
+        .into_iter()
+        .map(Some)
+        .chain([None])
+        .map(|player| {
+            let drafts: Vec<_> = results
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.options.player.as_deref() == player)
+                .collect();
+            (player, drafts)
+        })
+        .filter(|(_, drafts)| !drafts.is_empty());
+
+    out.push_str("<h1>Results</h1>\n");
+    if results.is_empty() {
+        out.push_str("<p>Nothing has been drafted yet.</p>\n");
+    }
+    for (player, drafts) in sections {
+        let _ = writeln!(out, "<h2>{}</h2>", escape(player.unwrap_or("Unassigned")));
+        for (n, result) in drafts {
+            out.push_str("<div class=\"draft\">\n");
+            let _ = writeln!(out, "<h3>{}</h3>", escape(&result.title(n)));
+            if !result.note.is_empty() {
+                let _ = writeln!(out, "<p class=\"note\">{}</p>", escape(&result.note));
+            }
+            out.push_str("<table>\n");
+            for mark in &result.marks {
+                let star = if result.wishlisted.contains(&mark.name) {
+                    " ★"
+                } else {
+                    ""
+                };
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td><b>{}</b>{star}<div class=\"description\">{}</div></td></tr>",
+                    badge(&mark.power, &lib.tiers),
+                    escape(&mark.name),
+                    escape(&mark.description),
+                );
+            }
+            out.push_str("</table>\n</div>\n");
+        }
+    }
+
+    out.push_str("<h1>Library</h1>\n<table>\n");
+    out.push_str("<tr><th>Power</th><th>Mark</th><th>Category</th><th>Tags</th></tr>\n");
+    for (mark, free) in &lib.list {
+        let _ = writeln!(out, "{}", library_row(mark, *free, &lib.tiers));
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+
+    out
+}
+
+fn library_row(mark: &Mark, free: bool, tiers: &PowerTiers) -> String {
+    let tags: String = mark
+        .tags
+        .iter()
+        .map(String::as_str)
+        .intersperse(", ")
+        .collect();
+    format!(
+        "<tr{}><td>{}</td><td><b>{}</b><div class=\"description\">{}</div></td><td>{}</td><td>{}</td></tr>",
+        if free { "" } else { " class=\"used\"" },
+        badge(&mark.power, tiers),
+        escape(&mark.name),
+        escape(&mark.description),
+        escape(&mark.category),
+        escape(&tags),
+    )
+}
+
+/// The power's name on a pill in its tier's colors.
+fn badge(power: &Power, tiers: &PowerTiers) -> String {
+    let (fg, bg) = match tiers.get(power) {
+        Some(tier) => (
+            css_color(&tier.color),
+            tier.background.as_deref().map(css_color),
+        ),
+        None => (None, None),
+    };
+    let mut style = String::new();
+    if let Some(fg) = fg {
+        let _ = write!(style, "color: {fg};");
+    }
+    if let Some(Some(bg)) = bg {
+        let _ = write!(style, " background: {bg};");
+    }
+    format!(
+        "<span class=\"badge\" style=\"{style}\">{}</span>",
+        escape(power.name())
+    )
+}
+
+/// The css equivalent of a terminal color, as written in a tiers file.
+pub fn css_color(color: &str) -> Option<String> {
+    const ANSI: [&str; 16] = [
+        "#000000", "#cd3131", "#0dbc79", "#e5e510", "#2472c8", "#bc3fbc", "#11a8cd", "#e5e5e5",
+        "#666666", "#f14c4c", "#23d18b", "#f5f543", "#3b8eea", "#d670d6", "#29b8db", "#ffffff",
+    ];
+    let hex = |r: u8, g: u8, b: u8| format!("#{r:02x}{g:02x}{b:02x}");
+
+    Some(match color.parse::<Color>().ok()? {
+        Color::Reset => return None,
+        Color::Black => ANSI[0].to_string(),
+        Color::Red => ANSI[1].to_string(),
+        Color::Green => ANSI[2].to_string(),
+        Color::Yellow => ANSI[3].to_string(),
+        Color::Blue => ANSI[4].to_string(),
+        Color::Magenta => ANSI[5].to_string(),
+        Color::Cyan => ANSI[6].to_string(),
+        Color::Gray => ANSI[7].to_string(),
+        Color::DarkGray => ANSI[8].to_string(),
+        Color::LightRed => ANSI[9].to_string(),
+        Color::LightGreen => ANSI[10].to_string(),
+        Color::LightYellow => ANSI[11].to_string(),
+        Color::LightBlue => ANSI[12].to_string(),
+        Color::LightMagenta => ANSI[13].to_string(),
+        Color::LightCyan => ANSI[14].to_string(),
+        Color::White => ANSI[15].to_string(),
+        Color::Rgb(r, g, b) => hex(r, g, b),
+        Color::Indexed(i @ 0..=15) => ANSI[i as usize].to_string(),
+        // the 6x6x6 color cube of the 256 color palette
+        Color::Indexed(i @ 16..=231) => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = i - 16;
+            hex(level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        // and its grayscale ramp
+        Color::Indexed(i) => {
+            let v = 8 + (i - 232) * 10;
+            hex(v, v, v)
+        }
+    })
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
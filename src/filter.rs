@@ -0,0 +1,291 @@
+//! Filter expressions of draws, for what the structured fields can't say,
+//! e.g. `power >= Good && (tag:Fire || tag:Ice) && !category:"Relics"`.
+//!
+//! `power` compares by rank with `<`, `<=`, `==`, `!=`, `>=` and `>`, and
+//! `tag:`, `category:`, `library:` and `name:` match like the fields of a
+//! draw do. Terms combine with `&&`, `||`, `!` and parentheses, and values
+//! with spaces are quoted.
+
+use std::{cmp::Ordering, fmt};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{suggest, Library, Mark, Power, PowerTiers};
+
+const FIELDS: &[&str] = &["power", "tag", "category", "library", "name"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    /// The mark's power compared to this one, e.g. `Ordering::Greater` and
+    /// `true` for `>=`, the bool allowing equal ranks.
+    Power(Option<Ordering>, bool, Power),
+    Tag(String),
+    Category(String),
+    Library(String),
+    Name(String),
+}
+
+/// A parsed filter, kept with its text to show and edit it by.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Filter {
+    text: String,
+    expr: Expr,
+}
+
+impl TryFrom<String> for Filter {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Filter::parse(&text)
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Filter, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(format!("unexpected {token}"));
+        }
+        Ok(Filter {
+            text: text.trim().to_string(),
+            expr,
+        })
+    }
+
+    pub fn matches(&self, mark: &Mark, lib: &Library) -> bool {
+        eval(&self.expr, mark, lib)
+    }
+
+    /// Fails on a power that isn't one of `tiers`, which would match nothing.
+    pub fn check(&self, tiers: &PowerTiers) -> Result<(), String> {
+        fn check(expr: &Expr, tiers: &PowerTiers) -> Result<(), String> {
+            match expr {
+                Expr::And(a, b) | Expr::Or(a, b) => check(a, tiers).and(check(b, tiers)),
+                Expr::Not(a) => check(a, tiers),
+                Expr::Power(_, _, p) if tiers.get(p).is_none() => Err(format!(
+                    "unknown power {:?}{}",
+                    p.name(),
+                    suggest(p.name(), tiers.names())
+                        .map(|s| format!(" (did you mean '{s}'?)"))
+                        .unwrap_or_default()
+                )),
+                _ => Ok(()),
+            }
+        }
+        check(&self.expr, tiers)
+    }
+}
+
+fn eval(expr: &Expr, mark: &Mark, lib: &Library) -> bool {
+    match expr {
+        Expr::And(a, b) => eval(a, mark, lib) && eval(b, mark, lib),
+        Expr::Or(a, b) => eval(a, mark, lib) || eval(b, mark, lib),
+        Expr::Not(a) => !eval(a, mark, lib),
+        Expr::Power(ordering, or_equal, power) => {
+            match (lib.tiers.rank(&mark.power), lib.tiers.rank(power)) {
+                (Some(rank), Some(other)) => {
+                    let cmp = rank.cmp(&other);
+                    Some(cmp) == *ordering || *or_equal && cmp == Ordering::Equal
+                }
+                _ => false,
+            }
+        }
+        Expr::Tag(tag) => lib.has_tag_under(mark, tag),
+        Expr::Category(category) => lib.same_label(&mark.category, category),
+        Expr::Library(library) => mark.library() == library,
+        Expr::Name(name) => mark.name == *name,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Colon,
+    Compare(&'static str),
+    Word(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => f.write_str("'('"),
+            Token::Close => f.write_str("')'"),
+            Token::And => f.write_str("'&&'"),
+            Token::Or => f.write_str("'||'"),
+            Token::Not => f.write_str("'!'"),
+            Token::Colon => f.write_str("':'"),
+            Token::Compare(op) => write!(f, "'{op}'"),
+            Token::Word(word) => write!(f, "{word:?}"),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    const COMPARISONS: &[&str] = &["<=", ">=", "==", "!=", "<", ">"];
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if let Some(op) = COMPARISONS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Compare(op));
+            rest = &rest[op.len()..];
+        } else if let Some(after) = rest.strip_prefix("&&") {
+            tokens.push(Token::And);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("||") {
+            tokens.push(Token::Or);
+            rest = after;
+        } else if c == '"' {
+            let Some(end) = rest[1..].find('"') else {
+                return Err("unclosed quote".to_string());
+            };
+            tokens.push(Token::Word(rest[1..end + 1].to_string()));
+            rest = &rest[end + 2..];
+        } else {
+            let token = match c {
+                '(' => Token::Open,
+                ')' => Token::Close,
+                '!' => Token::Not,
+                ':' => Token::Colon,
+                '&' | '|' => return Err(format!("'{c}' should be '{c}{c}'")),
+                _ => {
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || "()!:<>=&|\"".contains(c))
+                        .unwrap_or(rest.len());
+                    if end == 0 {
+                        return Err(format!("unexpected '{c}'"));
+                    }
+                    tokens.push(Token::Word(rest[..end].to_string()));
+                    rest = rest[end..].trim_start();
+                    continue;
+                }
+            };
+            tokens.push(token);
+            rest = &rest[c.len_utf8()..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.tokens.get(self.pos) == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.term()
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let field = match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                return Ok(expr);
+            }
+            Some(Token::Word(field)) => field,
+            Some(token) => return Err(format!("unexpected {token}")),
+            None => return Err("the filter ends early".to_string()),
+        };
+        let field = field.to_lowercase();
+
+        if !FIELDS.contains(&field.as_str()) {
+            let suggestion = suggest(&field, FIELDS.iter().copied())
+                .map(|s| format!(" (did you mean '{s}'?)"))
+                .unwrap_or_default();
+            return Err(format!("unknown field {field:?}{suggestion}"));
+        }
+
+        let op = match self.next() {
+            Some(Token::Colon) if field != "power" => ":",
+            Some(Token::Compare(op)) if field == "power" => op,
+            _ if field == "power" => return Err("power needs a comparison, e.g. >=".into()),
+            _ => return Err(format!("{field} needs a ':' and a value")),
+        };
+        let value = match self.next() {
+            Some(Token::Word(value)) => value,
+            _ => return Err(format!("{field}{op} needs a value")),
+        };
+
+        Ok(match field.as_str() {
+            "tag" => Expr::Tag(value),
+            "category" => Expr::Category(value),
+            "library" => Expr::Library(value),
+            "name" => Expr::Name(value),
+            _ => {
+                let (ordering, or_equal, negate) = match op {
+                    "<" => (Some(Ordering::Less), false, false),
+                    "<=" => (Some(Ordering::Less), true, false),
+                    "==" => (None, true, false),
+                    "!=" => (None, true, true),
+                    ">=" => (Some(Ordering::Greater), true, false),
+                    _ => (Some(Ordering::Greater), false, false),
+                };
+                let power = Expr::Power(ordering, or_equal, Power::from(value.as_str()));
+                if negate {
+                    Expr::Not(Box::new(power))
+                } else {
+                    power
+                }
+            }
+        })
+    }
+}
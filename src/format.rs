@@ -0,0 +1,139 @@
+use std::{fs, io::Read, path::Path};
+
+use anyhow::{bail, format_err, Context};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde_json::Value;
+
+use crate::{store, SaveFile};
+
+/// The formats a save can be written in, picked by the file's extension.
+/// JSON is the default, the others are nicer to edit by hand, or smaller in
+/// the case of gzipped JSON. SQLite saves are updated row by row instead of
+/// rewritten, for big campaigns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    Json,
+    JsonGz,
+    Toml,
+    Yaml,
+    Ron,
+    Sqlite,
+}
+
+/// The first bytes of every gzip file.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+impl SaveFormat {
+    /// Longest first, so `.json.gz` is matched before `.json` would be.
+    pub const EXTENSIONS: [&'static str; 7] =
+        ["json.gz", "json", "toml", "yaml", "yml", "ron", "db"];
+
+    pub fn from_path(path: &Path) -> Option<SaveFormat> {
+        let name = path.file_name()?.to_str()?;
+        let (_, ext) = name.split_once('.')?;
+        Some(match ext.rsplit('.').next()? {
+            "gz" if ext.ends_with("json.gz") => SaveFormat::JsonGz,
+            "json" => SaveFormat::Json,
+            "toml" => SaveFormat::Toml,
+            "yaml" | "yml" => SaveFormat::Yaml,
+            "ron" => SaveFormat::Ron,
+            "db" => SaveFormat::Sqlite,
+            _ => return None,
+        })
+    }
+
+    /// Reads a save without interpreting it yet. Gzipped files are detected
+    /// by their contents, so a compressed save loads whatever it is named.
+    pub fn read(self, path: &Path) -> anyhow::Result<Value> {
+        if self == SaveFormat::Sqlite {
+            return store::read(path);
+        }
+        let mut bytes = fs::read(path)?;
+        if bytes.starts_with(&GZIP_MAGIC) {
+            let mut text = Vec::new();
+            GzDecoder::new(bytes.as_slice())
+                .read_to_end(&mut text)
+                .with_context(|| format!("{} is not a valid gzip file", path.display()))?;
+            bytes = text;
+        }
+        let text = String::from_utf8(bytes)
+            .with_context(|| format!("{} is not a text or gzip file", path.display()))?;
+        self.parse(&text)
+    }
+
+    /// Parses a save without interpreting it yet, so older layouts can be
+    /// migrated first.
+    pub fn parse(self, text: &str) -> anyhow::Result<Value> {
+        Ok(match self {
+            SaveFormat::Json | SaveFormat::JsonGz => serde_json::from_str(text)?,
+            SaveFormat::Toml => toml::from_str(text)?,
+            SaveFormat::Yaml => serde_yaml::from_str(text)?,
+            SaveFormat::Ron => ron::from_str(text)?,
+            SaveFormat::Sqlite => bail!("A SQLite save is not text"),
+        })
+    }
+
+    /// Writes `save` to `path`, see [`SaveFormat::write`].
+    pub fn save(self, path: &Path, save: &SaveFile, compact: bool) -> anyhow::Result<()> {
+        match self {
+            SaveFormat::Sqlite => store::write(path, save),
+            _ => Ok(fs::write(path, self.write(save, compact)?)?),
+        }
+    }
+
+    /// Writes `save` out, indented unless `compact` is set. SQLite saves
+    /// can only be written in place with [`SaveFormat::save`].
+    pub fn write(self, save: &SaveFile, compact: bool) -> anyhow::Result<Vec<u8>> {
+        let mut text = match (self, compact) {
+            (SaveFormat::Sqlite, _) => bail!("A SQLite save can only be written to a file"),
+            (SaveFormat::JsonGz, _) => {
+                let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+                serde_json::to_writer(&mut gz, save)?;
+                return Ok(gz.finish()?);
+            }
+            (SaveFormat::Json, true) => return Ok(serde_json::to_vec(save)?),
+            (SaveFormat::Json, false) => serde_json::to_string_pretty(save)?,
+            // TOML and YAML have no compact form
+            (SaveFormat::Toml, _) => toml::to_string(save).context(
+                "Could not write TOML, results drafted by older versions can have seeds too \
+                 large for it, save as .json, .yaml or .ron instead",
+            )?,
+            (SaveFormat::Yaml, _) => serde_yaml::to_string(save)?,
+            (SaveFormat::Ron, true) => ron::to_string(save)?,
+            (SaveFormat::Ron, false) => {
+                ron::ser::to_string_pretty(save, ron::ser::PrettyConfig::default())?
+            }
+        };
+        // git complains about files without a final newline
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        Ok(text.into_bytes())
+    }
+}
+
+/// Whether a TOML file holds a save rather than a library, libraries have
+/// their marks at the top level.
+pub fn is_toml_save(text: &str) -> anyhow::Result<bool> {
+    let table: toml::Table = toml::from_str(text)?;
+    Ok(table.contains_key("library"))
+}
+
+/// Whether the app can open `path`, a library csv, or a library toml or save
+/// in any of the save formats. The tiers of a csv library are not one.
+pub fn is_library(path: &Path) -> bool {
+    !path.to_string_lossy().ends_with(".tiers.toml")
+        && (path.extension().is_some_and(|ext| ext == "csv")
+            || SaveFormat::from_path(path).is_some())
+}
+
+/// The format of a save at `path`, with an error naming the supported ones.
+pub fn of(path: &Path) -> anyhow::Result<SaveFormat> {
+    SaveFormat::from_path(path).ok_or_else(|| {
+        format_err!(
+            "{} is not a save, saves end in .{}",
+            path.display(),
+            SaveFormat::EXTENSIONS.join(", .")
+        )
+    })
+}
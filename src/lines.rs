@@ -0,0 +1,739 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    discord, export, filter::Filter, strategy, suggest, tag_tree, ui, Budget, DraftOptions,
+    DraftResult, Draw, Exclusion, Library, Power, PowerConstraint, PowerTiers, Preview, Recency,
+    SaveFile, NO_MARK,
+};
+
+const HELP: &str = "\
+marks               list the library
+show NAME           show a mark
+add                 add a draw
+remove N            remove draw N
+filter N [EXPR]     only draw marks matching EXPR in draw N, e.g.
+                    power >= Good && (tag:Fire || tag:Ice) && !category:Relics
+clear               remove all draws
+draft               show the current draft
+pin NAME            toggle pinning a mark
+ban NAME            toggle banning a mark
+free NAME           make a mark free to draft again
+use NAME            mark a mark as used up, so it isn't drafted
+unique              toggle no duplicate categories
+strategy [NAME]     draw with weighted (the default), uniform, balanced or bag
+player [NAME]       assign the draft to a player, or unassign it
+exclude [SCOPE]     leave out the marks drafted before in the campaign or by
+                    the player, or stop
+wish NAME           toggle a mark on the player's wishlist
+budget [N[!]]       cap the points the draft costs, ! to never go over
+recency [N [X]]     multiply the weight of marks drawn in the last N drafts
+                    with X, or stop
+session [NAME]      start a session new results are listed under, or stop
+run [NAME]          execute the draft, naming it NAME
+preview             draw the draft without taking it, with pool sizes and warnings
+results [all]       list the results, all also lists the archived ones
+archive N|SESSION   archive result N or every result of SESSION, or bring it back
+drafted PLAYER      list every mark PLAYER drafted, repeats first
+result N            show result N
+replay N            draw result N again from its seed
+post N              post result N to the webhook in the config
+save NAME           save library and results to NAME.json
+export NAME         write every drafted mark to NAME.csv
+report NAME         write the library and results to NAME.html
+dump N NAME         write result N on its own to NAME.json
+import NAME         add a result written by dump from NAME.json
+quit                exit";
+
+/// Line based draft workflow for terminals that can't run the TUI.
+pub fn run(save: SaveFile) -> anyhow::Result<()> {
+    let SaveFile {
+        mut library,
+        mut results,
+        presets,
+        ..
+    } = save;
+    let mut draws: Vec<Draw> = Vec::new();
+    let mut options = DraftOptions::default();
+    let mut rng = rand::thread_rng();
+
+    let mut input = io::stdin().lock();
+
+    println!(
+        "{} marks loaded, {} past drafts. Type \"help\" for commands.",
+        library.list.len(),
+        results.list().len()
+    );
+
+    while let Some(line) = prompt(&mut input, "> ")? {
+        let (cmd, arg) = match line.split_once(' ') {
+            Some((cmd, arg)) => (cmd, arg.trim()),
+            None => (line.as_str(), ""),
+        };
+
+        let res = match cmd {
+            "" => Ok(()),
+            "help" | "?" => {
+                println!("{HELP}");
+                Ok(())
+            }
+            "marks" => {
+                for (mark, free) in &library.list {
+                    let marker = if options.pinned.contains(&mark.name) {
+                        "+"
+                    } else if options.banned.contains(&mark.name) {
+                        "x"
+                    } else if !free {
+                        "-"
+                    } else {
+                        " "
+                    };
+                    let tags: String = mark
+                        .tags
+                        .iter()
+                        .map(String::as_str)
+                        .intersperse(", ")
+                        .collect();
+                    println!(
+                        "{marker} {:<24} {:<10} {:<12} {tags}",
+                        mark.name,
+                        mark.power.name(),
+                        mark.category
+                    );
+                }
+                Ok(())
+            }
+            "show" => find_mark(&library, arg).map(|name| {
+                let (mark, _) = library.list.iter().find(|(m, _)| m.name == name).unwrap();
+                println!("{}", mark.name);
+                println!("Power: {}", mark.power.name());
+                println!("Category: {}", mark.category);
+                let tags: String = mark
+                    .tags
+                    .iter()
+                    .map(String::as_str)
+                    .intersperse(", ")
+                    .collect();
+                println!("Tags: {tags}");
+                if mark.repeatable {
+                    println!("Repeatable: yes");
+                }
+                if let Some(upgrade) = &mark.upgrade {
+                    println!("Upgrades to: {upgrade}");
+                }
+                for (column, value) in &mark.extra {
+                    println!("{column}: {value}");
+                }
+                println!("{}", mark.description);
+            }),
+            "add" => add_draw(&mut input, &library).map(|draw| {
+                if let Some(draw) = draw {
+                    draws.push(draw);
+                    println!("Added draw {}", draws.len());
+                }
+            }),
+            "remove" => match arg.parse::<usize>() {
+                Ok(n) if (1..=draws.len()).contains(&n) => {
+                    draws.remove(n - 1);
+                    Ok(())
+                }
+                _ => Err(format!("no draw {arg:?}")),
+            },
+            "filter" => {
+                let (n, text) = arg.split_once(' ').unwrap_or((arg, ""));
+                match n.parse::<usize>() {
+                    Ok(n) if (1..=draws.len()).contains(&n) => {
+                        let text = text.trim();
+                        let filter = if text.is_empty() {
+                            Ok(None)
+                        } else {
+                            Filter::parse(text)
+                                .and_then(|f| f.check(&library.tiers).map(|_| Some(f)))
+                        };
+                        filter.map(|filter| draws[n - 1].filter = filter)
+                    }
+                    _ => Err(format!("no draw {n:?}")),
+                }
+            }
+            "clear" => {
+                draws.clear();
+                Ok(())
+            }
+            "draft" => {
+                print_draft(&draws, &options, Some(library.recency));
+                Ok(())
+            }
+            "pin" => find_mark(&library, arg).map(|name| {
+                options.banned.remove(&name);
+                if !options.pinned.remove(&name) {
+                    options.pinned.insert(name);
+                }
+            }),
+            "ban" => find_mark(&library, arg).map(|name| {
+                options.pinned.remove(&name);
+                if !options.banned.remove(&name) {
+                    options.banned.insert(name);
+                }
+            }),
+            "free" | "use" => find_mark(&library, arg).map(|name| {
+                for (mark, free) in &mut library.list {
+                    if mark.name == name {
+                        *free = cmd == "free";
+                    }
+                }
+            }),
+            "unique" => {
+                options.unique_categories = !options.unique_categories;
+                Ok(())
+            }
+            "strategy" if arg.is_empty() => {
+                options.strategy = None;
+                Ok(())
+            }
+            "strategy" => match strategy::STRATEGIES
+                .iter()
+                .find(|s| s.name().eq_ignore_ascii_case(arg))
+            {
+                Some(s) => {
+                    options.strategy = Some(s.name().to_string());
+                    Ok(())
+                }
+                None => {
+                    let names = strategy::STRATEGIES.iter().map(|s| s.name());
+                    let suggestion = suggest(arg, names)
+                        .map(|s| format!(" (did you mean '{s}'?)"))
+                        .unwrap_or_default();
+                    Err(format!("no strategy {arg:?}{suggestion}"))
+                }
+            },
+            "player" => {
+                options.player = if arg.is_empty() {
+                    None
+                } else {
+                    library.player_mut(arg);
+                    Some(arg.to_string())
+                };
+                Ok(())
+            }
+            "exclude" => {
+                let exclusion = match arg {
+                    "" => Ok(None),
+                    "campaign" => Ok(Some(Exclusion::Campaign)),
+                    "player" => Ok(Some(Exclusion::Player)),
+                    _ => Err(format!("{arg:?} is no scope, campaign or player")),
+                };
+                exclusion.map(|exclusion| options.exclude_drafted = exclusion)
+            }
+            "budget" => Budget::parse(arg).map(|budget| options.budget = budget),
+            "recency" => library
+                .recency
+                .parse(arg)
+                .map(|recency| library.recency = recency),
+            "wish" => match &options.player {
+                Some(player) => find_mark(&library, arg).map(|name| {
+                    let wishlist = &mut library.player_mut(player).wishlist;
+                    if !wishlist.remove(&name) {
+                        wishlist.insert(name);
+                    }
+                }),
+                None => Err("the draft isn't assigned to a player".to_string()),
+            },
+            "run" => {
+                let mut result = library.draft(
+                    draws.clone(),
+                    options.clone(),
+                    &results.history(library.recency.window),
+                    &mut rng,
+                );
+                result.name = (!arg.is_empty()).then(|| arg.to_string());
+                print_result(results.list().len(), &result);
+                results.push(result);
+                Ok(())
+            }
+            "preview" => {
+                let preview = library.preview(
+                    draws.clone(),
+                    &options,
+                    &results.history(library.recency.window),
+                    &mut rng,
+                );
+                print_preview(&preview);
+                Ok(())
+            }
+            "archive" => {
+                let list = results.list();
+                let picked: Vec<usize> = match arg.parse::<usize>() {
+                    Ok(n) if n < list.len() => vec![n],
+                    _ => (0..list.len())
+                        .filter(|&i| list[i].session.as_deref() == Some(arg))
+                        .collect(),
+                };
+                match picked.first() {
+                    Some(&first) => {
+                        let archived = !list[first].archived;
+                        for i in picked {
+                            results.set_archived(i, archived);
+                        }
+                        Ok(())
+                    }
+                    None => Err(format!("no result or session {arg:?}")),
+                }
+            }
+            "session" => {
+                results.start_session((!arg.is_empty()).then(|| arg.to_string()));
+                Ok(())
+            }
+            "results" => {
+                let mut session = None;
+                let all = arg == "all";
+                let archived = results.list().iter().filter(|r| r.archived).count();
+                for (i, result) in results.list().iter().enumerate() {
+                    if result.archived && !all {
+                        continue;
+                    }
+                    if result.session.is_some() && result.session != session {
+                        println!("{}:", result.session.as_deref().unwrap());
+                    }
+                    session = result.session.clone();
+                    let indent = if session.is_some() { "  " } else { "" };
+                    let names: String = result
+                        .marks
+                        .iter()
+                        .map(|m| m.name.as_str())
+                        .intersperse(", ")
+                        .collect();
+                    let note = if result.archived { " (archived)" } else { "" };
+                    let title = result_title(i, result);
+                    match &result.options.player {
+                        Some(player) => println!("{indent}{title} — {player}: {names}{note}"),
+                        None => println!("{indent}{title}: {names}{note}"),
+                    }
+                }
+                if archived > 0 && !all {
+                    println!("{archived} archived, \"results all\" lists them too");
+                }
+                if let Some(session) = results.session() {
+                    println!("New drafts go in the session {session}");
+                }
+                Ok(())
+            }
+            "drafted" => match library.player(arg) {
+                Some(player) => {
+                    let history = results.player_history(&player.name);
+                    let (repeats, once): (Vec<_>, Vec<_>) = history
+                        .iter()
+                        .partition(|(mark, drafts)| drafts.len() > 1 && !mark.repeatable);
+                    if history.is_empty() {
+                        println!("{} didn't draft anything yet", player.name);
+                    }
+                    for (i, (mark, drafts)) in repeats.iter().chain(&once).enumerate() {
+                        let drafts: String = drafts
+                            .iter()
+                            .map(|n| format!("#{n}"))
+                            .intersperse(", ".to_string())
+                            .collect();
+                        let repeat = if i < repeats.len() { " (repeat)" } else { "" };
+                        println!(
+                            "{} ({}): Draft {drafts}{repeat}",
+                            mark.name,
+                            mark.power.name()
+                        );
+                    }
+                    Ok(())
+                }
+                None => {
+                    let names = library.players.iter().map(|p| p.name.as_str());
+                    let suggestion = suggest(arg, names)
+                        .map(|s| format!(" (did you mean '{s}'?)"))
+                        .unwrap_or_default();
+                    Err(format!("no player {arg:?}{suggestion}"))
+                }
+            },
+            "result" => match arg
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| results.list().get(i))
+            {
+                Some(result) => {
+                    print_result(arg.parse().unwrap(), result);
+                    print_draft(&result.draws, &result.options, None);
+                    Ok(())
+                }
+                None => Err(format!("no result {arg:?}")),
+            },
+            "replay" => match arg
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n < results.list().len())
+            {
+                Some(n) => {
+                    let result = &results.list()[n];
+                    let history = results.history_before(n, library.recency.window);
+                    match library.replay(result, &history) {
+                        Ok(marks) => {
+                            let replayed = DraftResult {
+                                marks,
+                                ..Default::default()
+                            };
+                            print_result(n, &replayed);
+                            if replayed
+                                .marks
+                                .iter()
+                                .map(|m| &m.name)
+                                .eq(result.marks.iter().map(|m| &m.name))
+                            {
+                                println!("Replay matches the result");
+                            } else {
+                                println!("Replay differs from the result");
+                            }
+                            let pool: Vec<_> = library
+                                .list
+                                .iter()
+                                .filter(|(m, _)| result.was_free(m))
+                                .map(|(m, _)| m.name.as_str())
+                                .collect();
+                            println!("Pool of {} marks: {}", pool.len(), pool.join(", "));
+                            Ok(())
+                        }
+                        Err(reason) => Err(format!("result {n} {reason}")),
+                    }
+                }
+                None => Err(format!("no result {arg:?}")),
+            },
+            "post" => match arg
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n < results.list().len())
+            {
+                Some(n) => discord::post_result(n, &results.list()[n], &library.tiers)
+                    .map(|_| println!("Posted Draft #{n}"))
+                    .map_err(|e| e.to_string()),
+                None => Err(format!("no result {arg:?}")),
+            },
+            "save" if !arg.is_empty() => ui::save_path(arg)
+                .and_then(|path| {
+                    ui::save(&library, &results, &presets, &path)?;
+                    Ok(path)
+                })
+                .map(|path| println!("Saved to {}", path.display()))
+                .map_err(|e| format!("{e:#}")),
+            "save" => Err("save needs a file name".to_string()),
+            "export" if !arg.is_empty() => ui::file_path(arg, "csv")
+                .and_then(|path| Ok((export::write_csv(results.list(), &path)?, path)))
+                .map(|(rows, path)| println!("Exported {rows} marks to {}", path.display()))
+                .map_err(|e| e.to_string()),
+            "export" => Err("export needs a file name".to_string()),
+            "report" if !arg.is_empty() => ui::file_path(arg, "html")
+                .and_then(|path| {
+                    export::write_html(&library, results.list(), &path)?;
+                    Ok(path)
+                })
+                .map(|path| println!("Wrote the report to {}", path.display()))
+                .map_err(|e| e.to_string()),
+            "report" => Err("report needs a file name".to_string()),
+            "dump" => match arg.split_once(' ') {
+                Some((n, name)) => match n
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n < results.list().len())
+                {
+                    Some(n) => ui::file_path(name.trim(), "json")
+                        .and_then(|path| {
+                            export::write_result(&results.list()[n], &path)?;
+                            Ok(path)
+                        })
+                        .map(|path| println!("Wrote Draft #{n} to {}", path.display()))
+                        .map_err(|e| e.to_string()),
+                    None => Err(format!("no result {n:?}")),
+                },
+                None => Err("dump needs a result and a file name".to_string()),
+            },
+            "import" if !arg.is_empty() => ui::file_path(arg, "json")
+                .and_then(|path| export::read_result(&path))
+                .map(|result| {
+                    results.push(result);
+                    println!("Imported as Draft #{}", results.list().len() - 1);
+                })
+                .map_err(|e| format!("{e:#}")),
+            "import" => Err("import needs a file name".to_string()),
+            "quit" | "exit" => break,
+            _ => Err(format!(
+                "unknown command {cmd:?}, type \"help\" for commands"
+            )),
+        };
+
+        if let Err(e) = res {
+            println!("error: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+fn prompt(input: &mut impl BufRead, question: &str) -> io::Result<Option<String>> {
+    print!("{question}");
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+fn find_mark(lib: &Library, name: &str) -> Result<String, String> {
+    if let Some((mark, _)) = lib
+        .list
+        .iter()
+        .find(|(m, _)| m.name.eq_ignore_ascii_case(name))
+    {
+        return Ok(mark.name.clone());
+    }
+    match suggest(name, lib.list.iter().map(|(m, _)| m.name.as_str())) {
+        Some(s) => Err(format!("unknown mark {name:?}, did you mean '{s}'?")),
+        None => Err(format!("unknown mark {name:?}")),
+    }
+}
+
+fn find_in<'a>(
+    kind: &str,
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a String> + Clone,
+) -> Result<String, String> {
+    if let Some(c) = candidates
+        .clone()
+        .into_iter()
+        .find(|c| c.eq_ignore_ascii_case(name))
+    {
+        return Ok(c.clone());
+    }
+    match suggest(name, candidates.into_iter().map(String::as_str)) {
+        Some(s) => Err(format!("unknown {kind} {name:?}, did you mean '{s}'?")),
+        None => Err(format!("unknown {kind} {name:?}")),
+    }
+}
+
+fn parse_power(name: &str, tiers: &PowerTiers) -> Result<Power, String> {
+    let name = name.trim();
+    tiers
+        .powers()
+        .into_iter()
+        .find(|p| p.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| match suggest(name, tiers.names()) {
+            Some(s) => format!("unknown power {name:?}, did you mean '{s}'?"),
+            None => format!("unknown power {name:?}"),
+        })
+}
+
+/// Parses `Good`, `Good+` (or better), `Good-` (or worse) and `Good..Great`.
+fn parse_constraint(s: &str, tiers: &PowerTiers) -> Result<PowerConstraint, String> {
+    if let Some((lo, hi)) = s.split_once("..") {
+        Ok(PowerConstraint::Range(
+            parse_power(lo, tiers)?,
+            parse_power(hi, tiers)?,
+        ))
+    } else if let Some(p) = s.strip_suffix('+') {
+        Ok(PowerConstraint::AtLeast(parse_power(p, tiers)?))
+    } else if let Some(p) = s.strip_suffix('-') {
+        Ok(PowerConstraint::AtMost(parse_power(p, tiers)?))
+    } else {
+        Ok(PowerConstraint::Exact(parse_power(s, tiers)?))
+    }
+}
+
+/// Asks for each constraint of a new draw, re-asking on invalid answers.
+/// Returns `None` when input runs out.
+fn add_draw(input: &mut impl BufRead, lib: &Library) -> Result<Option<Draw>, String> {
+    let mut draw = Draw::default();
+
+    macro_rules! ask {
+        ($question:expr, |$answer:ident| $parse:expr) => {
+            loop {
+                let Some($answer) = prompt(input, $question).map_err(|e| e.to_string())? else {
+                    return Ok(None);
+                };
+                match $parse {
+                    Ok(v) => break v,
+                    Err(e) => println!("error: {e}"),
+                }
+            }
+        };
+    }
+
+    draw.power = ask!(
+        "Power (blank for any, e.g. Good, Good+, Good-, Good..Great): ",
+        |a| {
+            match a.as_str() {
+                "" => Ok(None),
+                a => parse_constraint(a, &lib.tiers).map(Some),
+            }
+        }
+    );
+    draw.category = ask!("Category (blank for any): ", |a| match a.as_str() {
+        "" => Ok(None),
+        a => find_in("category", a, &lib.categories).map(Some),
+    });
+    let libraries = lib.libraries();
+    if libraries.len() > 1 {
+        draw.library = ask!("Library (blank for any): ", |a| match a.as_str() {
+            "" => Ok(None),
+            a => find_in("library", a, &libraries).map(Some),
+        });
+    }
+    // a parent tag takes the marks of every tag under it
+    let tags = tag_tree(&lib.tags);
+    draw.tags = ask!(
+        "Tags (comma separated, | for either, blank for none): ",
+        |a| {
+            a.split(',')
+                .map(str::trim)
+                .filter(|g| !g.is_empty())
+                .map(|g| {
+                    g.split('|')
+                        .map(|t| find_in("tag", t.trim(), &tags))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()
+        }
+    );
+    draw.upgrade = ask!(
+        "Upgrade draw? [y/N] ",
+        |a| match a.to_lowercase().as_str() {
+            "" | "n" | "no" => Ok(false),
+            "y" | "yes" => Ok(true),
+            _ => Err("answer y or n".to_string()),
+        }
+    );
+
+    Ok(Some(draw))
+}
+
+fn describe_power(p: &PowerConstraint) -> String {
+    match p {
+        PowerConstraint::Exact(p) => p.name().to_string(),
+        PowerConstraint::AtLeast(p) => format!("{} or better", p.name()),
+        PowerConstraint::AtMost(p) => format!("{} or worse", p.name()),
+        PowerConstraint::Range(lo, hi) => format!("{} to {}", lo.name(), hi.name()),
+    }
+}
+
+/// The constraints of a draw, e.g. "Power Good or better, Tag fire".
+pub fn describe_draw(draw: &Draw) -> String {
+    let mut parts = Vec::new();
+    if let Some(p) = &draw.power {
+        parts.push(format!("Power {}", describe_power(p)));
+    }
+    if let Some(c) = &draw.category {
+        parts.push(format!("Category {c}"));
+    }
+    if let Some(l) = &draw.library {
+        parts.push(format!("Library {l}"));
+    }
+    for group in &draw.tags {
+        parts.push(format!("Tag {}", group.join(" or ")));
+    }
+    if draw.upgrade {
+        parts.push("Upgrade".to_string());
+    }
+    if let Some(f) = &draw.filter {
+        parts.push(format!("Filter {f}"));
+    }
+    if parts.is_empty() {
+        parts.push("Any".to_string());
+    }
+    parts.join(", ")
+}
+
+fn print_draft(draws: &[Draw], options: &DraftOptions, recency: Option<Recency>) {
+    if draws.is_empty() {
+        println!("No draws");
+    }
+    for (i, draw) in draws.iter().enumerate() {
+        println!("Draw {}: {}", i + 1, describe_draw(draw));
+    }
+
+    if options.unique_categories {
+        println!("No duplicate categories");
+    }
+    if let Some(exclusion) = options.exclude_drafted {
+        println!("{}", exclusion.label());
+    }
+    if options.strategy.is_some() {
+        println!("{}", strategy::get(options.strategy.as_deref()).label());
+    }
+    if let Some(player) = &options.player {
+        println!("Player: {player}");
+    }
+    if let Some(budget) = options.budget {
+        let strict = if budget.strict { ", never over" } else { "" };
+        println!("Budget: {} points{strict}", budget.points);
+    }
+    if let Some(recency) = recency.filter(|r| r.window > 0) {
+        let drafts = if recency.window == 1 {
+            "draft"
+        } else {
+            "drafts"
+        };
+        println!(
+            "Recency: last {} {drafts}, weight ×{}",
+            recency.window,
+            recency.penalty.max(Recency::MIN_PENALTY)
+        );
+    }
+    for (label, marks) in [("Pinned", &options.pinned), ("Banned", &options.banned)] {
+        if !marks.is_empty() {
+            let names: String = marks.iter().map(String::as_str).intersperse(", ").collect();
+            println!("{label}: {names}");
+        }
+    }
+}
+
+fn print_preview(preview: &Preview) {
+    println!("Dry run, nothing was drafted");
+    for (i, mark) in preview.marks.iter().enumerate() {
+        let (name, power) = if mark.name == NO_MARK {
+            ("(placeholder)", "")
+        } else {
+            (mark.name.as_str(), mark.power.name())
+        };
+        let pool = match preview.pools.get(i) {
+            Some(Some(n)) => format!("  {n} in the pool"),
+            Some(None) => "  pinned".to_string(),
+            None => String::new(),
+        };
+        println!("  {name:<24} {power}{pool}");
+    }
+    for warning in &preview.warnings {
+        println!("Warning: {warning}");
+    }
+}
+
+/// The name of the `n`th result with its number, which commands take.
+fn result_title(n: usize, result: &DraftResult) -> String {
+    match &result.name {
+        Some(name) => format!("{name} (#{n})"),
+        None => format!("Draft #{n}"),
+    }
+}
+
+fn print_result(n: usize, result: &DraftResult) {
+    let title = result_title(n, result);
+    match &result.source {
+        Some(source) => println!("{title} (from {source})"),
+        None => println!("{title}"),
+    }
+    for (slot, mark) in result.marks.iter().enumerate() {
+        let star = if result.wishlisted.contains(&mark.name) {
+            " *"
+        } else {
+            ""
+        };
+        let fallback = match result.fallbacks.get(&slot) {
+            Some(power) if *power == mark.power => format!(" (fell back to {})", power.name()),
+            _ => String::new(),
+        };
+        println!("  {:<24} {}{star}{fallback}", mark.name, mark.power.name());
+    }
+    for line in result.note.lines() {
+        println!("  > {line}");
+    }
+}
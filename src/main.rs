@@ -2,248 +2,2508 @@
 
 use anyhow::{bail, format_err};
 use crossterm::{
-    event::{self, Event},
+    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use rand::prelude::*;
 use ratatui::backend::CrosstermBackend;
-use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, env, fs::File, io, ops::ControlFlow, path::Path};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    cmp,
+    collections::{BTreeMap, BTreeSet},
+    env, fmt, fs, io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    process,
+    rc::Rc,
+    sync::OnceLock,
+    time::{Duration, Instant, SystemTime},
+};
 
 type Terminal = ratatui::Terminal<CrosstermBackend<io::Stdout>>;
 
+/// How often the UI gets a tick for timed things like status messages.
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// The name of the placeholder mark a draw gets when no free mark matches it.
+const NO_MARK: &str = "STUPID";
+
+/// How much less likely a mark gets when it would take a lenient budget over.
+const OVER_BUDGET_WEIGHT: f64 = 0.1;
+
+mod calibrate;
+mod config;
+mod convert;
+mod debug_log;
+mod diff;
+mod discord;
+mod export;
+mod filter;
+mod format;
+mod lines;
+mod merge;
+mod migrate;
+mod picker;
+mod remote;
+mod script;
+mod snake;
+mod stats;
+mod store;
+mod strategy;
+mod theme;
 mod ui;
+mod validate;
+mod watch;
 
+use strategy::Picking;
 use ui::{Results, UiState};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct Library {
     list: Vec<(Mark, bool)>,
     categories: BTreeSet<String>,
     tags: BTreeSet<String>,
+    #[serde(default)]
+    power_weights: BTreeMap<Power, f64>,
+    #[serde(default)]
+    tiers: PowerTiers,
+    #[serde(default)]
+    power_rules: PowerRules,
+    /// Weight multipliers for marks carrying a tag.
+    #[serde(default)]
+    tag_weights: BTreeMap<String, f64>,
+    #[serde(default)]
+    recency: Recency,
+    #[serde(default)]
+    players: Vec<Player>,
+    /// Weight multiplier for marks on the assigned player's wishlist.
+    #[serde(default = "default_weight")]
+    wishlist_boost: f64,
+    /// Whether tags and categories that differ only in case are the same
+    /// one, for spreadsheets where they were typed by hand.
+    #[serde(default)]
+    ignore_case: bool,
+    #[serde(default)]
+    bags: ShuffleBags,
+    /// A script with custom draft rules, relative to the file naming it, see
+    /// [`script`].
+    #[serde(default)]
+    script: Option<String>,
+    /// The compiled `script`.
+    #[serde(skip)]
+    rules: Option<Rc<script::Rules>>,
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        Library {
+            list: Vec::new(),
+            categories: BTreeSet::new(),
+            tags: BTreeSet::new(),
+            power_weights: BTreeMap::new(),
+            tiers: PowerTiers::default(),
+            power_rules: PowerRules::default(),
+            tag_weights: BTreeMap::new(),
+            recency: Recency::default(),
+            players: Vec::new(),
+            wishlist_boost: default_weight(),
+            ignore_case: false,
+            bags: ShuffleBags::default(),
+            script: None,
+            rules: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Player {
+    name: String,
+    #[serde(default)]
+    wishlist: BTreeSet<String>,
+}
+
+/// Makes marks drawn in the last `window` drafts less likely to show up
+/// again by multiplying their weight with `penalty`.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+struct Recency {
+    window: usize,
+    penalty: f64,
+}
+
+impl Default for Recency {
+    fn default() -> Self {
+        Recency {
+            window: 0,
+            penalty: 0.5,
+        }
+    }
+}
+
+impl fmt::Display for Recency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.window, self.penalty)
+    }
+}
+
+impl Recency {
+    /// The least a penalty can multiply a weight with, so marks drawn
+    /// recently can still fill a draw nothing else fits.
+    const MIN_PENALTY: f64 = 0.01;
+
+    /// Reads `3` or `3 0.5`, the window and then the penalty, keeping the
+    /// current penalty if there's none. Blank text turns the penalty off.
+    fn parse(&self, text: &str) -> Result<Recency, String> {
+        let mut words = text.split_whitespace();
+        let error = || format!("{:?} is no recency, e.g. 3 or 3 0.5", text.trim());
+        let window = match words.next() {
+            Some(window) => window.parse().map_err(|_| error())?,
+            None => 0,
+        };
+        let penalty = match words.next() {
+            Some(penalty) => match penalty.parse() {
+                Ok(penalty) if (Recency::MIN_PENALTY..=1.0).contains(&penalty) => penalty,
+                _ => {
+                    return Err(format!(
+                        "the penalty must be between {} and 1",
+                        Recency::MIN_PENALTY
+                    ))
+                }
+            },
+            None => self.penalty,
+        };
+        if words.next().is_some() {
+            return Err(error());
+        }
+        Ok(Recency { window, penalty })
+    }
+}
+
+/// What earlier drafts tell `exec_draw` about the campaign so far.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// Everything drafted so far, which upgrade draws without a player
+    /// build on.
+    pub owned: BTreeSet<String>,
+    /// Marks drafted within the recency window.
+    pub recent: BTreeSet<String>,
+    /// What each player drafted so far, which requirements and upgrade draws
+    /// build on.
+    pub by_player: BTreeMap<String, BTreeSet<String>>,
+    /// The marks drafted since the app was started, for scripts.
+    #[cfg(feature = "scripting")]
+    session: Vec<Mark>,
+}
+
+impl History {
+    /// The marks upgrade draws build on: those of the draft's player, or
+    /// everything drafted when the draft has no player.
+    fn inventory(&self, options: &DraftOptions) -> &BTreeSet<String> {
+        static NONE: BTreeSet<String> = BTreeSet::new();
+        match &options.player {
+            Some(player) => self.by_player.get(player).unwrap_or(&NONE),
+            None => &self.owned,
+        }
+    }
+
+    /// Whether `options` leave `mark` out for an earlier draft having it.
+    fn excludes(&self, mark: &Mark, options: &DraftOptions) -> bool {
+        if mark.repeatable {
+            return false;
+        }
+        match options.exclude_drafted {
+            None => false,
+            Some(Exclusion::Campaign) => self.owned.contains(&mark.name),
+            Some(Exclusion::Player) => options
+                .player
+                .as_ref()
+                .and_then(|p| self.by_player.get(p))
+                .is_some_and(|drafted| drafted.contains(&mark.name)),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct SaveFile {
+    /// The save format version, see [`migrate`].
+    #[serde(default)]
+    version: u32,
     library: Library,
     results: Results,
+    /// Named snapshots of which marks are free, by mark name.
+    #[serde(default)]
+    presets: BTreeMap<String, BTreeSet<String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Mark {
     name: String,
     power: Power,
     category: String,
+    #[serde(default)]
     tags: BTreeSet<String>,
+    #[serde(default)]
     description: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    upgrade: Option<String>,
+    #[serde(default = "default_weight")]
+    weight: f64,
+    /// Can be drawn more than once in the same draft.
+    #[serde(default)]
+    repeatable: bool,
+    /// Marks, or tags of marks, that can't be drafted alongside this one.
+    #[serde(default)]
+    excludes: BTreeSet<String>,
+    /// Marks, or tags of marks, that must be drafted before this one can be:
+    /// earlier in the same draft or by the draft's player before.
+    #[serde(default)]
+    requires: BTreeSet<String>,
+    /// Columns of the library csv the app has no use for, kept for display.
+    #[serde(default)]
+    extra: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
-enum Power {
-    BadKarma,
-    Poor,
-    #[default]
-    Moderate,
-    Good,
-    Great,
-    Supreme,
-    Unique,
+fn default_weight() -> f64 {
+    1.0
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-struct Draw {
-    power: Option<Power>,
-    category: Option<String>,
-    tags: Vec<String>,
+impl Mark {
+    /// The name of the library the mark was loaded from.
+    fn library(&self) -> &str {
+        library_name(&self.source)
+    }
 }
 
-fn main() -> anyhow::Result<()> {
-    let arg_err = || {
-        format_err!("You need to provide a path to a library csv/saved json to run this program")
-    };
-
-    env_logger::init();
-
-    let library_file_name = env::args().nth(1).ok_or(arg_err())?;
+/// A library's name from where it was loaded: the file name without the
+/// extension, or the name of an example.
+fn library_name(source: &str) -> &str {
+    let name = source.rsplit(['/', '\\', ':']).next().unwrap_or(source);
+    match name.split_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => name,
+    }
+}
 
-    let library_file_name = Path::new(&library_file_name);
-    // this path came from a string so we unwrap directly
-    let ext = library_file_name
-        .extension()
-        .ok_or(arg_err())?
-        .to_str()
-        .unwrap();
-
-    let save: SaveFile = match ext {
-        "csv" => SaveFile::parse_library_file(&library_file_name)?,
-        "json" => {
-            let f = File::open(library_file_name)?;
-            serde_json::from_reader(f)?
+impl Default for Mark {
+    fn default() -> Self {
+        Mark {
+            name: String::new(),
+            power: Power::default(),
+            category: String::new(),
+            tags: BTreeSet::new(),
+            description: String::new(),
+            source: String::new(),
+            upgrade: None,
+            weight: default_weight(),
+            repeatable: false,
+            excludes: BTreeSet::new(),
+            requires: BTreeSet::new(),
+            extra: BTreeMap::new(),
         }
-        _ => bail!("Unknown library extension {ext}"),
-    };
+    }
+}
 
-    let mut stdout = io::stdout();
-    enable_raw_mode()?;
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+/// The name of a power tier, ranked by the library's `PowerTiers`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(transparent)]
+struct Power(String);
 
-    let res = run_eventloop(save, &mut terminal);
+impl<'de> Deserialize<'de> for Power {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        // saves from before custom tiers spell it without the space
+        Ok(match String::deserialize(d)?.as_str() {
+            "BadKarma" => Power::from("Bad Karma"),
+            name => Power::from(name),
+        })
+    }
+}
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+impl From<&str> for Power {
+    fn from(name: &str) -> Self {
+        Power(name.to_string())
+    }
+}
 
-    res
+impl Power {
+    fn name(&self) -> &str {
+        &self.0
+    }
 }
 
-fn run_eventloop(save: SaveFile, terminal: &mut Terminal) -> anyhow::Result<()> {
-    let SaveFile {
-        mut library,
-        results: past_results,
-    } = save;
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Tier {
+    name: String,
+    color: String,
+    #[serde(default)]
+    background: Option<String>,
+    rank: i32,
+    /// Points a mark of this tier costs in a budgeted draft, its rank if unset.
+    #[serde(default)]
+    cost: Option<i32>,
+    /// The tier a draw asking for exactly this one takes instead when none
+    /// of its marks are free, which may fall back further in turn.
+    #[serde(default)]
+    fallback: Option<Power>,
+}
 
-    let mut state = UiState::new(&mut library, terminal, past_results);
+/// The power tiers of a library, ordered from lowest to highest rank.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Tier>", into = "Vec<Tier>")]
+struct PowerTiers(Vec<Tier>);
 
-    state.draw()?;
+impl TryFrom<Vec<Tier>> for PowerTiers {
+    type Error = String;
 
-    loop {
-        let ev = event::read()?;
+    fn try_from(mut tiers: Vec<Tier>) -> Result<Self, Self::Error> {
+        if tiers.is_empty() {
+            return Err("at least one power tier is needed".to_string());
+        }
+        for (i, tier) in tiers.iter().enumerate() {
+            if tiers[..i].iter().any(|t| t.name == tier.name) {
+                return Err(format!("duplicate power tier {:?}", tier.name));
+            }
+            for color in [Some(&tier.color), tier.background.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                if color.parse::<ratatui::style::Color>().is_err() {
+                    return Err(format!("unknown color {color:?} for tier {:?}", tier.name));
+                }
+            }
+        }
+        for tier in &tiers {
+            // a chain ends, or it would be followed forever
+            let mut next = tier.fallback.as_ref();
+            for _ in 0..tiers.len() {
+                let Some(power) = next else {
+                    break;
+                };
+                let Some(fallback) = tiers.iter().find(|t| t.name == power.0) else {
+                    return Err(format!(
+                        "unknown fallback {:?} for tier {:?}",
+                        power.0, tier.name
+                    ));
+                };
+                next = fallback.fallback.as_ref();
+            }
+            if next.is_some() {
+                return Err(format!(
+                    "the fallbacks of tier {:?} go in a circle",
+                    tier.name
+                ));
+            }
+        }
+        tiers.sort_by_key(|t| t.rank);
+
+        Ok(PowerTiers(tiers))
+    }
+}
+
+impl From<PowerTiers> for Vec<Tier> {
+    fn from(tiers: PowerTiers) -> Self {
+        tiers.0
+    }
+}
 
-        match ev {
-            Event::Key(ev) => match state.input(ev)? {
-                ControlFlow::Break(_) => break,
-                ControlFlow::Continue(_) => {}
+impl Default for PowerTiers {
+    fn default() -> Self {
+        let tier = |name: &str, color: &str, rank| Tier {
+            name: name.to_string(),
+            color: color.to_string(),
+            background: None,
+            rank,
+            cost: None,
+            fallback: None,
+        };
+        PowerTiers(vec![
+            Tier {
+                background: Some("red".to_string()),
+                ..tier("Bad Karma", "black", 0)
             },
-            _ => {}
+            tier("Poor", "dark gray", 1),
+            tier("Moderate", "white", 2),
+            tier("Good", "green", 3),
+            tier("Great", "cyan", 4),
+            tier("Supreme", "red", 5),
+            tier("Unique", "magenta", 6),
+        ])
+    }
+}
+
+impl PowerTiers {
+    fn iter(&self) -> impl Iterator<Item = &Tier> {
+        self.0.iter()
+    }
+
+    fn powers(&self) -> Vec<Power> {
+        self.iter().map(|t| Power::from(t.name.as_str())).collect()
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|t| t.name.as_str())
+    }
+
+    fn get(&self, power: &Power) -> Option<&Tier> {
+        self.iter().find(|t| t.name == power.0)
+    }
+
+    fn power(&self, name: &str) -> Option<Power> {
+        self.get(&Power::from(name)).map(|t| Power(t.name.clone()))
+    }
+
+    fn rank(&self, power: &Power) -> Option<i32> {
+        self.get(power).map(|t| t.rank)
+    }
+
+    /// The points a mark of `power` costs, see [`Tier::cost`].
+    fn cost(&self, power: &Power) -> i32 {
+        self.get(power).map_or(0, |t| t.cost.unwrap_or(t.rank))
+    }
+
+    /// The powers a draw asking for exactly `power` falls back to in turn.
+    fn fallbacks(&self, power: &Power) -> Vec<Power> {
+        let mut chain: Vec<Power> = Vec::new();
+        let mut next = self.get(power).and_then(|t| t.fallback.clone());
+        while let Some(power) = next.filter(|p| !chain.contains(p)) {
+            next = self.get(&power).and_then(|t| t.fallback.clone());
+            chain.push(power);
         }
+        chain
+    }
 
-        state.draw()?;
+    /// Whether any tier sets its cost rather than going by its rank.
+    fn has_costs(&self) -> bool {
+        self.iter().any(|t| t.cost.is_some())
     }
 
-    Ok(())
+    /// The most a mark allowed by `constraint` can cost.
+    fn max_cost(&self, constraint: Option<&PowerConstraint>) -> i32 {
+        self.iter()
+            .map(|t| Power::from(t.name.as_str()))
+            .filter(|p| constraint.is_none_or(|c| c.matches(p, &BTreeSet::new(), self)))
+            .map(|p| self.cost(&p))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Checks that every mark has a power from these tiers.
+    fn check(&self, lib: &Library) -> anyhow::Result<()> {
+        for (mark, _) in &lib.list {
+            if self.get(&mark.power).is_none() {
+                let names: String = self.names().intersperse(", ").collect();
+                bail!(
+                    "Mark {:?} has unknown power {:?}, the tiers are {names}",
+                    mark.name,
+                    mark.power.name()
+                );
+            }
+        }
+        Ok(())
+    }
 }
 
-impl Library {
-    pub fn exec_draw(&mut self, draws: Vec<Draw>, rng: &mut ThreadRng) -> Vec<Mark> {
-        let mut pool = Vec::new();
+/// Powers that also satisfy a draw asking for exactly some other power. Each
+/// substitute is allowed in a draw with its own chance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct PowerRules(BTreeMap<Power, Vec<Substitute>>);
 
-        let mut marks: Vec<Mark> = Vec::new();
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Substitute {
+    power: Power,
+    #[serde(default = "default_chance")]
+    chance: f64,
+}
 
-        for draw in draws {
-            'mark: for (mark, free) in &self.list {
-                if !free {
-                    continue;
-                }
-                if draw.power.as_ref().is_some_and(|p| match (*p, mark.power) {
-                    (x, y) if x == y => false,
-                    (Power::BadKarma, Power::Poor | Power::Moderate) => false,
-                    _ => true,
-                }) {
-                    continue;
-                }
-                if draw.category.as_ref().is_some_and(|c| &mark.category != c) {
-                    continue;
-                }
-                for tag in &draw.tags {
-                    if !mark.tags.contains(tag) {
-                        continue 'mark;
-                    }
-                }
-                if marks.iter().find(|m| m.name == mark.name).is_some() {
-                    continue;
-                }
+fn default_chance() -> f64 {
+    1.0
+}
 
-                pool.push(mark);
+impl Default for PowerRules {
+    fn default() -> Self {
+        let substitute = |power| Substitute {
+            power: Power::from(power),
+            chance: default_chance(),
+        };
+        PowerRules(BTreeMap::from([(
+            Power::from("Bad Karma"),
+            vec![substitute("Poor"), substitute("Moderate")],
+        )]))
+    }
+}
+
+impl PowerRules {
+    /// Rolls which substitutes a draw with the `power` constraint accepts.
+    fn roll(&self, power: Option<&PowerConstraint>, rng: &mut impl Rng) -> BTreeSet<Power> {
+        let Some(PowerConstraint::Exact(p)) = power else {
+            return BTreeSet::new();
+        };
+        self.0
+            .get(p)
+            .into_iter()
+            .flatten()
+            .filter(|s| rng.gen_bool(s.chance.clamp(0.0, 1.0)))
+            .map(|s| s.power.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+enum PowerConstraint {
+    Exact(Power),
+    AtLeast(Power),
+    AtMost(Power),
+    Range(Power, Power),
+}
+
+impl PowerConstraint {
+    /// `substitutes` are the powers an exact constraint accepts besides its
+    /// own, see `PowerRules`.
+    fn matches(&self, power: &Power, substitutes: &BTreeSet<Power>, tiers: &PowerTiers) -> bool {
+        let rank = |p| tiers.rank(p);
+        match self {
+            PowerConstraint::Exact(p) => p == power || substitutes.contains(power),
+            PowerConstraint::AtLeast(p) => {
+                matches!((rank(power), rank(p)), (Some(r), Some(lo)) if r >= lo)
             }
+            PowerConstraint::AtMost(p) => {
+                matches!((rank(power), rank(p)), (Some(r), Some(hi)) if r <= hi)
+            }
+            PowerConstraint::Range(lo, hi) => matches!(
+                (rank(power), rank(lo), rank(hi)),
+                (Some(r), Some(lo), Some(hi)) if (lo..=hi).contains(&r)
+            ),
+        }
+    }
 
-            let choice = pool.choose(rng).map(|m| (**m).clone()).unwrap_or(Mark {
-                name: "STUPID".to_string(),
-                power: Power::Poor,
-                ..Default::default()
-            });
-            marks.push(choice);
-            pool.clear()
+    fn next_mode(self) -> Self {
+        match self {
+            PowerConstraint::Exact(p) => PowerConstraint::AtLeast(p),
+            PowerConstraint::AtLeast(p) => PowerConstraint::AtMost(p),
+            PowerConstraint::AtMost(p) => PowerConstraint::Range(p.clone(), p),
+            PowerConstraint::Range(lo, _) => PowerConstraint::Exact(lo),
+        }
+    }
+
+    fn bound(&self, upper: bool) -> &Power {
+        match self {
+            PowerConstraint::Range(_, hi) if upper => hi,
+            PowerConstraint::Exact(p)
+            | PowerConstraint::AtLeast(p)
+            | PowerConstraint::AtMost(p)
+            | PowerConstraint::Range(p, _) => p,
+        }
+    }
+
+    /// The power edited by the draft editor, `upper` picks the top of a range.
+    fn bound_mut(&mut self, upper: bool) -> &mut Power {
+        match self {
+            PowerConstraint::Range(_, hi) if upper => hi,
+            PowerConstraint::Exact(p)
+            | PowerConstraint::AtLeast(p)
+            | PowerConstraint::AtMost(p)
+            | PowerConstraint::Range(p, _) => p,
         }
+    }
 
-        marks
+    /// Sets the power `bound_mut` edits, dragging the other bound of a range
+    /// along if they would end up the wrong way round.
+    fn set_bound(&mut self, upper: bool, power: Power, tiers: &PowerTiers) {
+        *self.bound_mut(upper) = power;
+        if let PowerConstraint::Range(lo, hi) = self {
+            if tiers.rank(lo) > tiers.rank(hi) {
+                if upper {
+                    *lo = hi.clone();
+                } else {
+                    *hi = lo.clone();
+                }
+            }
+        }
     }
 }
 
-impl SaveFile {
-    fn parse_library_file<S: AsRef<Path>>(path: S) -> anyhow::Result<Self> {
-        // NAME,POWER,CATEGORY,TAG,TAG,DESCRIPTION
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Draw {
+    #[serde(default)]
+    power: Option<PowerConstraint>,
+    category: Option<String>,
+    // groups are AND'd together, the tags within a group are OR'd
+    tags: Vec<Vec<String>>,
+    #[serde(default)]
+    upgrade: bool,
+    /// Only marks of this library, see [`Mark::library`].
+    #[serde(default)]
+    library: Option<String>,
+    /// Only marks the expression matches, on top of the fields above.
+    #[serde(default)]
+    filter: Option<filter::Filter>,
+}
 
-        let mut rdr = csv::Reader::from_path(path)?;
-        let tag_count = rdr.headers()?.iter().filter(|f| f == &"TAG").count();
-        let mut v = Vec::new();
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DraftOptions {
+    /// Marks that always end up in the draft.
+    #[serde(default)]
+    pinned: BTreeSet<String>,
+    /// Marks that never end up in the draft.
+    #[serde(default)]
+    banned: BTreeSet<String>,
+    /// No two marks of the draft may share a category.
+    #[serde(default)]
+    unique_categories: bool,
+    /// The player the draft is for.
+    #[serde(default)]
+    player: Option<String>,
+    /// The most points the marks of the draft should cost together.
+    #[serde(default)]
+    budget: Option<Budget>,
+    /// The name of the draw strategy, see [`strategy::get`].
+    #[serde(default)]
+    strategy: Option<String>,
+    /// Leave out the marks earlier drafts took, repeatable ones aside.
+    #[serde(default)]
+    exclude_drafted: Option<Exclusion>,
+}
 
-        let mut categories = BTreeSet::new();
-        let mut all_tags = BTreeSet::new();
+/// Whose earlier drafts take marks out of a draft, for campaigns where a
+/// drafted mark is gone for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Exclusion {
+    /// Any draft of the campaign.
+    Campaign,
+    /// The drafts of the draft's player, a draft without one excludes none.
+    Player,
+}
 
-        for result in rdr.into_records() {
-            use Power as P;
-
-            let record = result?;
-            let mut fields = record.iter();
-            let mut next = || {
-                fields
-                    .next()
-                    .ok_or(anyhow::Error::msg("Malformed library csv"))
-            };
+impl Exclusion {
+    /// No exclusion, then the campaign, then the player, and around again.
+    fn next(exclusion: Option<Exclusion>) -> Option<Exclusion> {
+        match exclusion {
+            None => Some(Exclusion::Campaign),
+            Some(Exclusion::Campaign) => Some(Exclusion::Player),
+            Some(Exclusion::Player) => None,
+        }
+    }
 
-            let name = next()?.to_string();
-            let power = match next()? {
-                "Poor" => P::Poor,
-                "Moderate" => P::Moderate,
-                "Good" => P::Good,
-                "Great" => P::Great,
-                "Supreme" => P::Supreme,
-                "Unique" => P::Unique,
-                "Bad Karma" => P::BadKarma,
-                e => bail!("Unknown power level {:?}", e),
-            };
+    fn label(self) -> &'static str {
+        match self {
+            Exclusion::Campaign => "No marks drafted before",
+            Exclusion::Player => "No marks the player drafted before",
+        }
+    }
+}
+
+/// The marks of each tier that bag drafts haven't drawn yet this round. A
+/// tier without a bag has all of its marks left.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct ShuffleBags(BTreeMap<Power, BTreeSet<String>>);
+
+impl ShuffleBags {
+    fn contains(&self, mark: &Mark) -> bool {
+        self.0
+            .get(&mark.power)
+            .is_none_or(|bag| bag.contains(&mark.name))
+    }
+
+    /// Takes `mark` out of the bag of its tier. Emptying the bag starts the
+    /// tier's next round, as does drawing a mark that was taken already,
+    /// which happens when nothing left in the bag fit the draw.
+    fn take(&mut self, mark: &Mark, list: &[(Mark, bool)]) {
+        if mark.name == NO_MARK {
+            return;
+        }
+        let all = || {
+            list.iter()
+                .filter(|(m, _)| m.power == mark.power)
+                .map(|(m, _)| m.name.clone())
+                .collect()
+        };
+        let bag = self.0.entry(mark.power.clone()).or_insert_with(all);
+        if !bag.remove(&mark.name) {
+            *bag = all();
+            bag.remove(&mark.name);
+        }
+        if bag.is_empty() {
+            self.0.remove(&mark.power);
+        }
+    }
+}
+
+/// A cap on the summed [`PowerTiers::cost`] of a draft's marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Budget {
+    points: i32,
+    /// Never go over, rather than only making marks that would go over less likely.
+    #[serde(default)]
+    strict: bool,
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.points, if self.strict { "!" } else { "" })
+    }
+}
+
+impl Budget {
+    /// Reads `12`, or `12!` for a strict budget. Blank text is no budget.
+    fn parse(text: &str) -> Result<Option<Budget>, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let (points, strict) = match text.strip_suffix('!') {
+            Some(points) => (points.trim(), true),
+            None => (text, false),
+        };
+        match points.parse() {
+            Ok(points) if points >= 0 => Ok(Some(Budget { points, strict })),
+            _ => Err(format!("{text:?} is no budget, e.g. 12 or 12!")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DraftResult {
+    marks: Vec<Mark>,
+    draws: Vec<Draw>,
+    #[serde(default)]
+    options: DraftOptions,
+    /// Drawn marks that were on the player's wishlist.
+    #[serde(default)]
+    wishlisted: BTreeSet<String>,
+    /// Free-text annotation, e.g. who drafted it in which session.
+    #[serde(default)]
+    note: String,
+    /// Seed of the rng the marks were drawn with, or the shared pool of a
+    /// snake draft rolled with.
+    #[serde(default)]
+    seed: Option<u64>,
+    /// The marks that weren't free when the draft was made, by name. Marks
+    /// added to the library since count as free.
+    #[serde(default)]
+    unavailable: BTreeSet<String>,
+    /// Picked by hand in a snake draft, which its seed can't replay.
+    #[serde(default)]
+    snake: bool,
+    /// Slots that were re-rolled with the mark they held before, latest last.
+    #[serde(default)]
+    rerolls: Vec<(usize, Mark)>,
+    /// When the draft was made, in seconds since the unix epoch.
+    #[serde(default)]
+    timestamp: Option<u64>,
+    /// The save a result was merged in from, to tell the results of
+    /// different hosts apart.
+    #[serde(default)]
+    source: Option<String>,
+    /// The shuffle bags before a bag draft, to replay and undo it with.
+    #[serde(default)]
+    bags: Option<ShuffleBags>,
+    /// Slots whose power had no free marks, with the power they fell back to.
+    #[serde(default)]
+    fallbacks: BTreeMap<usize, Power>,
+    /// The session of the campaign it was drafted in, results are listed
+    /// under their session.
+    #[serde(default)]
+    session: Option<String>,
+    /// Left out of the results list unless archived results are shown. It
+    /// still counts for the history.
+    #[serde(default)]
+    archived: bool,
+    /// Given when the draft was executed, listed instead of its number.
+    #[serde(default)]
+    name: Option<String>,
+}
+
+impl DraftResult {
+    /// The name of the `n`th result, or `Draft #n` if it has none.
+    fn title(&self, n: usize) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("Draft #{n}"),
+        }
+    }
+
+    /// Whether `mark` was free when the draft was made.
+    fn was_free(&self, mark: &Mark) -> bool {
+        !self.unavailable.contains(&mark.name)
+    }
+}
+
+/// What a draft would draw, drawn without taking it, see [`Library::preview`].
+#[derive(Debug, Clone, Default)]
+struct Preview {
+    marks: Vec<Mark>,
+    /// How many marks each draw could be drawn from, `None` for pinned ones.
+    pools: Vec<Option<usize>>,
+    /// Draws that fell back to another power, see [`Tier::fallback`].
+    fallbacks: BTreeMap<usize, Power>,
+    /// What didn't go as the draws ask, e.g. draws that got a placeholder.
+    warnings: Vec<String>,
+}
+
+/// The current time in seconds since the unix epoch, for timestamping drafts.
+fn unix_time() -> Option<u64> {
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+    now.ok().map(|d| d.as_secs())
+}
+
+/// When the app was started, drafts since then are of this session.
+fn started() -> Option<u64> {
+    static STARTED: OnceLock<Option<u64>> = OnceLock::new();
+    *STARTED.get_or_init(unix_time)
+}
+
+impl Draw {
+    /// Checks the power, category, library, tag and filter constraints of
+    /// this draw.
+    fn matches(&self, mark: &Mark, substitutes: &BTreeSet<Power>, lib: &Library) -> bool {
+        self.power
+            .as_ref()
+            .is_none_or(|p| p.matches(&mark.power, substitutes, &lib.tiers))
+            && self
+                .category
+                .as_ref()
+                .is_none_or(|c| lib.same_label(&mark.category, c))
+            && self.library.as_ref().is_none_or(|l| mark.library() == l)
+            && self
+                .tags
+                .iter()
+                .all(|group| group.iter().any(|t| lib.has_tag_under(mark, t)))
+            && self.filter.as_ref().is_none_or(|f| f.matches(mark, lib))
+    }
 
-            let category = next()?.to_string();
-            if !categories.contains(&category) && category != "" {
-                categories.insert(category.clone());
+    fn tag_count(&self) -> usize {
+        self.tags.iter().map(Vec::len).sum()
+    }
+
+    fn all_tags(&self) -> impl Iterator<Item = &String> {
+        self.tags.iter().flatten()
+    }
+
+    /// Group and index within that group of the `n`th tag.
+    fn tag_position(&self, mut n: usize) -> (usize, usize) {
+        for (g, group) in self.tags.iter().enumerate() {
+            if n < group.len() {
+                return (g, n);
             }
+            n -= group.len();
+        }
+        panic!("Tag index out of range")
+    }
 
-            let mut tags = BTreeSet::new();
-            for _ in 0..tag_count {
-                match next()? {
-                    "" => continue,
-                    t => {
-                        tags.insert(t.to_string());
-                        if !all_tags.contains(t) {
-                            all_tags.insert(t.to_string());
-                        }
-                    }
+    fn tag_mut(&mut self, n: usize) -> &mut String {
+        let (g, i) = self.tag_position(n);
+        &mut self.tags[g][i]
+    }
+
+    fn remove_tag(&mut self, n: usize) {
+        let (g, i) = self.tag_position(n);
+        self.tags[g].remove(i);
+        if self.tags[g].is_empty() {
+            self.tags.remove(g);
+        }
+    }
+
+    /// Replaces a renamed tag, a group that has both keeps just one. Tags
+    /// are compared like `lib` does, see [`Library::same_label`].
+    fn rename_tag(&mut self, from: &str, to: &str, lib: &Library) {
+        for group in &mut self.tags {
+            if let Some(i) = group.iter().position(|t| lib.same_label(t, from)) {
+                if group.iter().any(|t| t == to) {
+                    group.remove(i);
+                } else {
+                    group[i] = to.to_string();
+                }
+            }
+        }
+    }
+
+    /// Replaces a renamed category, a draw of a category deleted without
+    /// moving its marks elsewhere draws from any category.
+    fn rename_category(&mut self, from: &str, to: &str, lib: &Library) {
+        if self
+            .category
+            .as_ref()
+            .is_some_and(|c| lib.same_label(c, from))
+        {
+            self.category = (!to.is_empty()).then(|| to.to_string());
+        }
+    }
+
+    /// Drops the power, category, library and tags `lib` doesn't know, e.g.
+    /// of a draw copied from a result drafted before a label was deleted.
+    /// Labels spelled differently are changed to the library's spelling.
+    fn repair(&mut self, lib: &Library) {
+        let label = |name: &String, labels: &BTreeSet<String>| {
+            labels.iter().find(|l| lib.same_label(l, name)).cloned()
+        };
+        if let Some(power) = &self.power {
+            if [false, true]
+                .iter()
+                .any(|&upper| lib.tiers.rank(power.bound(upper)).is_none())
+            {
+                self.power = None;
+            }
+        }
+        self.category = self
+            .category
+            .as_ref()
+            .and_then(|c| label(c, &lib.categories));
+        if let Some(library) = &self.library {
+            if !lib.libraries().contains(library) {
+                self.library = None;
+            }
+        }
+        for group in &mut self.tags {
+            let mut known: Vec<String> = Vec::new();
+            for tag in group.iter().filter_map(|t| label(t, &lib.tags)) {
+                if !known.contains(&tag) {
+                    known.push(tag);
                 }
             }
+            *group = known;
+        }
+        self.tags.retain(|group| !group.is_empty());
+    }
 
-            let description = next()?.to_string();
+    /// Toggles whether the `n`th tag is OR'd with the one before it.
+    fn toggle_tag_or(&mut self, n: usize) {
+        let (g, i) = self.tag_position(n);
+        if i > 0 {
+            let rest = self.tags[g].split_off(i);
+            self.tags.insert(g + 1, rest);
+        } else if g > 0 {
+            let group = self.tags.remove(g);
+            self.tags[g - 1].extend(group);
+        }
+    }
+}
 
-            let mark = Mark {
-                name,
-                power,
-                category,
-                tags,
-                description,
-            };
+#[cfg(feature = "examples")]
+const EXAMPLES: &[(&str, &str)] = &[
+    ("fantasy", include_str!("examples/fantasy.toml")),
+    ("scifi", include_str!("examples/scifi.toml")),
+];
 
-            v.push((mark, true));
+fn main() -> anyhow::Result<()> {
+    started();
+    let arg_err = || {
+        format_err!(
+            "You need to provide a path to a library csv/toml/saved json to run this program"
+        )
+    };
+
+    let mut lenient = false;
+    let mut no_tui = false;
+    let mut library_file_names = Vec::new();
+    let mut examples = Vec::new();
+    let mut args = env::args().skip(1).peekable();
+    if args.next_if_eq("convert").is_some() {
+        env_logger::init();
+        return convert::run(args);
+    }
+    if args.next_if_eq("diff").is_some() {
+        env_logger::init();
+        if !diff::run(args)? {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if args.next_if_eq("merge").is_some() {
+        env_logger::init();
+        return merge::run(args);
+    }
+    if args.next_if_eq("stats").is_some() {
+        env_logger::init();
+        return stats::run(args);
+    }
+    if args.next_if_eq("validate").is_some() {
+        if !validate::run(args)? {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lenient" => lenient = true,
+            "--no-tui" => no_tui = true,
+            "--example" => examples.push(args.next().ok_or(format_err!(
+                "--example needs the name of an example library"
+            ))?),
+            a if a.starts_with("--") => bail!("Unknown option {a}"),
+            _ => library_file_names.push(PathBuf::from(arg)),
         }
+    }
 
-        Ok(SaveFile {
-            library: Library {
-                list: v,
-                categories,
-                tags: all_tags,
-            },
-            ..Default::default()
-        })
+    // without a file the TUI lets the user pick one
+    let picking = library_file_names.is_empty() && examples.is_empty();
+    if picking && no_tui {
+        return Err(arg_err());
+    }
+
+    // stderr is only free to log to when the TUI isn't running
+    if no_tui {
+        env_logger::init();
+    } else {
+        debug_log::init();
+    }
+
+    let paths = library_paths(library_file_names)?;
+
+    let mut save = load_library_files(&paths, lenient)?;
+    for name in &examples {
+        save.library.merge(load_example(name)?.library)?;
+    }
+
+    if no_tui {
+        return lines::run(save);
+    }
+
+    let mut stdout = io::stdout();
+    enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let res = (|| {
+        let (mut paths, mut save) = if picking {
+            // the picker comes up before the session loads the theme
+            theme::load(config::Config::load().theme.as_deref());
+            let open = |path: &Path| load_library_file(path, lenient);
+            match picker::pick(&mut terminal, "quit", open)? {
+                Some((path, save)) => (vec![path], save),
+                None => return Ok(()),
+            }
+        } else {
+            (paths, save)
+        };
+        let mut examples = examples;
+
+        // a file opened from within the app replaces the session with a new one
+        loop {
+            let source = match (paths.as_slice(), examples.as_slice()) {
+                ([path], []) => path.display().to_string(),
+                ([], [name]) => format!("example {name}"),
+                (paths, examples) => format!("{} libraries", paths.len() + examples.len()),
+            };
+            if let ([path], []) = (paths.as_slice(), examples.as_slice()) {
+                if path.is_file() && !remote::is_cached(path) {
+                    config::Config::remember(path);
+                }
+            }
+            // only saves have a version, a library file is never quick-saved over,
+            // nor a fetched copy that is replaced on the next fetch
+            let save_path = match (paths.as_slice(), examples.as_slice()) {
+                ([path], []) if save.version != 0 && !remote::is_cached(path) => Some(path.clone()),
+                _ => None,
+            };
+
+            // examples can't change, so only sessions of files alone are watched
+            let watched = if examples.is_empty() {
+                paths.as_slice()
+            } else {
+                &[]
+            };
+
+            match run_eventloop(save, source, save_path, watched, &mut terminal)? {
+                Some((path, opened)) => {
+                    paths = vec![path];
+                    examples.clear();
+                    save = opened;
+                }
+                None => return Ok(()),
+            }
+        }
+    })();
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
+    terminal.show_cursor()?;
+
+    res
+}
+
+/// The given paths with directories replaced by the library files in them,
+/// and URLs by the fetched copies of the libraries there.
+fn library_paths(names: Vec<PathBuf>) -> anyhow::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for path in names {
+        if let Some(url) = path.to_str().filter(|p| remote::is_url(p)) {
+            paths.push(remote::fetch(url)?);
+        } else if path.is_dir() {
+            let mut entries = fs::read_dir(&path)?
+                .map(|e| e.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.retain(|p| format::is_library(p));
+            entries.sort();
+            if entries.is_empty() {
+                bail!("No library files found in {}", path.display());
+            }
+            paths.extend(entries);
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Loads every file and merges them into one save.
+fn load_library_files(paths: &[PathBuf], lenient: bool) -> anyhow::Result<SaveFile> {
+    let mut save = SaveFile::default();
+    for path in paths {
+        let file = load_library_file(path, lenient)?;
+        save.library.merge(file.library)?;
+        save.results.extend(file.results);
+        save.presets.extend(file.presets);
+        save.version = save.version.max(file.version);
+    }
+    Ok(save)
+}
+
+fn load_library_file(path: &Path, lenient: bool) -> anyhow::Result<SaveFile> {
+    let (mut save, errors) = read_library_file(path, lenient)?;
+    if !errors.is_empty() {
+        log::warn!(
+            "Skipped {} malformed row(s) in {}:",
+            errors.len(),
+            path.display()
+        );
+        for e in &errors {
+            log::warn!("  {e}");
+        }
+    }
+    save.library.tiers.check(&save.library)?;
+    if let Some(script) = &save.library.script {
+        let script = path.parent().unwrap_or(Path::new("")).join(script);
+        save.library.rules = Some(Rc::new(script::Rules::load(&script)?));
+    }
+
+    Ok(save)
+}
+
+/// Reads a library or save without checking its marks against the tiers,
+/// with the rows of a csv that were skipped in `lenient` mode.
+fn read_library_file(path: &Path, lenient: bool) -> anyhow::Result<(SaveFile, Vec<ParseError>)> {
+    let arg_err = || format_err!("{} is not a library csv/toml/saved json", path.display());
+
+    // this path came from a string so we unwrap directly
+    let ext = path.extension().ok_or(arg_err())?.to_str().unwrap();
+
+    let (mut save, errors) = match ext {
+        "csv" => {
+            // tiers for a csv library live next to it in NAME.tiers.toml
+            let tiers_path = path.with_extension("tiers.toml");
+            let TiersToml {
+                tiers,
+                ignore_case,
+                script,
+            } = if tiers_path.exists() {
+                toml::from_str(&fs::read_to_string(&tiers_path)?)?
+            } else {
+                TiersToml::default()
+            };
+            let (mut save, errors) = SaveFile::parse_library_file(path, lenient, tiers)?;
+            save.library.ignore_case = ignore_case;
+            save.library.script = script;
+            (save, errors)
+        }
+        "json" | "gz" | "yaml" | "yml" | "ron" | "db" => (migrate::load(path)?, Vec::new()),
+        "toml" => {
+            let text = fs::read_to_string(path)?;
+            if format::is_toml_save(&text)? {
+                (migrate::load(path)?, Vec::new())
+            } else {
+                (SaveFile::parse_library_toml(&text)?, Vec::new())
+            }
+        }
+        _ => bail!("Unknown library extension {ext}"),
+    };
+
+    save.library.set_source(&path.display().to_string());
+
+    Ok((save, errors))
+}
+
+#[cfg(feature = "examples")]
+fn load_example(name: &str) -> anyhow::Result<SaveFile> {
+    let Some((_, text)) = EXAMPLES.iter().find(|(n, _)| *n == name) else {
+        let names: String = EXAMPLES.iter().map(|(n, _)| *n).intersperse(", ").collect();
+        bail!("Unknown example library {name:?}, available are {names}");
+    };
+
+    let mut save = SaveFile::parse_library_toml(text)?;
+    save.library.tiers.check(&save.library)?;
+    save.library.set_source(&format!("example:{name}"));
+
+    Ok(save)
+}
+
+#[cfg(not(feature = "examples"))]
+fn load_example(_: &str) -> anyhow::Result<SaveFile> {
+    bail!("This build does not include the example libraries")
+}
+
+/// Runs a session until it is quit, or closed to open the file it returns.
+fn run_eventloop(
+    save: SaveFile,
+    source: String,
+    path: Option<PathBuf>,
+    watched: &[PathBuf],
+    terminal: &mut Terminal,
+) -> anyhow::Result<Option<(PathBuf, SaveFile)>> {
+    let SaveFile {
+        mut library,
+        results: past_results,
+        presets,
+        ..
+    } = save;
+
+    let mut config = config::Config::load();
+    theme::load(config.theme.as_deref());
+    let whats_new = config.unseen_releases();
+    config.mark_seen();
+    if let Err(e) = config.save() {
+        log::warn!("Could not save the config: {e}");
+    }
+
+    let mut state = UiState::new(&mut library, terminal, past_results, presets);
+    state.whats_new = whats_new;
+    state.file = source;
+    state.path = path;
+    state.watcher = watch::Watcher::new(watched);
+
+    state.draw()?;
+
+    let mut last_tick = Instant::now();
+    loop {
+        // wait for input until the next tick is due
+        if event::poll(TICK_RATE.saturating_sub(last_tick.elapsed()))? {
+            match event::read()? {
+                Event::Key(ev) => match state.input(ev) {
+                    ControlFlow::Break(_) => break,
+                    ControlFlow::Continue(_) => {}
+                },
+                Event::Paste(text) => state.paste(&text),
+                Event::Resize(width, height) => state.resize(width, height)?,
+                _ => {}
+            }
+
+            state.draw()?;
+        }
+
+        if last_tick.elapsed() >= TICK_RATE {
+            last_tick = Instant::now();
+            if state.tick() {
+                state.draw()?;
+            }
+        }
+    }
+
+    Ok(state.opened.take())
+}
+
+impl Library {
+    pub fn from_marks(marks: Vec<Mark>) -> Library {
+        Library {
+            categories: marks
+                .iter()
+                .map(|m| m.category.clone())
+                .filter(|c| !c.is_empty())
+                .collect(),
+            tags: marks.iter().flat_map(|m| m.tags.iter().cloned()).collect(),
+            list: marks.into_iter().map(|m| (m, true)).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn set_source(&mut self, source: &str) {
+        for (mark, _) in &mut self.list {
+            if mark.source.is_empty() {
+                mark.source = source.to_string();
+            }
+        }
+    }
+
+    /// The libraries the marks were loaded from, in the order they were.
+    fn libraries(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for (mark, _) in &self.list {
+            let name = mark.library();
+            if !name.is_empty() && !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// Appends another library's marks. A mark sharing its name with one of a
+    /// different library is namespaced as `library:NAME`, one sharing it with
+    /// a mark of the same library is refused.
+    pub fn merge(&mut self, other: Library) -> anyhow::Result<()> {
+        if self.list.is_empty() {
+            self.tiers = other.tiers.clone();
+        } else if self.tiers != other.tiers {
+            let source = other.list.first().map_or("", |(m, _)| m.source.as_str());
+            bail!("{source} uses different power tiers than the libraries before it");
+        }
+
+        for (mut mark, free) in other.list {
+            let clash = self.list.iter().find(|(m, _)| m.name == mark.name);
+            if clash.is_some_and(|(m, _)| m.library() != mark.library()) {
+                mark.name = format!("{}:{}", mark.library(), mark.name);
+            }
+            if let Some((existing, _)) = self.list.iter().find(|(m, _)| m.name == mark.name) {
+                bail!(
+                    "Duplicate mark {:?} in {} (already defined in {})",
+                    mark.name,
+                    mark.source,
+                    existing.source
+                );
+            }
+            self.list.push((mark, free));
+        }
+
+        self.categories.extend(other.categories);
+        self.tags.extend(other.tags);
+        self.ignore_case |= other.ignore_case;
+        if self.rules.is_none() {
+            self.script = other.script;
+            self.rules = other.rules;
+        }
+        if self.recency.window == 0 {
+            self.recency = other.recency;
+        }
+        if self.wishlist_boost == 1.0 {
+            self.wishlist_boost = other.wishlist_boost;
+        }
+        for player in other.players {
+            self.player_mut(&player.name)
+                .wishlist
+                .extend(player.wishlist);
+        }
+        if self.power_rules == PowerRules::default() {
+            self.power_rules = other.power_rules;
+        }
+        for (tag, weight) in other.tag_weights {
+            self.tag_weights.entry(tag).or_insert(weight);
+        }
+        for (power, weight) in other.power_weights {
+            self.power_weights.entry(power).or_insert(weight);
+        }
+        self.fold_case();
+
+        Ok(())
+    }
+
+    pub fn player(&self, name: &str) -> Option<&Player> {
+        self.players.iter().find(|p| p.name == name)
+    }
+
+    pub fn player_mut(&mut self, name: &str) -> &mut Player {
+        match self.players.iter().position(|p| p.name == name) {
+            Some(i) => &mut self.players[i],
+            None => {
+                self.players.push(Player {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                self.players.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Marks on the wishlist of the player a draft is assigned to.
+    pub fn wishlist(&self, options: &DraftOptions) -> Option<&BTreeSet<String>> {
+        let player = self.player(options.player.as_ref()?)?;
+        Some(&player.wishlist)
+    }
+
+    /// The names of `marks` that are on the wishlist of the draft's player.
+    pub fn wishlisted(&self, options: &DraftOptions, marks: &[Mark]) -> BTreeSet<String> {
+        match self.wishlist(options) {
+            Some(wishlist) => marks
+                .iter()
+                .filter(|m| wishlist.contains(&m.name))
+                .map(|m| m.name.clone())
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+
+    pub fn power_weight(&self, power: &Power) -> f64 {
+        self.power_weights.get(power).copied().unwrap_or(1.0)
+    }
+
+    pub fn weight(&self, mark: &Mark, history: &History) -> f64 {
+        let mut weight = self.power_weight(&mark.power) * mark.weight;
+        for tag in &mark.tags {
+            weight *= self.tag_weight(tag);
+        }
+        if history.recent.contains(&mark.name) {
+            weight *= self.recency.penalty.max(Recency::MIN_PENALTY);
+        }
+        weight
+    }
+
+    /// Renames a tag on every mark, merging it into `to` if that is a tag
+    /// already. Returns how many marks had it.
+    pub fn rename_tag(&mut self, from: &str, to: &str) -> usize {
+        let mut renamed = 0;
+        let mut list = std::mem::take(&mut self.list);
+        for (mark, _) in &mut list {
+            let had = mark.tags.len();
+            mark.tags.retain(|t| !self.same_label(t, from));
+            if mark.tags.len() < had {
+                mark.tags.insert(to.to_string());
+                renamed += 1;
+            }
+        }
+        self.list = list;
+        let mut tags = std::mem::take(&mut self.tags);
+        tags.retain(|t| !self.same_label(t, from));
+        tags.insert(to.to_string());
+        self.tags = tags;
+        // a weight set for the tag merged into wins
+        let weight = self
+            .tag_weights
+            .keys()
+            .find(|t| self.same_label(t, from))
+            .cloned()
+            .and_then(|t| self.tag_weights.remove(&t));
+        if let Some(weight) = weight {
+            self.tag_weights.entry(to.to_string()).or_insert(weight);
+        }
+        renamed
+    }
+
+    /// Moves every mark of a category to `to`, merging the two if `to` is a
+    /// category already. An empty `to` leaves the marks without a category.
+    /// Returns how many marks moved.
+    pub fn rename_category(&mut self, from: &str, to: &str) -> usize {
+        let mut moved = 0;
+        let mut list = std::mem::take(&mut self.list);
+        for (mark, _) in &mut list {
+            if self.same_label(&mark.category, from) {
+                mark.category = to.to_string();
+                moved += 1;
+            }
+        }
+        self.list = list;
+        let mut categories = std::mem::take(&mut self.categories);
+        categories.retain(|c| !self.same_label(c, from));
+        self.categories = categories;
+        if !to.is_empty() {
+            self.categories.insert(to.to_string());
+        }
+        moved
+    }
+
+    /// Whether two tags or categories are the same one, see `ignore_case`.
+    pub fn same_label(&self, a: &str, b: &str) -> bool {
+        a == b
+            || self.ignore_case
+                && a.chars()
+                    .flat_map(char::to_lowercase)
+                    .eq(b.chars().flat_map(char::to_lowercase))
+    }
+
+    pub fn has_tag(&self, mark: &Mark, tag: &str) -> bool {
+        mark.tags.contains(tag)
+            || self.ignore_case && mark.tags.iter().any(|t| self.same_label(t, tag))
+    }
+
+    /// Whether `mark` has `tag` or a tag under it in the hierarchy, e.g.
+    /// `Weapon/Melee/Sword` for `Weapon`.
+    pub fn has_tag_under(&self, mark: &Mark, tag: &str) -> bool {
+        self.has_tag(mark, tag)
+            || mark.tags.iter().any(|t| {
+                t.get(..tag.len())
+                    .is_some_and(|parent| self.same_label(parent, tag))
+                    && t[tag.len()..].starts_with('/')
+            })
+    }
+
+    /// Whether `a` and `b` can't be in one draft because one of them excludes
+    /// the other, by its name or by one of its tags.
+    pub fn excludes(&self, a: &Mark, b: &Mark) -> bool {
+        let excluded = |by: &Mark, mark: &Mark| {
+            by.excludes
+                .iter()
+                .any(|x| *x == mark.name || self.has_tag_under(mark, x))
+        };
+        a.name != b.name && (excluded(a, b) || excluded(b, a))
+    }
+
+    /// The slots of a draft's marks that exclude each other, in pairs. Marks
+    /// are checked as the library has them now, results hold older copies.
+    pub fn exclusions(&self, marks: &[Mark]) -> Vec<(usize, usize)> {
+        let current = |mark: &'_ Mark| {
+            self.list
+                .iter()
+                .map(|(m, _)| m)
+                .find(|m| m.name == mark.name)
+                .cloned()
+                .unwrap_or_else(|| mark.clone())
+        };
+        let marks: Vec<Mark> = marks.iter().map(current).collect();
+        let mut pairs = Vec::new();
+        for (i, a) in marks.iter().enumerate() {
+            for (j, b) in marks.iter().enumerate().skip(i + 1) {
+                if self.excludes(a, b) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// The spelling of a mark's category the library lists, which can differ
+    /// from the mark's own when case is ignored.
+    pub fn category_of<'a>(&'a self, mark: &'a Mark) -> &'a str {
+        match self.ignore_case {
+            true => self
+                .categories
+                .iter()
+                .find(|c| self.same_label(c, &mark.category))
+                .map_or(&mark.category, String::as_str),
+            false => &mark.category,
+        }
+    }
+
+    fn tag_weight(&self, tag: &str) -> f64 {
+        let weight = match self.tag_weights.get(tag) {
+            None if self.ignore_case => self
+                .tag_weights
+                .iter()
+                .find(|(t, _)| self.same_label(t, tag))
+                .map(|(_, w)| w),
+            weight => weight,
+        };
+        weight.copied().unwrap_or(1.0)
+    }
+
+    /// Keeps one spelling of the tags and categories that differ only in
+    /// case when the library ignores it, the one most marks use.
+    fn fold_case(&mut self) {
+        if !self.ignore_case {
+            return;
+        }
+        let fold = |labels: &BTreeSet<String>, uses: &dyn Fn(&str) -> usize| {
+            let mut spellings: BTreeMap<String, (&String, usize)> = BTreeMap::new();
+            for label in labels {
+                let n = uses(label);
+                let best = spellings.entry(label.to_lowercase()).or_insert((label, n));
+                if n > best.1 {
+                    *best = (label, n);
+                }
+            }
+            spellings
+                .into_values()
+                .map(|(label, _)| label.clone())
+                .collect()
+        };
+        let marks = || self.list.iter().map(|(m, _)| m);
+        self.tags = fold(&self.tags, &|tag| {
+            marks().filter(|m| m.tags.contains(tag)).count()
+        });
+        self.categories = fold(&self.categories, &|category| {
+            marks().filter(|m| m.category == category).count()
+        });
+    }
+
+    /// Names of the extra csv columns any mark has a value for.
+    pub fn extra_columns(&self) -> BTreeSet<String> {
+        self.list
+            .iter()
+            .flat_map(|(m, _)| m.extra.keys().cloned())
+            .collect()
+    }
+
+    /// Marks that owning any of `owned` makes available to an upgrade draw.
+    pub fn upgrades_of<'a>(&self, owned: impl IntoIterator<Item = &'a str>) -> BTreeSet<String> {
+        let owned: BTreeSet<_> = owned.into_iter().collect();
+        self.list
+            .iter()
+            .filter(|(m, _)| owned.contains(m.name.as_str()))
+            .filter_map(|(m, _)| m.upgrade.clone())
+            .collect()
+    }
+
+    /// Whether the requirements of `mark` are met by `drafted`, the marks
+    /// drawn earlier in the draft, or by what the draft's player drafted
+    /// before.
+    pub fn requirements_met<'a>(
+        &self,
+        mark: &Mark,
+        drafted: impl Iterator<Item = &'a Mark> + Clone,
+        options: &DraftOptions,
+        history: &History,
+    ) -> bool {
+        if mark.requires.is_empty() {
+            return true;
+        }
+        let past = options
+            .player
+            .as_ref()
+            .and_then(|p| history.by_player.get(p));
+        let meets = |m: &Mark, req: &str| m.name == req || self.has_tag_under(m, req);
+        mark.requires.iter().all(|req| {
+            drafted.clone().any(|m| meets(m, req))
+                || past.is_some_and(|past| {
+                    past.contains(req)
+                        || self
+                            .list
+                            .iter()
+                            .any(|(m, _)| past.contains(&m.name) && meets(m, req))
+                })
+        })
+    }
+
+    /// How many marks `draw` could take before anything is drawn: those it
+    /// can take now, and those waiting on a requirement that an earlier draw
+    /// may still meet.
+    pub fn pool_preview(
+        &self,
+        draw: &Draw,
+        options: &DraftOptions,
+        history: &History,
+    ) -> (usize, usize) {
+        let upgrades = if draw.upgrade {
+            self.upgrades_of(history.inventory(options).iter().map(String::as_str))
+        } else {
+            BTreeSet::new()
+        };
+        let pinned: Vec<&Mark> = self
+            .list
+            .iter()
+            .map(|(m, _)| m)
+            .filter(|m| options.pinned.contains(&m.name))
+            .collect();
+        let (mut eligible, mut waiting) = (0, 0);
+        for (mark, free) in &self.list {
+            if !free
+                || options.banned.contains(&mark.name)
+                || history.excludes(mark, options)
+                || !draw.matches(mark, &BTreeSet::new(), self)
+                || draw.upgrade && !upgrades.contains(&mark.name)
+            {
+                continue;
+            }
+            if self.requirements_met(mark, pinned.iter().copied(), options, history) {
+                eligible += 1;
+            } else {
+                waiting += 1;
+            }
+        }
+        (eligible, waiting)
+    }
+
+    pub fn exec_draw(
+        &self,
+        draws: Vec<Draw>,
+        options: &DraftOptions,
+        history: &History,
+        rng: &mut impl Rng,
+    ) -> Vec<Mark> {
+        self.preview(draws, options, history, rng).marks
+    }
+
+    /// Executes a draft without taking it, with how large each draw's pool
+    /// was and what went wrong, to check the draws before the real roll.
+    pub fn preview(
+        &self,
+        draws: Vec<Draw>,
+        options: &DraftOptions,
+        history: &History,
+        rng: &mut impl Rng,
+    ) -> Preview {
+        self.exec_draw_after(draws, options, history, rng, &[], &mut self.bags.clone())
+    }
+
+    /// Like [`Library::exec_draw`], for the rest of a draft whose `earlier`
+    /// marks are drawn already.
+    fn exec_draw_after(
+        &self,
+        draws: Vec<Draw>,
+        options: &DraftOptions,
+        history: &History,
+        rng: &mut impl Rng,
+        earlier: &[&Mark],
+        bags: &mut ShuffleBags,
+    ) -> Preview {
+        let mut pools = Vec::new();
+        let mut fallbacks = BTreeMap::new();
+        let mut warnings = Vec::new();
+
+        let substitutes: Vec<_> = draws
+            .iter()
+            .map(|d| self.power_rules.roll(d.power.as_ref(), rng))
+            .collect();
+
+        // pinned marks claim the first draw they satisfy before anything is
+        // rolled, or the first unclaimed draw if none of them fit
+        let mut pinned: Vec<Option<Mark>> = vec![None; draws.len()];
+        for name in &options.pinned {
+            let Some((mark, _)) = self.list.iter().find(|(m, _)| &m.name == name) else {
+                continue;
+            };
+            let unclaimed = || (0..draws.len()).filter(|&i| pinned[i].is_none());
+            let slot = unclaimed()
+                .find(|&i| draws[i].matches(mark, &substitutes[i], self))
+                .or_else(|| unclaimed().next());
+            match slot {
+                Some(i) => {
+                    if !draws[i].matches(mark, &substitutes[i], self) {
+                        warnings.push(format!(
+                            "{name} is pinned to draw {}, it doesn't fit",
+                            i + 1
+                        ));
+                    }
+                    pinned[i] = Some(mark.clone());
+                }
+                None => warnings.push(format!("{name} is pinned, but no draw is left for it")),
+            }
+        }
+
+        let wishlist = self.wishlist(options);
+        let weight = |mark: &Mark| {
+            let mut weight = self.weight(mark, history);
+            if wishlist.is_some_and(|w| w.contains(&mark.name)) {
+                weight *= self.wishlist_boost;
+            }
+            weight
+        };
+
+        // what the draws still to come cost at least, so a budget leaves room for them
+        let cheapest: Vec<i32> = draws
+            .iter()
+            .zip(&substitutes)
+            .map(|(d, s)| {
+                self.list
+                    .iter()
+                    .filter(|(m, free)| *free && d.matches(m, s, self))
+                    .map(|(m, _)| self.tiers.cost(&m.power))
+                    .min()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let strategy = strategy::get(options.strategy.as_deref());
+
+        let mut marks: Vec<Mark> = Vec::new();
+
+        let draws_len = draws.len();
+        for (i, draw) in draws.into_iter().enumerate() {
+            if let Some(mark) = pinned[i].take() {
+                marks.push(mark);
+                pools.push(None);
+                continue;
+            }
+
+            // marks drawn earlier in this draft count as owned too
+            let upgrades = if draw.upgrade {
+                self.upgrades_of(
+                    history
+                        .inventory(options)
+                        .iter()
+                        .map(String::as_str)
+                        .chain(marks.iter().map(|m| m.name.as_str())),
+                )
+            } else {
+                BTreeSet::new()
+            };
+            let drafted: Vec<&Mark> = earlier
+                .iter()
+                .copied()
+                .chain(&marks)
+                .chain(pinned.iter().flatten())
+                .collect();
+            let rules = self.rules.as_ref().map(|rules| {
+                let draft = script::draft_map(options.player.as_deref(), &drafted, history);
+                (rules, draft)
+            });
+
+            // the most this draw may cost without taking the draft over budget
+            let allowance = options.budget.map(|budget| {
+                let spent: i32 = drafted.iter().map(|m| self.tiers.cost(&m.power)).sum();
+                let reserved: i32 = (i + 1..draws_len)
+                    .filter(|&j| pinned[j].is_none())
+                    .map(|j| cheapest[j])
+                    .sum();
+                (budget.points - spent - reserved, budget.strict)
+            });
+            let over_budget =
+                |mark: &Mark| allowance.is_some_and(|(a, _)| self.tiers.cost(&mark.power) > a);
+
+            let pool_of = |draw: &Draw, substitutes: &BTreeSet<Power>| {
+                let mut pool = Vec::new();
+                for (mark, free) in &self.list {
+                    if !free || options.banned.contains(&mark.name) {
+                        continue;
+                    }
+                    if history.excludes(mark, options) {
+                        continue;
+                    }
+                    if !draw.matches(mark, substitutes, self) {
+                        continue;
+                    }
+                    if draw.upgrade && !upgrades.contains(&mark.name) {
+                        continue;
+                    }
+                    if allowance.is_some_and(|(_, strict)| strict) && over_budget(mark) {
+                        continue;
+                    }
+                    if !self.requirements_met(mark, drafted.iter().copied(), options, history) {
+                        continue;
+                    }
+                    if marks.iter().chain(pinned.iter().flatten()).any(|m| {
+                        m.name == mark.name && !mark.repeatable
+                            || options.unique_categories
+                                && !mark.category.is_empty()
+                                && self.same_label(&m.category, &mark.category)
+                            || self.excludes(m, mark)
+                    }) {
+                        continue;
+                    }
+                    if rules
+                        .as_ref()
+                        .is_some_and(|(rules, draft)| !rules.eligible(mark, draft))
+                    {
+                        continue;
+                    }
+
+                    pool.push(mark);
+                }
+                pool
+            };
+            let mut pool = pool_of(&draw, &substitutes[i]);
+
+            // a power without free marks falls back along its tier's chain
+            let mut fallback = None;
+            if let (true, Some(PowerConstraint::Exact(power))) = (pool.is_empty(), &draw.power) {
+                for power in self.tiers.fallbacks(power) {
+                    let draw = Draw {
+                        power: Some(PowerConstraint::Exact(power.clone())),
+                        ..draw.clone()
+                    };
+                    pool = pool_of(&draw, &BTreeSet::new());
+                    if !pool.is_empty() {
+                        fallback = Some(power);
+                        break;
+                    }
+                }
+            }
+
+            let weigh = |m: &Mark| {
+                weight(m)
+                    * if over_budget(m) {
+                        OVER_BUDGET_WEIGHT
+                    } else {
+                        1.0
+                    }
+            };
+            let mut picking = Picking {
+                lib: self,
+                options,
+                drafted: &drafted,
+                weight: &weigh,
+                bags,
+            };
+            let choice = strategy.pick(&pool, &mut picking, rng);
+            match choice {
+                None => warnings.push(format!(
+                    "Draw {}: no free mark fits, it gets a placeholder",
+                    i + 1
+                )),
+                Some(mark) if over_budget(mark) => warnings.push(format!(
+                    "Draw {}: {} takes the draft over budget",
+                    i + 1,
+                    mark.name
+                )),
+                Some(_) if fallback.is_some() => {}
+                Some(mark) => {
+                    if let Some(p) = &draw.power {
+                        if !p.matches(&mark.power, &BTreeSet::new(), &self.tiers) {
+                            warnings.push(format!(
+                                "Draw {}: a power rule put {} ({}) in its place",
+                                i + 1,
+                                mark.name,
+                                mark.power.name()
+                            ));
+                        }
+                    }
+                }
+            }
+            if let (Some(power), Some(requested)) = (fallback, &draw.power) {
+                warnings.push(format!(
+                    "Draw {}: no {} mark fits, it fell back to {}",
+                    i + 1,
+                    requested.bound(false).name(),
+                    power.name()
+                ));
+                fallbacks.insert(i, power);
+            }
+            pools.push(Some(pool.len()));
+            let choice = choice.cloned().unwrap_or_else(|| Mark {
+                name: NO_MARK.to_string(),
+                power: self
+                    .tiers
+                    .power("Poor")
+                    .unwrap_or_else(|| self.tiers.powers().remove(0)),
+                ..Default::default()
+            });
+            marks.push(choice);
+        }
+
+        // the script has the last word on what was drawn
+        if let Some(rules) = &self.rules {
+            let draft = script::draft_map(options.player.as_deref(), earlier, history);
+            let drawn: Vec<String> = marks.iter().map(|m| m.name.clone()).collect();
+            marks = rules.after_draft(marks, self, &draft);
+            if !marks.iter().map(|m| &m.name).eq(&drawn) {
+                warnings.push("The script's after_draft changed the drawn marks".to_string());
+            }
+        }
+        Preview {
+            marks,
+            pools,
+            fallbacks,
+            warnings,
+        }
+    }
+
+    /// Executes a draft and records which of the drawn marks were wished for.
+    /// A bag draft takes its marks out of the library's bags.
+    pub fn draft(
+        &mut self,
+        draws: Vec<Draw>,
+        options: DraftOptions,
+        history: &History,
+        rng: &mut impl Rng,
+    ) -> DraftResult {
+        // TOML only has signed integers, larger seeds couldn't be saved as TOML
+        let seed = rng.gen_range(0..=i64::MAX as u64);
+        let mut bags = self.bags.clone();
+        let Preview {
+            marks, fallbacks, ..
+        } = self.exec_draw_after(
+            draws.clone(),
+            &options,
+            history,
+            &mut StdRng::seed_from_u64(seed),
+            &[],
+            &mut bags,
+        );
+        let unavailable = self.unavailable();
+        let wishlisted = self.wishlisted(&options, &marks);
+        let bags = (bags != self.bags).then(|| std::mem::replace(&mut self.bags, bags));
+
+        DraftResult {
+            marks,
+            draws,
+            options,
+            wishlisted,
+            seed: Some(seed),
+            unavailable,
+            timestamp: unix_time(),
+            bags,
+            fallbacks,
+            ..Default::default()
+        }
+    }
+
+    /// Draws a new mark for one slot of `result` with that slot's draw,
+    /// keeping the rest of the result as it is. Also returns the power the
+    /// draw fell back to, if it did.
+    pub fn reroll(
+        &self,
+        result: &DraftResult,
+        slot: usize,
+        history: &History,
+        rng: &mut impl Rng,
+    ) -> (Mark, Option<Power>) {
+        let others: Vec<_> = result
+            .marks
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != slot)
+            .map(|(_, m)| m)
+            .collect();
+
+        // the other marks can't be drawn again and count as owned for upgrades
+        let mut options = DraftOptions {
+            pinned: BTreeSet::new(),
+            ..result.options.clone()
+        };
+        options.banned.insert(result.marks[slot].name.clone());
+        for (mark, _) in &self.list {
+            if others.iter().any(|o| {
+                o.name == mark.name && !mark.repeatable
+                    || options.unique_categories
+                        && !mark.category.is_empty()
+                        && self.same_label(&o.category, &mark.category)
+                    || self.excludes(o, mark)
+            }) {
+                options.banned.insert(mark.name.clone());
+            }
+        }
+        let mut history = history.clone();
+        history.owned.extend(others.iter().map(|m| m.name.clone()));
+        if let Some(player) = &options.player {
+            let owned = history.by_player.entry(player.clone()).or_default();
+            owned.extend(others.iter().map(|m| m.name.clone()));
+        }
+
+        let draw = result.draws.get(slot).cloned().unwrap_or_default();
+        let mut preview = self.exec_draw_after(
+            vec![draw],
+            &options,
+            &history,
+            rng,
+            &others,
+            &mut self.bags.clone(),
+        );
+        (preview.marks.remove(0), preview.fallbacks.remove(&0))
+    }
+
+    /// The names of the marks that aren't free, to record with a result.
+    fn unavailable(&self) -> BTreeSet<String> {
+        self.list
+            .iter()
+            .filter(|(_, free)| !free)
+            .map(|(m, _)| m.name.clone())
+            .collect()
+    }
+
+    /// Draws `result` again with its seed, with the marks that weren't free
+    /// back then left out. Marks, weights and rules come from this library, so
+    /// the outcome only matches if those haven't changed since. Says why if
+    /// the result can't be replayed.
+    pub fn replay(
+        &self,
+        result: &DraftResult,
+        history: &History,
+    ) -> Result<Vec<Mark>, &'static str> {
+        if result.snake {
+            return Err("was picked in a snake draft");
+        }
+        let seed = result.seed.ok_or("was saved without a seed")?;
+        let lib = Library {
+            list: self
+                .list
+                .iter()
+                .map(|(m, _)| (m.clone(), result.was_free(m)))
+                .collect(),
+            bags: result.bags.clone().unwrap_or_default(),
+            ..self.clone()
+        };
+        Ok(lib.exec_draw(
+            result.draws.clone(),
+            &result.options,
+            history,
+            &mut StdRng::seed_from_u64(seed),
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseError {
+    line: u64,
+    message: String,
+    value: Option<String>,
+    suggestion: Option<String>,
+}
+
+impl ParseError {
+    /// The error without its line number.
+    fn detail(&self) -> String {
+        let mut detail = self.message.clone();
+        if let Some(value) = &self.value {
+            detail.push_str(&format!(" {value:?}"));
+        }
+        if let Some(suggestion) = &self.suggestion {
+            detail.push_str(&format!(" (did you mean '{suggestion}'?)"));
+        }
+        detail
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.detail())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// `tags` with the parents of hierarchical tags added, e.g. `Weapon` and
+/// `Weapon/Melee` for `Weapon/Melee/Sword`, ordered so that every tag comes
+/// right after its parent.
+pub fn tag_tree<'a>(tags: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    let mut tree = BTreeSet::new();
+    for tag in tags {
+        for (i, _) in tag.match_indices('/').filter(|(i, _)| *i > 0) {
+            tree.insert(tag[..i].to_string());
+        }
+        tree.insert(tag.clone());
+    }
+    let mut tree: Vec<String> = tree.into_iter().collect();
+    tree.sort_by(|a, b| a.split('/').cmp(b.split('/')));
+    tree
+}
+
+fn suggest<'a>(value: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let value = value.to_lowercase();
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(&value, &c.to_lowercase()), c))
+        .filter(|(d, c)| *d <= cmp::max(2, c.len() / 3))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct TiersToml {
+    #[serde(default)]
+    tiers: PowerTiers,
+    /// See [`Library::ignore_case`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    ignore_case: bool,
+    /// See [`Library::script`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    script: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LibraryToml {
+    marks: Vec<Mark>,
+    tiers: Option<PowerTiers>,
+    power_rules: Option<PowerRules>,
+    #[serde(default)]
+    tag_weights: BTreeMap<String, f64>,
+    #[serde(default)]
+    ignore_case: bool,
+    script: Option<String>,
+}
+
+impl SaveFile {
+    fn parse_library_toml(text: &str) -> anyhow::Result<Self> {
+        let LibraryToml {
+            marks,
+            tiers,
+            power_rules,
+            tag_weights,
+            ignore_case,
+            script,
+        } = toml::from_str(text)?;
+
+        if let Some((tag, w)) = tag_weights.iter().find(|(_, w)| **w < 0.0) {
+            bail!("Negative weight {w} for tag {tag:?}");
+        }
+
+        let mut library = Library::from_marks(marks);
+        library.tag_weights = tag_weights;
+        library.ignore_case = ignore_case;
+        library.script = script;
+        if let Some(tiers) = tiers {
+            library.tiers = tiers;
+        }
+        if let Some(power_rules) = power_rules {
+            library.power_rules = power_rules;
+        }
+
+        Ok(SaveFile {
+            library,
+            ..Default::default()
+        })
+    }
+
+    /// Parses a library csv. In lenient mode malformed rows are skipped and
+    /// returned alongside the library instead of aborting on the first one.
+    fn parse_library_file<S: AsRef<Path>>(
+        path: S,
+        lenient: bool,
+        tiers: PowerTiers,
+    ) -> anyhow::Result<(Self, Vec<ParseError>)> {
+        // NAME,POWER,CATEGORY,TAG,TAG,DESCRIPTION[,UPGRADE][,WEIGHT][,REPEAT]
+        // [,EXCLUDES,EXCLUDES][,REQUIRES,REQUIRES]
+        // any other column is kept as an extra field of the marks
+
+        let mut rdr = csv::Reader::from_path(path)?;
+        let headers = rdr.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let required =
+            |name: &str| column(name).ok_or(format_err!("Library csv is missing a {name} column"));
+
+        let name_col = required("NAME")?;
+        let power_col = required("POWER")?;
+        let category_col = required("CATEGORY")?;
+        let description_col = required("DESCRIPTION")?;
+        let upgrade_col = column("UPGRADE");
+        let weight_col = column("WEIGHT");
+        let repeat_col = column("REPEAT");
+        let columns = |name: &str| -> Vec<_> {
+            headers
+                .iter()
+                .enumerate()
+                .filter(|(_, h)| *h == name)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        let tag_cols = columns("TAG");
+        let excludes_cols = columns("EXCLUDES");
+        let requires_cols = columns("REQUIRES");
+        let known = [
+            "NAME",
+            "POWER",
+            "CATEGORY",
+            "TAG",
+            "DESCRIPTION",
+            "UPGRADE",
+            "WEIGHT",
+            "REPEAT",
+            "EXCLUDES",
+            "REQUIRES",
+        ];
+        let extra_cols: Vec<_> = headers
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| !h.is_empty() && !known.contains(h))
+            .map(|(i, h)| (i, h.to_string()))
+            .collect();
+
+        let mut v = Vec::new();
+        let mut errors = Vec::new();
+
+        let mut categories = BTreeSet::new();
+        let mut all_tags = BTreeSet::new();
+
+        for result in rdr.into_records() {
+            let record = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    let e = ParseError {
+                        line: e.position().map_or(0, |p| p.line()),
+                        message: match e.kind() {
+                            csv::ErrorKind::UnequalLengths {
+                                expected_len, len, ..
+                            } => format!("expected {expected_len} fields but found {len}"),
+                            _ => e.to_string(),
+                        },
+                        value: None,
+                        suggestion: None,
+                    };
+                    if !lenient {
+                        return Err(e.into());
+                    }
+                    errors.push(e);
+                    continue;
+                }
+            };
+            let line = record.position().map_or(0, |p| p.line());
+            let err = |message: &str, value: Option<&str>| ParseError {
+                line,
+                message: message.to_string(),
+                value: value.map(str::to_string),
+                suggestion: None,
+            };
+
+            let field = |i: usize| {
+                record
+                    .get(i)
+                    .ok_or(err("malformed row, missing field", None))
+            };
+
+            let mark = (|| -> Result<Mark, ParseError> {
+                let name = field(name_col)?.to_string();
+                let power = field(power_col)?;
+                let power = tiers.power(power).ok_or(ParseError {
+                    suggestion: suggest(power, tiers.names()).map(str::to_string),
+                    ..err("unknown power level", Some(power))
+                })?;
+
+                let category = field(category_col)?.to_string();
+
+                let mut tags = BTreeSet::new();
+                for &i in &tag_cols {
+                    match field(i)? {
+                        "" => continue,
+                        t => {
+                            tags.insert(t.to_string());
+                        }
+                    }
+                }
+
+                let mut excludes = BTreeSet::new();
+                for &i in &excludes_cols {
+                    match field(i)? {
+                        "" => continue,
+                        x => {
+                            excludes.insert(x.to_string());
+                        }
+                    }
+                }
+
+                let mut requires = BTreeSet::new();
+                for &i in &requires_cols {
+                    match field(i)? {
+                        "" => continue,
+                        r => {
+                            requires.insert(r.to_string());
+                        }
+                    }
+                }
+
+                let description = field(description_col)?.to_string();
+
+                let upgrade = match upgrade_col.map(field).transpose()? {
+                    None | Some("") => None,
+                    Some(u) => Some(u.to_string()),
+                };
+
+                let weight = match weight_col.map(field).transpose()? {
+                    None | Some("") => default_weight(),
+                    Some(w) => w
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|w: &f64| *w >= 0.0)
+                        .ok_or(err("invalid weight", Some(w)))?,
+                };
+
+                let repeatable = match repeat_col.map(field).transpose()? {
+                    None => false,
+                    Some(r) => match r.trim().to_lowercase().as_str() {
+                        "" | "0" | "n" | "no" | "false" => false,
+                        "1" | "y" | "yes" | "true" | "x" => true,
+                        _ => return Err(err("invalid repeat flag", Some(r))),
+                    },
+                };
+
+                let mut extra = BTreeMap::new();
+                for (i, header) in &extra_cols {
+                    match field(*i)? {
+                        "" => {}
+                        v => {
+                            extra.insert(header.clone(), v.to_string());
+                        }
+                    }
+                }
+
+                Ok(Mark {
+                    name,
+                    power,
+                    category,
+                    tags,
+                    description,
+                    upgrade,
+                    weight,
+                    repeatable,
+                    excludes,
+                    requires,
+                    extra,
+                    ..Default::default()
+                })
+            })();
+
+            let mark = match mark {
+                Ok(mark) => mark,
+                Err(e) if lenient => {
+                    errors.push(e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if !categories.contains(&mark.category) && !mark.category.is_empty() {
+                categories.insert(mark.category.clone());
+            }
+            for t in &mark.tags {
+                if !all_tags.contains(t) {
+                    all_tags.insert(t.clone());
+                }
+            }
+
+            v.push((mark, true));
+        }
+
+        Ok((
+            SaveFile {
+                library: Library {
+                    list: v,
+                    categories,
+                    tags: all_tags,
+                    tiers,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            errors,
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "examples"))]
+mod tests {
+    use super::*;
+    use format::SaveFormat;
+
+    #[test]
+    fn examples_round_trip() {
+        for (name, _) in EXAMPLES {
+            let mut save = load_example(name).unwrap();
+            save.version = migrate::VERSION;
+            assert!(!save.library.list.is_empty(), "{name} has no marks");
+            let expected = serde_json::to_value(&save).unwrap();
+
+            for format in [
+                SaveFormat::Json,
+                SaveFormat::Toml,
+                SaveFormat::Yaml,
+                SaveFormat::Ron,
+            ] {
+                let text = String::from_utf8(format.write(&save, false).unwrap()).unwrap();
+                let value = format.parse(&text).unwrap();
+                let loaded: SaveFile = serde_json::from_value(value).unwrap();
+                assert_eq!(
+                    serde_json::to_value(&loaded).unwrap(),
+                    expected,
+                    "{name} changed going through {format:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_example_fails() {
+        assert!(load_example("nonexistent").is_err());
     }
 }
@@ -0,0 +1,101 @@
+use std::{collections::btree_map::Entry, path::PathBuf};
+
+use anyhow::bail;
+
+use crate::{format, library_name, load_library_file, migrate, SaveFile};
+
+const USAGE: &str = "usage: upheaval-draft merge [--compact] SAVE SAVE... OUTPUT";
+
+/// `merge SAVE SAVE... OUTPUT`: combines the saves of several hosts into one.
+/// Marks are matched by name, a mark drafted in any of the saves is taken in
+/// the merged one. Results are appended in the order the saves are given,
+/// labelled with the name of the save they came from.
+pub fn run(args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut compact = false;
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--compact" => compact = true,
+            a if a.starts_with("--") => bail!("Unknown option {a}\n{USAGE}"),
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    let Some((output, inputs)) = files.split_last() else {
+        bail!("{USAGE}");
+    };
+    if inputs.len() < 2 {
+        bail!("{USAGE}");
+    }
+    // checked before loading anything, so a typo doesn't cost a long merge
+    let save_format = format::of(output)?;
+
+    let mut save = SaveFile {
+        version: migrate::VERSION,
+        ..Default::default()
+    };
+    for path in inputs {
+        let mut file = load_library_file(path, false)?;
+        let label = label(path);
+
+        let mut new_marks = Vec::new();
+        for (mark, free) in file.library.list {
+            match save
+                .library
+                .list
+                .iter_mut()
+                .find(|(m, _)| m.name == mark.name)
+            {
+                Some((existing, existing_free)) => {
+                    if existing.power != mark.power
+                        || existing.category != mark.category
+                        || existing.tags != mark.tags
+                        || existing.description != mark.description
+                    {
+                        eprintln!(
+                            "Mark {:?} of {} differs from the one merged before it, keeping \
+                             the first",
+                            mark.name,
+                            path.display()
+                        );
+                    }
+                    *existing_free &= free;
+                }
+                None => new_marks.push((mark, free)),
+            }
+        }
+        file.library.list = new_marks;
+        save.library.merge(file.library)?;
+
+        file.results.set_source(&label);
+        let count = file.results.list().len();
+        save.results.extend(file.results);
+        for (name, preset) in file.presets {
+            match save.presets.entry(name) {
+                Entry::Occupied(e) => {
+                    eprintln!(
+                        "Preset {:?} of {label} is already taken, skipping it",
+                        e.key()
+                    )
+                }
+                Entry::Vacant(e) => {
+                    e.insert(preset);
+                }
+            }
+        }
+        eprintln!("Merged {} with {count} results", path.display());
+    }
+
+    save_format.save(output, &save, compact)?;
+    eprintln!(
+        "Wrote {} marks and {} results to {}",
+        save.library.list.len(),
+        save.results.list().len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// What the results of a save are labelled with, its name as a library.
+fn label(path: &std::path::Path) -> String {
+    library_name(&path.display().to_string()).to_string()
+}
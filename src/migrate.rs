@@ -0,0 +1,112 @@
+use std::{collections::BTreeSet, path::Path};
+
+use anyhow::bail;
+use serde_json::{json, Value};
+
+use crate::{format, SaveFile};
+
+/// The version of the save format written by this build. Bump it whenever the
+/// layout of a save changes and add a migration from the previous version.
+pub const VERSION: u32 = 2;
+
+/// The `i`th migration brings a save from version `i` to version `i + 1`.
+const MIGRATIONS: [fn(&mut Value); VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Loads a save in any of the save formats, bringing saves of older versions
+/// up to date first.
+pub fn load(path: &Path) -> anyhow::Result<SaveFile> {
+    let value = format::of(path)?.read(path)?;
+    migrate(value, &path.display().to_string())
+}
+
+fn migrate(mut save: Value, name: &str) -> anyhow::Result<SaveFile> {
+    // saves from before versioning have no version field
+    let version = match save.get("version") {
+        None => 0,
+        Some(v) => match v.as_u64() {
+            Some(v) => v,
+            None => bail!("{name} has an invalid save format version {v}"),
+        },
+    };
+    if version > VERSION as u64 {
+        bail!(
+            "{name} was saved by a newer version of upheaval-draft (save format {version}, \
+             this version reads up to {VERSION}), update the app to open it"
+        );
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("Migrating {name} from save format {from} to {}", from + 1);
+        migration(&mut save);
+    }
+    save["version"] = json!(VERSION);
+
+    Ok(serde_json::from_value(save)?)
+}
+
+/// The layouts from before saves were versioned: results stored as a
+/// `[marks, draws]` pair, draws with a bare power instead of a constraint and
+/// draws with a flat list of tags instead of groups of alternatives.
+fn v0_to_v1(save: &mut Value) {
+    let Some(results) = save
+        .pointer_mut("/results/results")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+
+    for result in results {
+        if let Some([marks, draws]) = result.as_array().map(Vec::as_slice) {
+            *result = json!({ "marks": marks, "draws": draws });
+        }
+
+        let Some(draws) = result.get_mut("draws").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for draw in draws {
+            if let Some(power) = draw.get_mut("power").filter(|p| p.is_string()) {
+                *power = json!({ "Exact": power });
+            }
+            if let Some(tags) = draw.get_mut("tags").and_then(Value::as_array_mut) {
+                for tag in tags.iter_mut().filter(|t| t.is_string()) {
+                    *tag = json!([tag]);
+                }
+            }
+        }
+    }
+}
+
+/// Results kept a copy of every mark that was free when they were drafted,
+/// now they keep the names of the marks of the library that weren't.
+fn v1_to_v2(save: &mut Value) {
+    let names: Vec<String> = save
+        .pointer("/library/list")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.get(0)?.get("name")?.as_str()?.to_string()))
+        .collect();
+    let Some(results) = save
+        .pointer_mut("/results/results")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+
+    for result in results {
+        let Some(available) = result.as_object_mut().and_then(|r| r.remove("available")) else {
+            continue;
+        };
+        let available: BTreeSet<&str> = available
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|mark| mark.get("name")?.as_str())
+            .collect();
+        let unavailable: Vec<_> = names
+            .iter()
+            .filter(|name| !available.contains(name.as_str()))
+            .collect();
+        result["unavailable"] = json!(unavailable);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    cmp, env, fs,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{config::Config, format, theme, ui::centered_rect, Terminal};
+
+/// A row of the file browser.
+enum Entry {
+    Parent,
+    Dir(PathBuf),
+    File(PathBuf),
+}
+
+/// The popup shown when the app is started without a file: the recent files
+/// to reopen with one key, and a browser for everything else.
+struct Picker {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    state: ListState,
+    /// Recent files that still exist, latest first.
+    recent: Vec<PathBuf>,
+    /// Why the last pick could not be opened.
+    error: Option<String>,
+}
+
+/// Lets the user pick a library or save until `open` accepts one, returns
+/// `None` if they quit instead. `cancel` says what quitting the picker does.
+pub fn pick<T>(
+    terminal: &mut Terminal,
+    cancel: &'static str,
+    mut open: impl FnMut(&Path) -> anyhow::Result<T>,
+) -> anyhow::Result<Option<(PathBuf, T)>> {
+    let mut picker = Picker {
+        dir: env::current_dir()?,
+        entries: Vec::new(),
+        state: ListState::default(),
+        recent: Config::load()
+            .recent
+            .into_iter()
+            .filter(|p| p.exists())
+            .collect(),
+        error: None,
+    };
+    picker.read_dir();
+
+    loop {
+        terminal.draw(|f| picker.draw(f, cancel))?;
+        let Event::Key(ev) = event::read()? else {
+            continue;
+        };
+        let path = match ev.code {
+            KeyCode::Esc | KeyCode::Char('q' | 'Q') => return Ok(None),
+            KeyCode::Char(c @ '1'..='9') => match picker.recent.get(c as usize - '1' as usize) {
+                Some(path) => path.clone(),
+                None => continue,
+            },
+            code => match picker.input(code) {
+                Some(path) => path,
+                None => continue,
+            },
+        };
+        match open(&path) {
+            Ok(opened) => return Ok(Some((path, opened))),
+            Err(e) => picker.error = Some(format!("{e:#}")),
+        }
+    }
+}
+
+impl Picker {
+    /// Lists the directories and the files that can be opened in `dir`.
+    fn read_dir(&mut self) {
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        match fs::read_dir(&self.dir) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    let hidden = entry.file_name().to_string_lossy().starts_with('.');
+                    if hidden {
+                        continue;
+                    }
+                    if path.is_dir() {
+                        dirs.push(path);
+                    } else if format::is_library(&path) {
+                        files.push(path);
+                    }
+                }
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("Could not read {}: {e}", self.dir.display())),
+        }
+        dirs.sort();
+        files.sort();
+
+        self.entries = self
+            .dir
+            .parent()
+            .map(|_| Entry::Parent)
+            .into_iter()
+            .chain(dirs.into_iter().map(Entry::Dir))
+            .chain(files.into_iter().map(Entry::File))
+            .collect();
+        self.state.select(Some(0));
+    }
+
+    /// Moves through the browser, returns the file picked with Enter.
+    fn input(&mut self, code: KeyCode) -> Option<PathBuf> {
+        let selected = self.state.selected().unwrap_or(0);
+        let last = self.entries.len().saturating_sub(1);
+        match code {
+            KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => self.state.select(Some(cmp::min(selected + 1, last))),
+            KeyCode::PageUp => self.state.select(Some(selected.saturating_sub(10))),
+            KeyCode::PageDown => self.state.select(Some(cmp::min(selected + 10, last))),
+            KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Backspace | KeyCode::Left => self.up(),
+            KeyCode::Enter | KeyCode::Right => match self.entries.get(selected)? {
+                Entry::Parent => self.up(),
+                Entry::Dir(dir) => {
+                    self.dir = dir.clone();
+                    self.read_dir();
+                }
+                Entry::File(path) => return Some(path.clone()),
+            },
+            _ => {}
+        }
+        None
+    }
+
+    /// Goes to the parent directory with the one left selected.
+    fn up(&mut self) {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let left = std::mem::replace(&mut self.dir, parent);
+        self.read_dir();
+        let i = self
+            .entries
+            .iter()
+            .position(|e| matches!(e, Entry::Dir(d) if *d == left));
+        self.state.select(i.or(Some(0)));
+    }
+
+    fn draw(&mut self, f: &mut Frame, cancel: &'static str) {
+        let theme = theme::current();
+        let size = f.size();
+        let area = centered_rect(
+            size,
+            cmp::min(72, size.width),
+            size.height.saturating_sub(2),
+        );
+        f.render_widget(Clear, area);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(Span::styled("Open a library or save", theme.title))
+            .title_alignment(Alignment::Center)
+            .title_bottom(
+                Line::from(vec![
+                    "1-9".bold(),
+                    Span::styled(" recent  ", theme.muted),
+                    "Enter".bold(),
+                    Span::styled(" open  ", theme.muted),
+                    "Backspace".bold(),
+                    Span::styled(" up  ", theme.muted),
+                    "q".bold(),
+                    Span::styled(format!(" {cancel}"), theme.muted),
+                ])
+                .centered(),
+            )
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let recent_height = match self.recent.len() {
+            0 => 0,
+            n => n as u16 + 2,
+        };
+        let error_height = if self.error.is_some() { 2 } else { 0 };
+        let [recent_area, dir_area, list_area, error_area] = Layout::vertical([
+            Constraint::Length(recent_height),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(error_height),
+        ])
+        .areas(inner);
+
+        if !self.recent.is_empty() {
+            let mut lines = vec![Line::from("Recent".bold())];
+            for (i, path) in self.recent.iter().enumerate() {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{} ", i + 1), theme.title).bold(),
+                    Span::raw(shorten(path)),
+                ]));
+            }
+            f.render_widget(Paragraph::new(lines), recent_area);
+        }
+
+        let mut dir = shorten(&self.dir);
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+        f.render_widget(Line::from(dir.bold()), dir_area);
+        let list = if self.entries.is_empty() {
+            List::new([Line::from(
+                Span::styled("<no libraries or saves here>", theme.muted).italic(),
+            )])
+        } else {
+            List::new(self.entries.iter().map(|e| match e {
+                Entry::Parent => Line::from(Span::styled("../", theme.muted)),
+                Entry::Dir(dir) => Line::from(format!("{}/", file_name(dir)).blue()),
+                Entry::File(file) => Line::raw(file_name(file)),
+            }))
+        };
+        f.render_stateful_widget(
+            list.highlight_symbol(">>")
+                .highlight_spacing(HighlightSpacing::Always),
+            list_area,
+            &mut self.state,
+        );
+
+        if let Some(error) = &self.error {
+            f.render_widget(
+                Paragraph::new(Span::styled(error.as_str(), theme.error)).wrap(Wrap { trim: true }),
+                error_area,
+            );
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    )
+}
+
+/// `path` with the home directory written as `~`.
+fn shorten(path: &Path) -> String {
+    match env::var_os("HOME").and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
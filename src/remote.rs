@@ -0,0 +1,118 @@
+use std::{
+    env, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{format_err, Context};
+
+use crate::format;
+
+/// How long fetching a library may take before the cached copy is used.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether a library argument is a URL to fetch rather than a file.
+pub fn is_url(name: &str) -> bool {
+    name.starts_with("https://") || name.starts_with("http://")
+}
+
+/// Where fetched libraries are kept, in `$XDG_CACHE_HOME/upheaval-draft`.
+fn cache_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+    Some(dir.join("upheaval-draft").join("libraries"))
+}
+
+/// Whether `path` is the cached copy of a fetched library, which would be
+/// replaced on the next fetch.
+pub fn is_cached(path: &Path) -> bool {
+    cache_dir().is_some_and(|dir| path.starts_with(dir))
+}
+
+/// Downloads the library at `url` and returns the path of the copy in the
+/// cache. When it can't be fetched, e.g. offline, the copy from the last
+/// time is used instead.
+pub fn fetch(url: &str) -> anyhow::Result<PathBuf> {
+    let dir = cache_dir()
+        .ok_or_else(|| format_err!("Nowhere to keep {url}, set $HOME or $XDG_CACHE_HOME"))?
+        .join(escape(url));
+
+    let bytes = match download(url) {
+        Ok(bytes) => bytes,
+        Err(e) => match cached(&dir) {
+            Some(path) => {
+                log::warn!("Could not fetch {url} ({e}), using the copy from the last time");
+                return Ok(path);
+            }
+            None => return Err(e.context(format!("Could not fetch {url}"))),
+        },
+    };
+
+    let path = dir.join(file_name(url, &bytes));
+    // a pastebin's text can change format, only the latest copy is kept
+    if let Some(old) = cached(&dir).filter(|old| *old != path) {
+        fs::remove_file(old)?;
+    }
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, &bytes).with_context(|| format!("Could not cache {url}"))?;
+
+    // the tiers of a csv library live next to it, if the host has them
+    if let Some(base) = url.split(['?', '#']).next().unwrap().strip_suffix(".csv") {
+        if let Ok(tiers) = download(&format!("{base}.tiers.toml")) {
+            fs::write(path.with_extension("tiers.toml"), tiers)?;
+        }
+    }
+    Ok(path)
+}
+
+fn download(url: &str) -> anyhow::Result<Vec<u8>> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .build()
+        .get(url)
+        .call()?;
+    let mut bytes = Vec::new();
+    response.into_reader().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The last library downloaded into `dir`.
+fn cached(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| format::is_library(p))
+}
+
+/// The file a download is kept in, named after the end of the URL so the
+/// library gets the right format and name. Pastebins serve their raw text
+/// without an extension, so those are told apart by the first character.
+fn file_name(url: &str, bytes: &[u8]) -> String {
+    let path = url.split(['?', '#']).next().unwrap();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    if !name.is_empty() && format::is_library(Path::new(name)) {
+        return name.to_string();
+    }
+    let name = if name.is_empty() { "library" } else { name };
+    let text = String::from_utf8_lossy(bytes);
+    match text.trim_start().chars().next() {
+        Some('{') => format!("{name}.json"),
+        _ => format!("{name}.csv"),
+    }
+}
+
+/// A URL as the name of a directory.
+fn escape(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    url.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            _ => '_',
+        })
+        .take(200)
+        .collect()
+}
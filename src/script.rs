@@ -0,0 +1,218 @@
+//! Custom draft rules, written in a [Rhai](https://rhai.rs) script the
+//! library names in its `script` setting. The script can define
+//!
+//! - `eligible(mark, draft)`, whether a mark that fits a draw may be drawn
+//! - `after_draft(marks, draft)`, the drawn marks to keep, by name or as the
+//!   marks themselves, which may also be other marks of the library
+//!
+//! Marks are maps with `name`, `power`, `category`, `tags`, `weight`,
+//! `repeatable` and `library`. The draft is a map with `player`, the marks
+//! drawn before in this draft as `drafted` and since the app was started as
+//! `session`, and `owned` and `recent`, the names of the marks drafted so far
+//! in the campaign and in the recency window. For example:
+//!
+//! ```rhai
+//! // Bad Karma only once per session
+//! fn eligible(mark, draft) {
+//!     let drawn = draft.session + draft.drafted;
+//!     mark.power != "Bad Karma" || !drawn.some(|m| m.power == "Bad Karma")
+//! }
+//! ```
+
+#[cfg(feature = "scripting")]
+pub use rhai_rules::{draft_map, Rules};
+
+#[cfg(feature = "scripting")]
+mod rhai_rules {
+    use std::{cell::RefCell, fmt, fs, path::Path};
+
+    use anyhow::Context;
+    use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+    use crate::{History, Library, Mark};
+
+    /// How many operations a call may take, so a script stuck in a loop
+    /// fails instead of hanging the app.
+    const MAX_OPERATIONS: u64 = 1_000_000;
+
+    pub struct Rules {
+        engine: Engine,
+        ast: AST,
+        file: String,
+        /// The last error a call failed with, each is only logged once.
+        last_error: RefCell<String>,
+    }
+
+    impl fmt::Debug for Rules {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Rules({})", self.file)
+        }
+    }
+
+    impl Rules {
+        pub fn load(path: &Path) -> anyhow::Result<Rules> {
+            let file = path.display().to_string();
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("Could not read the script {file}"))?;
+            let mut engine = Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS);
+            let ast = engine
+                .compile(text)
+                .map_err(|e| anyhow::format_err!("{file}: {e}"))?;
+            Ok(Rules {
+                engine,
+                ast,
+                file,
+                last_error: RefCell::new(String::new()),
+            })
+        }
+
+        fn defines(&self, name: &str) -> bool {
+            self.ast
+                .iter_functions()
+                .any(|f| f.name == name && f.params.len() == 2)
+        }
+
+        fn call<T: Clone + 'static>(&self, name: &str, args: (Dynamic, Dynamic)) -> Option<T> {
+            let result = self
+                .engine
+                .call_fn::<T>(&mut Scope::new(), &self.ast, name, args);
+            match result {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    let message = format!("{}: {name}: {e}", self.file);
+                    if *self.last_error.borrow() != message {
+                        log::warn!("{message}");
+                        *self.last_error.borrow_mut() = message;
+                    }
+                    None
+                }
+            }
+        }
+
+        /// Whether `mark` may be drawn, marks are when the script fails.
+        pub fn eligible(&self, mark: &Mark, draft: &Dynamic) -> bool {
+            if !self.defines("eligible") {
+                return true;
+            }
+            self.call("eligible", (mark_map(mark), draft.clone()))
+                .unwrap_or(true)
+        }
+
+        /// The marks to keep of a draft, `marks` as they are when the
+        /// script fails or returns a mark that doesn't exist.
+        pub fn after_draft(&self, marks: Vec<Mark>, lib: &Library, draft: &Dynamic) -> Vec<Mark> {
+            if !self.defines("after_draft") {
+                return marks;
+            }
+            let array: Array = marks.iter().map(mark_map).collect();
+            let Some(kept) = self.call::<Array>("after_draft", (array.into(), draft.clone()))
+            else {
+                return marks;
+            };
+            let resolve = |value: &Dynamic| {
+                let name = match value.read_lock::<Map>() {
+                    Some(map) => map.get("name")?.clone().into_string().ok()?,
+                    None => value.clone().into_string().ok()?,
+                };
+                marks
+                    .iter()
+                    .chain(lib.list.iter().map(|(m, _)| m))
+                    .find(|m| m.name == name)
+                    .cloned()
+            };
+            match kept.iter().map(resolve).collect::<Option<Vec<_>>>() {
+                Some(kept) => kept,
+                None => {
+                    log::warn!(
+                        "{}: after_draft returned a mark that doesn't exist",
+                        self.file
+                    );
+                    marks
+                }
+            }
+        }
+    }
+
+    /// The draft as scripts see it, see the module docs.
+    pub fn draft_map(player: Option<&str>, drafted: &[&Mark], history: &History) -> Dynamic {
+        let names = |names: &mut dyn Iterator<Item = &String>| -> Dynamic {
+            names
+                .map(|n| Dynamic::from(n.clone()))
+                .collect::<Array>()
+                .into()
+        };
+        let mut map = Map::new();
+        map.insert(
+            "player".into(),
+            player.map_or(Dynamic::UNIT, |p| p.to_string().into()),
+        );
+        map.insert(
+            "drafted".into(),
+            drafted
+                .iter()
+                .map(|m| mark_map(m))
+                .collect::<Array>()
+                .into(),
+        );
+        map.insert("owned".into(), names(&mut history.owned.iter()));
+        map.insert("recent".into(), names(&mut history.recent.iter()));
+        map.insert(
+            "session".into(),
+            history
+                .session
+                .iter()
+                .map(mark_map)
+                .collect::<Array>()
+                .into(),
+        );
+        map.into()
+    }
+
+    fn mark_map(mark: &Mark) -> Dynamic {
+        let mut map = Map::new();
+        map.insert("name".into(), mark.name.clone().into());
+        map.insert("power".into(), mark.power.name().to_string().into());
+        map.insert("category".into(), mark.category.clone().into());
+        let tags: Array = mark.tags.iter().map(|t| t.clone().into()).collect();
+        map.insert("tags".into(), tags.into());
+        map.insert("weight".into(), mark.weight.into());
+        map.insert("repeatable".into(), mark.repeatable.into());
+        map.insert("library".into(), mark.library().to_string().into());
+        map.into()
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+pub use stub::{draft_map, Rules};
+
+#[cfg(not(feature = "scripting"))]
+mod stub {
+    use std::path::Path;
+
+    use crate::{History, Library, Mark};
+
+    /// The draft as scripts would see it, there are none in this build.
+    pub struct Draft;
+
+    #[derive(Debug)]
+    pub struct Rules;
+
+    impl Rules {
+        pub fn load(_: &Path) -> anyhow::Result<Rules> {
+            anyhow::bail!("This build does not include scripting support")
+        }
+
+        pub fn eligible(&self, _: &Mark, _: &Draft) -> bool {
+            true
+        }
+
+        pub fn after_draft(&self, marks: Vec<Mark>, _: &Library, _: &Draft) -> Vec<Mark> {
+            marks
+        }
+    }
+
+    pub fn draft_map(_: Option<&str>, _: &[&Mark], _: &History) -> Draft {
+        Draft
+    }
+}
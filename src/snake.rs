@@ -0,0 +1,135 @@
+use std::collections::BTreeSet;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{unix_time, DraftOptions, DraftResult, Draw, History, Library, Mark};
+
+/// A draft where several players take turns picking from one shared pool.
+/// Picks go in snake order, so whoever picks last in a round picks first in
+/// the next one.
+#[derive(Debug, Clone)]
+pub struct SnakeDraft {
+    pub players: Vec<String>,
+    draws: Vec<Draw>,
+    options: DraftOptions,
+    pub pool: Vec<Mark>,
+    pub picks: Vec<Vec<Mark>>,
+    /// How many picks have been made so far.
+    pick: usize,
+    /// Seed of the rng the pool was rolled with.
+    seed: u64,
+    /// The marks that weren't free when the pool was rolled.
+    unavailable: BTreeSet<String>,
+}
+
+impl SnakeDraft {
+    /// Rolls the shared pool, which holds one copy of the draft per player.
+    pub fn new(
+        lib: &Library,
+        players: Vec<String>,
+        draws: Vec<Draw>,
+        options: DraftOptions,
+        history: &History,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let pool_draws = draws
+            .iter()
+            .cycle()
+            .take(draws.len() * players.len())
+            .cloned()
+            .collect();
+        // the same range as a draft's seed, see Library::draft
+        let seed = rng.gen_range(0..=i64::MAX as u64);
+        let pool = lib.exec_draw(
+            pool_draws,
+            &options,
+            history,
+            &mut StdRng::seed_from_u64(seed),
+        );
+
+        SnakeDraft {
+            picks: vec![Vec::new(); players.len()],
+            players,
+            draws,
+            options,
+            pool,
+            pick: 0,
+            seed,
+            unavailable: lib.unavailable(),
+        }
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.draws.len()
+    }
+
+    pub fn round(&self) -> usize {
+        self.pick / self.players.len()
+    }
+
+    /// The index of the player whose turn it is, `None` once the draft is over.
+    pub fn current_player(&self) -> Option<usize> {
+        let n = self.players.len();
+        if self.round() >= self.rounds() || self.pool.is_empty() {
+            return None;
+        }
+        let i = self.pick % n;
+        Some(if self.round().is_multiple_of(2) {
+            i
+        } else {
+            n - 1 - i
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.current_player().is_none()
+    }
+
+    /// Gives the `i`th mark of the pool to the player whose turn it is.
+    pub fn take(&mut self, i: usize) {
+        let Some(player) = self.current_player() else {
+            return;
+        };
+        if i < self.pool.len() {
+            let mark = self.pool.remove(i);
+            self.picks[player].push(mark);
+            self.pick += 1;
+        }
+    }
+
+    /// One result per player, assigned to them.
+    pub fn into_results(self, lib: &Library) -> Vec<DraftResult> {
+        let SnakeDraft {
+            players,
+            draws,
+            options,
+            picks,
+            seed,
+            unavailable,
+            ..
+        } = self;
+
+        players
+            .into_iter()
+            .zip(picks)
+            .map(|(player, marks)| {
+                let options = DraftOptions {
+                    player: Some(player),
+                    ..options.clone()
+                };
+                let wishlisted = lib.wishlisted(&options, &marks);
+                DraftResult {
+                    marks,
+                    draws: draws.clone(),
+                    options,
+                    wishlisted,
+                    seed: Some(seed),
+                    unavailable: unavailable.clone(),
+                    snake: true,
+                    timestamp: unix_time(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    cmp,
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use serde::Serialize;
+
+use crate::{library_paths, load_library_files, DraftResult, Library, Mark};
+
+const USAGE: &str = "usage: upheaval-draft stats [--json] [--csv FILE] [--lenient] LIBRARY...";
+
+/// How many of the most drafted marks are listed.
+const TOP_MARKS: usize = 10;
+
+/// How wide the bar of the most drawn mark is in the usage chart.
+const BAR_WIDTH: usize = 30;
+
+#[derive(Serialize)]
+struct Stats {
+    library: LibraryStats,
+    /// Only for saves with results.
+    results: Option<ResultStats>,
+}
+
+#[derive(Serialize)]
+struct LibraryStats {
+    marks: usize,
+    free: usize,
+    powers: Vec<Count>,
+    categories: Vec<Count>,
+    tags: Vec<Count>,
+}
+
+/// How many marks have some power, category or tag, and how many of those are
+/// still free.
+#[derive(Serialize)]
+struct Count {
+    name: String,
+    marks: usize,
+    free: usize,
+}
+
+#[derive(Serialize)]
+struct ResultStats {
+    drafts: usize,
+    marks: usize,
+    rerolls: usize,
+    wishlisted: usize,
+    powers: Vec<Tally>,
+    players: Vec<PlayerStats>,
+    most_drafted: Vec<Tally>,
+    /// How often every mark of the library was drawn, re-rolled away or
+    /// not, most drawn first and never drawn marks last.
+    usage: Vec<Tally>,
+}
+
+#[derive(Serialize)]
+struct Tally {
+    name: String,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct PlayerStats {
+    /// `None` for results not assigned to anyone.
+    name: Option<String>,
+    drafts: usize,
+    marks: usize,
+}
+
+/// A row of the `--csv` export, for a mark or for a category.
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    kind: &'static str,
+    name: &'a str,
+    /// Drawn at all, including the marks re-rolled away.
+    drawn: usize,
+    /// Still in the drafts.
+    drafted: usize,
+}
+
+/// `stats LIBRARY...`: prints what the libraries are made of and, for saves,
+/// what has been drafted from them, as tables or as JSON with `--json`.
+/// `--csv FILE` writes how often each mark and category was drawn instead.
+pub fn run(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut json = false;
+    let mut lenient = false;
+    let mut csv = None;
+    let mut names = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--lenient" => lenient = true,
+            "--csv" => match args.next() {
+                Some(path) => csv = Some(PathBuf::from(path)),
+                None => bail!("--csv needs a file\n{USAGE}"),
+            },
+            a if a.starts_with("--") => bail!("Unknown option {a}\n{USAGE}"),
+            _ => names.push(PathBuf::from(arg)),
+        }
+    }
+    if names.is_empty() {
+        bail!("{USAGE}");
+    }
+
+    let save = load_library_files(&library_paths(names)?, lenient)?;
+    let results = save.results.list();
+    if let Some(path) = csv {
+        let rows = write_csv(&save.library, results, &path)?;
+        println!("Wrote {rows} rows to {}", path.display());
+        return Ok(());
+    }
+    let stats = Stats {
+        library: library_stats(&save.library),
+        results: (!results.is_empty()).then(|| result_stats(&save.library, results)),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print_tables(&stats);
+    }
+    Ok(())
+}
+
+fn library_stats(lib: &Library) -> LibraryStats {
+    let count = |name: &str, matches: &dyn Fn(&Mark) -> bool| {
+        let marks: Vec<_> = lib.list.iter().filter(|(m, _)| matches(m)).collect();
+        Count {
+            name: name.to_string(),
+            marks: marks.len(),
+            free: marks.iter().filter(|(_, free)| *free).count(),
+        }
+    };
+
+    LibraryStats {
+        marks: lib.list.len(),
+        free: lib.list.iter().filter(|(_, free)| *free).count(),
+        powers: lib
+            .tiers
+            .names()
+            .map(|power| count(power, &|m| m.power.name() == power))
+            .collect(),
+        categories: lib
+            .categories
+            .iter()
+            .map(|category| count(category, &|m| lib.same_label(&m.category, category)))
+            .collect(),
+        tags: lib
+            .tags
+            .iter()
+            .map(|tag| count(tag, &|m| lib.has_tag(m, tag)))
+            .collect(),
+    }
+}
+
+fn result_stats(lib: &Library, results: &[DraftResult]) -> ResultStats {
+    let drafted = || results.iter().flat_map(|r| &r.marks);
+
+    let mut times_drafted: BTreeMap<&str, usize> = BTreeMap::new();
+    for mark in drafted() {
+        *times_drafted.entry(&mark.name).or_default() += 1;
+    }
+    let mut usage: Vec<_> = lib
+        .list
+        .iter()
+        .map(|(m, _)| {
+            let rerolled = results
+                .iter()
+                .flat_map(|r| &r.rerolls)
+                .filter(|(_, old)| old.name == m.name)
+                .count();
+            let drawn = times_drafted.get(m.name.as_str()).copied().unwrap_or(0) + rerolled;
+            (m.name.as_str(), drawn)
+        })
+        .collect();
+    usage.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+    usage.dedup_by_key(|(name, _)| *name);
+
+    let mut most_drafted: Vec<_> = times_drafted.into_iter().collect();
+    // stable, so ties stay in alphabetical order
+    most_drafted.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let mut players: Vec<PlayerStats> = Vec::new();
+    for result in results {
+        let name = &result.options.player;
+        let player = match players.iter_mut().find(|p| &p.name == name) {
+            Some(player) => player,
+            None => {
+                players.push(PlayerStats {
+                    name: name.clone(),
+                    drafts: 0,
+                    marks: 0,
+                });
+                players.last_mut().unwrap()
+            }
+        };
+        player.drafts += 1;
+        player.marks += result.marks.len();
+    }
+    // unassigned results last
+    players.sort_by_key(|p| p.name.is_none());
+
+    ResultStats {
+        drafts: results.len(),
+        marks: drafted().count(),
+        rerolls: results.iter().map(|r| r.rerolls.len()).sum(),
+        wishlisted: results.iter().map(|r| r.wishlisted.len()).sum(),
+        powers: lib
+            .tiers
+            .names()
+            .map(|power| Tally {
+                name: power.to_string(),
+                count: drafted().filter(|m| m.power.name() == power).count(),
+            })
+            .collect(),
+        players,
+        most_drafted: most_drafted
+            .into_iter()
+            .take(TOP_MARKS)
+            .map(|(name, count)| Tally {
+                name: name.to_string(),
+                count,
+            })
+            .collect(),
+        usage: usage
+            .into_iter()
+            .map(|(name, count)| Tally {
+                name: name.to_string(),
+                count,
+            })
+            .collect(),
+    }
+}
+
+/// Writes how often each mark and each category was drawn and drafted to a
+/// csv at `path`, returns how many rows were written.
+fn write_csv(lib: &Library, results: &[DraftResult], path: &Path) -> anyhow::Result<usize> {
+    let drafted = || results.iter().flat_map(|r| &r.marks);
+    let rerolled = || {
+        results
+            .iter()
+            .flat_map(|r| r.rerolls.iter().map(|(_, m)| m))
+    };
+    let count = |matches: &dyn Fn(&Mark) -> bool| {
+        let drafted = drafted().filter(|m| matches(m)).count();
+        (drafted + rerolled().filter(|m| matches(m)).count(), drafted)
+    };
+
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut rows = 0;
+    let mut names = BTreeSet::new();
+    for (mark, _) in &lib.list {
+        if !names.insert(&mark.name) {
+            continue;
+        }
+        let (drawn, drafted) = count(&|m| m.name == mark.name);
+        writer.serialize(CsvRow {
+            kind: "mark",
+            name: &mark.name,
+            drawn,
+            drafted,
+        })?;
+        rows += 1;
+    }
+    for category in &lib.categories {
+        let (drawn, drafted) = count(&|m| lib.same_label(&m.category, category));
+        writer.serialize(CsvRow {
+            kind: "category",
+            name: category,
+            drawn,
+            drafted,
+        })?;
+        rows += 1;
+    }
+
+    writer.flush()?;
+    Ok(rows)
+}
+
+fn print_tables(stats: &Stats) {
+    let lib = &stats.library;
+    println!("{} marks, {} free", lib.marks, lib.free);
+
+    let counts = |title: &str, counts: &[Count]| {
+        let rows: Vec<_> = counts
+            .iter()
+            .map(|c| [c.name.clone(), c.marks.to_string(), c.free.to_string()])
+            .collect();
+        print_table([title, "Marks", "Free"], &rows);
+    };
+    counts("Power", &lib.powers);
+    counts("Category", &lib.categories);
+    counts("Tag", &lib.tags);
+
+    let Some(results) = &stats.results else {
+        return;
+    };
+    println!(
+        "\n{} drafts, {} marks drafted, {} rerolls, {} wishlisted",
+        results.drafts, results.marks, results.rerolls, results.wishlisted
+    );
+
+    let share = |count: usize| match results.marks {
+        0 => "-".to_string(),
+        total => format!("{:.1}%", count as f64 * 100.0 / total as f64),
+    };
+    let rows: Vec<_> = results
+        .powers
+        .iter()
+        .map(|t| [t.name.clone(), t.count.to_string(), share(t.count)])
+        .collect();
+    print_table(["Power", "Drafted", "Share"], &rows);
+
+    let rows: Vec<_> = results
+        .players
+        .iter()
+        .map(|p| {
+            [
+                p.name.clone().unwrap_or_else(|| "<unassigned>".to_string()),
+                p.drafts.to_string(),
+                p.marks.to_string(),
+            ]
+        })
+        .collect();
+    print_table(["Player", "Drafts", "Marks"], &rows);
+
+    let rows: Vec<_> = results
+        .most_drafted
+        .iter()
+        .map(|t| [t.name.clone(), t.count.to_string()])
+        .collect();
+    print_table(["Most drafted", "Times"], &rows);
+
+    print_usage(&results.usage);
+}
+
+/// Charts how often each mark was drawn, the bars scaled to the most drawn
+/// one, to spot the marks no draft ever picks.
+fn print_usage(usage: &[Tally]) {
+    let Some(most) = usage.first().map(|t| t.count) else {
+        return;
+    };
+    let name_width = usage
+        .iter()
+        .map(|t| t.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let count_width = most.to_string().len();
+
+    println!("\nUsage");
+    for tally in usage.iter().filter(|t| t.count > 0) {
+        // at least one block, so every drawn mark shows up
+        let bar = cmp::max(tally.count * BAR_WIDTH / most, 1);
+        println!(
+            "{:name_width$}  {:>count_width$}  {}",
+            tally.name,
+            tally.count,
+            "█".repeat(bar)
+        );
+    }
+
+    let never: Vec<_> = usage.iter().filter(|t| t.count == 0).collect();
+    if !never.is_empty() {
+        let names: String = never
+            .iter()
+            .map(|t| t.name.as_str())
+            .intersperse(", ")
+            .collect();
+        println!("Never drawn ({}): {names}", never.len());
+    }
+}
+
+/// Prints a table with the first column aligned left and the others, which
+/// hold numbers, aligned right.
+fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    let mut widths = header.map(|h| h.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: [&str; N]| {
+        let mut line = String::new();
+        for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            let pad = " ".repeat(width - cell.chars().count());
+            if i == 0 {
+                line.push_str(cell);
+                line.push_str(&pad);
+            } else {
+                line.push_str("  ");
+                line.push_str(&pad);
+                line.push_str(cell);
+            }
+        }
+        println!("{}", line.trim_end());
+    };
+
+    println!();
+    line(header);
+    for row in rows {
+        line(row.each_ref().map(String::as_str));
+    }
+}
@@ -0,0 +1,210 @@
+#[cfg(feature = "sqlite")]
+pub use sqlite::{read, write};
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::{collections::HashMap, path::Path};
+
+    use anyhow::{format_err, Context};
+    use rusqlite::{params, Connection, OptionalExtension};
+    use serde_json::{json, Map, Value};
+
+    use crate::SaveFile;
+
+    /// Marks, results and presets are rows holding the same JSON a `.json`
+    /// save would, the history of which marks were drafted is kept apart so
+    /// it can be queried.
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+        CREATE TABLE IF NOT EXISTS marks (
+            position INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            free INTEGER NOT NULL,
+            data TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS marks_name ON marks (name);
+        CREATE TABLE IF NOT EXISTS results (position INTEGER PRIMARY KEY, data TEXT NOT NULL);
+        CREATE TABLE IF NOT EXISTS history (
+            result INTEGER NOT NULL,
+            slot INTEGER NOT NULL,
+            mark TEXT NOT NULL,
+            player TEXT,
+            PRIMARY KEY (result, slot)
+        );
+        CREATE INDEX IF NOT EXISTS history_mark ON history (mark);
+        CREATE TABLE IF NOT EXISTS presets (name TEXT PRIMARY KEY, marks TEXT NOT NULL);
+    ";
+
+    fn open(path: &Path) -> anyhow::Result<Connection> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Could not open the database {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("{} is not a save database", path.display()))?;
+        Ok(conn)
+    }
+
+    /// Reads a save database like the text of a save, so it can be migrated.
+    pub fn read(path: &Path) -> anyhow::Result<Value> {
+        if !path.exists() {
+            // opening would create an empty database in its place
+            anyhow::bail!("{} does not exist", path.display());
+        }
+        let conn = open(path)?;
+        let meta = |key: &str| -> anyhow::Result<Option<Value>> {
+            let text: Option<String> = conn
+                .query_row("SELECT value FROM meta WHERE key = ?1", [key], |r| r.get(0))
+                .optional()?;
+            Ok(text.map(|t| serde_json::from_str(&t)).transpose()?)
+        };
+
+        let version =
+            meta("version")?.ok_or_else(|| format_err!("{} holds no save", path.display()))?;
+        let mut library = meta("library")?.unwrap_or_else(|| json!({}));
+
+        let mut marks = Vec::new();
+        let mut rows = conn.prepare("SELECT data, free FROM marks ORDER BY position")?;
+        let mut rows = rows.query([])?;
+        while let Some(row) = rows.next()? {
+            let mark: Value = serde_json::from_str(&row.get::<_, String>(0)?)?;
+            marks.push(json!([mark, row.get::<_, bool>(1)?]));
+        }
+        library["list"] = Value::Array(marks);
+
+        let mut results = Vec::new();
+        let mut rows = conn.prepare("SELECT data FROM results ORDER BY position")?;
+        let mut rows = rows.query([])?;
+        while let Some(row) = rows.next()? {
+            results.push(serde_json::from_str::<Value>(&row.get::<_, String>(0)?)?);
+        }
+
+        let mut presets = Map::new();
+        let mut rows = conn.prepare("SELECT name, marks FROM presets")?;
+        let mut rows = rows.query([])?;
+        while let Some(row) = rows.next()? {
+            let marks: Value = serde_json::from_str(&row.get::<_, String>(1)?)?;
+            presets.insert(row.get(0)?, marks);
+        }
+
+        Ok(json!({
+            "version": version,
+            "library": library,
+            "results": { "results": results, "session": meta("session")? },
+            "presets": presets,
+        }))
+    }
+
+    /// Writes the rows of `save` that differ from those in the database at
+    /// `path`, creating it if there is none. Big campaigns save without
+    /// rewriting every mark and result.
+    pub fn write(path: &Path, save: &SaveFile) -> anyhow::Result<()> {
+        let mut conn = open(path)?;
+        let tx = conn.transaction()?;
+
+        // everything but the marks changes rarely and is small
+        let mut library = serde_json::to_value(&save.library)?;
+        if let Some(library) = library.as_object_mut() {
+            library.remove("list");
+        }
+        for (key, value) in [
+            ("version", json!(save.version)),
+            ("library", library),
+            ("session", json!(save.results.session())),
+        ] {
+            tx.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+                params![key, value.to_string()],
+            )?;
+        }
+
+        let mut stored: HashMap<i64, (String, bool, String)> = HashMap::new();
+        {
+            let mut rows = tx.prepare("SELECT position, name, free, data FROM marks")?;
+            let mut rows = rows.query([])?;
+            while let Some(row) = rows.next()? {
+                stored.insert(row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?));
+            }
+        }
+        for (i, (mark, free)) in save.library.list.iter().enumerate() {
+            let row = (mark.name.clone(), *free, serde_json::to_string(mark)?);
+            if stored.get(&(i as i64)) != Some(&row) {
+                tx.execute(
+                    "INSERT OR REPLACE INTO marks (position, name, free, data) \
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![i as i64, row.0, row.1, row.2],
+                )?;
+            }
+        }
+        tx.execute(
+            "DELETE FROM marks WHERE position >= ?1",
+            [save.library.list.len() as i64],
+        )?;
+
+        let mut stored: HashMap<i64, String> = HashMap::new();
+        {
+            let mut rows = tx.prepare("SELECT position, data FROM results")?;
+            let mut rows = rows.query([])?;
+            while let Some(row) = rows.next()? {
+                stored.insert(row.get(0)?, row.get(1)?);
+            }
+        }
+        let results = save.results.list();
+        for (i, result) in results.iter().enumerate() {
+            let data = serde_json::to_string(result)?;
+            if stored.get(&(i as i64)) == Some(&data) {
+                continue;
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO results (position, data) VALUES (?1, ?2)",
+                params![i as i64, data],
+            )?;
+            tx.execute("DELETE FROM history WHERE result = ?1", [i as i64])?;
+            for (slot, mark) in result.marks.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO history (result, slot, mark, player) VALUES (?1, ?2, ?3, ?4)",
+                    params![i as i64, slot as i64, mark.name, result.options.player],
+                )?;
+            }
+        }
+        for sql in [
+            "DELETE FROM results WHERE position >= ?1",
+            "DELETE FROM history WHERE result >= ?1",
+        ] {
+            tx.execute(sql, [results.len() as i64])?;
+        }
+
+        let mut stored: HashMap<String, String> = HashMap::new();
+        {
+            let mut rows = tx.prepare("SELECT name, marks FROM presets")?;
+            let mut rows = rows.query([])?;
+            while let Some(row) = rows.next()? {
+                stored.insert(row.get(0)?, row.get(1)?);
+            }
+        }
+        for (name, marks) in &save.presets {
+            let marks = serde_json::to_string(marks)?;
+            if stored.remove(name).as_ref() != Some(&marks) {
+                tx.execute(
+                    "INSERT OR REPLACE INTO presets (name, marks) VALUES (?1, ?2)",
+                    params![name, marks],
+                )?;
+            }
+        }
+        // what is left was deleted in the app
+        for name in stored.keys() {
+            tx.execute("DELETE FROM presets WHERE name = ?1", [name])?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn read(_: &std::path::Path) -> anyhow::Result<serde_json::Value> {
+    anyhow::bail!("This build does not include SQLite support")
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn write(_: &std::path::Path, _: &crate::SaveFile) -> anyhow::Result<()> {
+    anyhow::bail!("This build does not include SQLite support")
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+
+use rand::{seq::SliceRandom, RngCore};
+
+use crate::{DraftOptions, Library, Mark, ShuffleBags};
+
+/// What a strategy gets to know about the draft it picks a mark for.
+pub struct Picking<'a> {
+    pub lib: &'a Library,
+    pub options: &'a DraftOptions,
+    /// The marks of the draft so far, pinned ones included.
+    pub drafted: &'a [&'a Mark],
+    /// A mark's weight in this draft, with wishlists and the budget applied.
+    pub weight: &'a dyn Fn(&Mark) -> f64,
+    /// What bag drafts draw out of, kept in the library between drafts.
+    pub bags: &'a mut ShuffleBags,
+}
+
+/// How a draw picks one of the marks that fit it. Add an implementation to
+/// [`STRATEGIES`] to make it selectable.
+pub trait DrawStrategy: Sync {
+    /// The name drafts record the strategy with.
+    fn name(&self) -> &'static str;
+
+    /// What the draft editor shows for a draft using the strategy.
+    fn label(&self) -> &'static str;
+
+    /// Picks a mark of `pool`, `None` if it can't pick any and the draw gets
+    /// a placeholder.
+    fn pick<'m>(
+        &self,
+        pool: &[&'m Mark],
+        picking: &mut Picking,
+        rng: &mut dyn RngCore,
+    ) -> Option<&'m Mark>;
+}
+
+/// Every strategy, the first is what drafts that don't pick one use.
+pub const STRATEGIES: &[&dyn DrawStrategy] = &[&Weighted, &Uniform, &Balanced, &Bag];
+
+/// The strategy called `name`, the default for no or an unknown name.
+pub fn get(name: Option<&str>) -> &'static dyn DrawStrategy {
+    name.and_then(|name| {
+        STRATEGIES
+            .iter()
+            .find(|s| s.name().eq_ignore_ascii_case(name))
+    })
+    .copied()
+    .unwrap_or(STRATEGIES[0])
+}
+
+/// The name of the strategy after `name`'s, `None` for the default.
+pub fn next(name: Option<&str>) -> Option<String> {
+    let current = get(name);
+    let i = STRATEGIES
+        .iter()
+        .position(|s| s.name() == current.name())
+        .unwrap();
+    match (i + 1) % STRATEGIES.len() {
+        0 => None,
+        next => Some(STRATEGIES[next].name().to_string()),
+    }
+}
+
+/// By weight, the library's way to make some marks likelier than others.
+struct Weighted;
+
+impl DrawStrategy for Weighted {
+    fn name(&self) -> &'static str {
+        "weighted"
+    }
+
+    fn label(&self) -> &'static str {
+        "Drawn by weight"
+    }
+
+    fn pick<'m>(
+        &self,
+        pool: &[&'m Mark],
+        picking: &mut Picking,
+        rng: &mut dyn RngCore,
+    ) -> Option<&'m Mark> {
+        pool.choose_weighted(rng, |m| (picking.weight)(m))
+            .ok()
+            .copied()
+    }
+}
+
+/// Every mark that fits equally likely, whatever its weight.
+struct Uniform;
+
+impl DrawStrategy for Uniform {
+    fn name(&self) -> &'static str {
+        "uniform"
+    }
+
+    fn label(&self) -> &'static str {
+        "Every mark equally likely"
+    }
+
+    fn pick<'m>(
+        &self,
+        pool: &[&'m Mark],
+        _: &mut Picking,
+        rng: &mut dyn RngCore,
+    ) -> Option<&'m Mark> {
+        pool.choose(rng).copied()
+    }
+}
+
+/// Spreads the draft over the categories in proportion to how many free
+/// marks each has, so it isn't all from one of them.
+struct Balanced;
+
+impl DrawStrategy for Balanced {
+    fn name(&self) -> &'static str {
+        "balanced"
+    }
+
+    fn label(&self) -> &'static str {
+        "Balanced across categories"
+    }
+
+    fn pick<'m>(
+        &self,
+        pool: &[&'m Mark],
+        picking: &mut Picking,
+        rng: &mut dyn RngCore,
+    ) -> Option<&'m Mark> {
+        let lib = picking.lib;
+        let mut shares: BTreeMap<&str, f64> = BTreeMap::new();
+        let free = lib
+            .list
+            .iter()
+            .filter(|(m, free)| *free && !picking.options.banned.contains(&m.name));
+        for (mark, _) in free {
+            *shares.entry(lib.category_of(mark)).or_default() += 1.0;
+        }
+        let total: f64 = shares.values().sum();
+
+        // a category is picked by how far it is behind its share of the
+        // draft so far, then one of its marks by weight
+        let mut totals: BTreeMap<&str, f64> = BTreeMap::new();
+        for m in pool {
+            *totals.entry(lib.category_of(m)).or_default() += (picking.weight)(m);
+        }
+        let balanced = |m: &&Mark| {
+            let category = lib.category_of(m);
+            let due = shares.get(category).copied().unwrap_or(0.0) / total
+                * (picking.drafted.len() + 1) as f64;
+            let drawn = picking
+                .drafted
+                .iter()
+                .filter(|d| lib.same_label(&d.category, category))
+                .count();
+            let behind = (due - drawn as f64).max(0.0);
+            match totals[category] {
+                total if total > 0.0 => behind * (picking.weight)(m) / total,
+                _ => 0.0,
+            }
+        };
+        match pool.choose_weighted(rng, balanced) {
+            Ok(mark) => Some(*mark),
+            Err(_) => Weighted.pick(pool, picking, rng),
+        }
+    }
+}
+
+/// Draws each tier's marks out of a bag, none comes up again before the rest
+/// of its tier did, see [`ShuffleBags`].
+struct Bag;
+
+impl DrawStrategy for Bag {
+    fn name(&self) -> &'static str {
+        "bag"
+    }
+
+    fn label(&self) -> &'static str {
+        "Drawn from shuffle bags"
+    }
+
+    fn pick<'m>(
+        &self,
+        pool: &[&'m Mark],
+        picking: &mut Picking,
+        rng: &mut dyn RngCore,
+    ) -> Option<&'m Mark> {
+        // only what is left in the bags, unless none of that fits
+        let left: Vec<&Mark> = pool
+            .iter()
+            .copied()
+            .filter(|m| picking.bags.contains(m))
+            .collect();
+        let pool = if left.is_empty() { pool } else { &left };
+        let mark = Weighted.pick(pool, picking, rng)?;
+        picking.bags.take(mark, &picking.lib.list);
+        Some(mark)
+    }
+}
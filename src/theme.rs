@@ -0,0 +1,222 @@
+use std::{
+    fs,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{bail, Context};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::config;
+
+static BUILTIN: &str = include_str!("themes.toml");
+
+static CURRENT: RwLock<Option<Arc<Theme>>> = RwLock::new(None);
+static THEMES: RwLock<Vec<Arc<Theme>>> = RwLock::new(Vec::new());
+
+/// Colors of the UI, see `themes.toml` for the file format.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub border_active: Style,
+    pub border_inactive: Style,
+    pub highlight: Style,
+    /// Error messages and things that went wrong.
+    pub error: Style,
+    /// Things worth a second look, like unsaved changes.
+    pub warning: Style,
+    /// Messages that something worked.
+    pub success: Style,
+    /// Secondary text: hints, placeholders and counts.
+    pub muted: Style,
+    /// Popup titles and the letters of key bindings.
+    pub title: Style,
+    /// Pinned and picked marks.
+    pub pinned: Style,
+    /// Replaces the library's power colors, from the lowest tier up. Tiers
+    /// past the end of the ramp use its last color.
+    pub power_ramp: Option<Vec<Color>>,
+    /// Powers are drawn without any color.
+    pub monochrome: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeToml {
+    name: String,
+    #[serde(default)]
+    border_active: String,
+    #[serde(default = "default_border_inactive")]
+    border_inactive: String,
+    #[serde(default = "default_highlight")]
+    highlight: String,
+    #[serde(default = "default_error")]
+    error: String,
+    #[serde(default = "default_warning")]
+    warning: String,
+    #[serde(default = "default_success")]
+    success: String,
+    #[serde(default = "default_muted")]
+    muted: String,
+    #[serde(default = "default_title")]
+    title: String,
+    #[serde(default = "default_pinned")]
+    pinned: String,
+    #[serde(default)]
+    power_ramp: Option<Vec<String>>,
+    #[serde(default)]
+    monochrome: bool,
+}
+
+#[derive(Deserialize)]
+struct ThemesToml {
+    themes: Vec<ThemeToml>,
+}
+
+fn default_border_inactive() -> String {
+    "darkgray".to_string()
+}
+
+fn default_highlight() -> String {
+    "reversed".to_string()
+}
+
+fn default_error() -> String {
+    "red".to_string()
+}
+
+fn default_warning() -> String {
+    "yellow".to_string()
+}
+
+fn default_success() -> String {
+    "green".to_string()
+}
+
+fn default_muted() -> String {
+    "darkgray".to_string()
+}
+
+fn default_title() -> String {
+    "red".to_string()
+}
+
+fn default_pinned() -> String {
+    "yellow".to_string()
+}
+
+fn parse_color(s: &str) -> anyhow::Result<Color> {
+    s.parse()
+        .ok()
+        .with_context(|| format!("{s:?} is not a color"))
+}
+
+/// Parses a style like "bold white on blue".
+fn parse_style(s: &str) -> anyhow::Result<Style> {
+    let mut style = Style::default();
+    let mut words = s.split_whitespace();
+    while let Some(word) = words.next() {
+        style = match word {
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underlined" => style.add_modifier(Modifier::UNDERLINED),
+            "reversed" => style.add_modifier(Modifier::REVERSED),
+            "on" => match words.next() {
+                Some(color) => style.bg(parse_color(color)?),
+                None => bail!("{s:?} is missing a color after \"on\""),
+            },
+            color => style.fg(parse_color(color)?),
+        };
+    }
+    Ok(style)
+}
+
+impl TryFrom<ThemeToml> for Theme {
+    type Error = anyhow::Error;
+
+    fn try_from(theme: ThemeToml) -> anyhow::Result<Theme> {
+        let style = |s: &str| parse_style(s).with_context(|| format!("in theme {}", theme.name));
+        Ok(Theme {
+            border_active: style(&theme.border_active)?,
+            border_inactive: style(&theme.border_inactive)?,
+            highlight: style(&theme.highlight)?,
+            error: style(&theme.error)?,
+            warning: style(&theme.warning)?,
+            success: style(&theme.success)?,
+            muted: style(&theme.muted)?,
+            title: style(&theme.title)?,
+            pinned: style(&theme.pinned)?,
+            power_ramp: match &theme.power_ramp {
+                Some(ramp) if ramp.is_empty() => {
+                    bail!("theme {} has an empty power_ramp", theme.name)
+                }
+                Some(ramp) => Some(
+                    ramp.iter()
+                        .map(|c| parse_color(c))
+                        .collect::<Result<_, _>>()?,
+                ),
+                None => None,
+            },
+            monochrome: theme.monochrome,
+            name: theme.name,
+        })
+    }
+}
+
+fn parse_themes(text: &str) -> anyhow::Result<Vec<Theme>> {
+    let ThemesToml { themes } = toml::from_str(text)?;
+    themes.into_iter().map(Theme::try_from).collect()
+}
+
+/// Loads the built in themes and the user's, and makes `selected` current
+/// if there is a theme by that name. Problems with the user's themes are
+/// logged and the built in ones used anyway.
+pub fn load(selected: Option<&str>) {
+    let mut themes = parse_themes(BUILTIN).expect("the built in themes are valid");
+
+    if let Some(path) = config::dir().map(|d| d.join("themes.toml")) {
+        if let Ok(text) = fs::read_to_string(&path) {
+            match parse_themes(&text) {
+                Ok(user) => {
+                    for theme in user {
+                        themes.retain(|t| t.name != theme.name);
+                        themes.push(theme);
+                    }
+                }
+                Err(e) => log::warn!("Ignoring {}: {e:#}", path.display()),
+            }
+        }
+    }
+
+    let themes: Vec<_> = themes.into_iter().map(Arc::new).collect();
+    let current = selected
+        .and_then(|name| themes.iter().find(|t| t.name == name))
+        .unwrap_or(&themes[0])
+        .clone();
+    *CURRENT.write().unwrap() = Some(current);
+    *THEMES.write().unwrap() = themes;
+}
+
+pub fn current() -> Arc<Theme> {
+    if let Some(theme) = &*CURRENT.read().unwrap() {
+        return theme.clone();
+    }
+    // only reached before `load`, e.g. in --no-tui mode
+    let theme = parse_themes(BUILTIN).expect("the built in themes are valid");
+    Arc::new(theme.into_iter().next().unwrap())
+}
+
+/// Switches to the next theme and returns its name.
+pub fn cycle() -> String {
+    let themes = THEMES.read().unwrap();
+    let current = current();
+    if themes.is_empty() {
+        return current.name.clone();
+    }
+    let i = themes.iter().position(|t| t.name == current.name);
+    let next = themes[i.map_or(0, |i| (i + 1) % themes.len())].clone();
+    let name = next.name.clone();
+    *CURRENT.write().unwrap() = Some(next);
+    name
+}
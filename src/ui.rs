@@ -1,15 +1,43 @@
-use std::{cmp, fs::File, io::Write, ops::ControlFlow};
+use std::{
+    cmp,
+    collections::{BTreeMap, BTreeSet},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use rand::prelude::*;
 use ratatui::{layout::Flex, prelude::*, style::Stylize, widgets::*};
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::{Draw, Library, Mark, Power, SaveFile};
+use crate::{
+    calibrate::{self, Calibration},
+    config::Config,
+    config::Release,
+    debug_log,
+    diff::LibraryDiff,
+    discord, export,
+    filter::Filter,
+    format::{self, SaveFormat},
+    migrate, picker,
+    snake::SnakeDraft,
+    strategy, tag_tree, theme,
+    validate::{self, Problem, Severity},
+    watch::Watcher,
+    Budget, DraftOptions, DraftResult, Draw, Exclusion, History, Library, Mark, Player, Power,
+    PowerConstraint, PowerTiers, Preview, Recency, SaveFile, NO_MARK,
+};
 
 const CONT: ControlFlow<()> = ControlFlow::Continue(());
 const BREAK: ControlFlow<()> = ControlFlow::Break(());
 
+const MIN_WIDTH: u16 = 80;
+const MIN_HEIGHT: u16 = 24;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Pane {
     Left,
@@ -20,6 +48,7 @@ pub enum Pane {
 pub enum Tab {
     DraftCreation,
     Results,
+    Snake,
 }
 
 pub struct UiState<'a> {
@@ -27,8 +56,84 @@ pub struct UiState<'a> {
     pub terminal: &'a mut crate::Terminal,
     save_box: Prompt<'static>,
     is_saving: bool,
+    calibrate_box: Prompt<'static>,
+    is_calibrating: bool,
+    calibration: Option<Calibration>,
+    player_box: Prompt<'static>,
+    is_naming_player: bool,
+    session_box: Prompt<'static>,
+    is_naming_session: bool,
+    draft_name_box: Prompt<'static>,
+    is_naming_draft: bool,
+    budget_box: Prompt<'static>,
+    is_budgeting: bool,
+    recency_box: Prompt<'static>,
+    is_setting_recency: bool,
+    filter_box: Prompt<'static>,
+    is_filtering: bool,
+    note_box: NoteBox,
+    is_noting: bool,
+    roster: Option<Roster>,
+    /// Saved availability snapshots, see [`PresetList`].
+    presets: BTreeMap<String, BTreeSet<String>>,
+    preset_list: Option<PresetList>,
+    label_manager: Option<LabelManager>,
+    replay: Option<Replay>,
+    dry_run: Option<DryRun>,
+    /// Asks where to export the results to, and in which format.
+    export_box: Option<(Prompt<'static>, export::Format)>,
+    /// Asks which exported result to add to the results.
+    import_box: Option<Prompt<'static>>,
+    /// Asks which library file to compare the library with.
+    diff_box: Option<Prompt<'static>>,
+    diff: Option<DiffView>,
+    /// Problems found in the library on load, until dismissed.
+    report: Option<Report>,
+    /// Opened on the first copy, on X11 the copied text is only available
+    /// for as long as the clipboard it was set on lives.
+    clipboard: Option<arboard::Clipboard>,
+    /// A drafted mark whose details are shown in a popup.
+    mark_detail: Option<Mark>,
+    column_chooser: Option<ListState>,
+    tag_picker: Option<TagPicker>,
+    /// The picked power and, for a range, whether it is the top of it.
+    power_picker: Option<(ListState, Option<bool>)>,
+    /// A yes/no question and what to do if the answer is yes.
+    asking: Option<(YesNo, Pending)>,
+    /// The library, the results or the presets changed since the last save,
+    /// set by whatever changes them.
+    unsaved: bool,
+    /// Counts the keys and pastes handled, any of which may have changed the
+    /// library or the draft.
+    edits: u64,
+    /// The `edits` the draws were last checked at, see
+    /// [`UiState::check_draws`].
+    checked: Option<u64>,
     show_help: bool,
+    /// Release notes shown once after an upgrade, until any key is pressed.
+    pub whats_new: Vec<Release>,
+    /// Something that went wrong, shown until any key is pressed.
+    error: Option<String>,
+    /// Where the session was loaded from or last saved to.
+    pub file: String,
+    /// The save Ctrl+S writes to, the one loaded or last saved as.
+    pub path: Option<PathBuf>,
+    /// A file picked with Ctrl+O, the session closes to open it.
+    pub opened: Option<(PathBuf, SaveFile)>,
+    /// The library files, offered to be reloaded when they change.
+    pub watcher: Watcher,
+    /// A message for the status bar and when it was set.
+    status: Option<(String, Instant)>,
+    /// The result being posted to the webhook, away from the UI thread so
+    /// a slow server doesn't freeze it.
+    posting: Option<(usize, JoinHandle<anyhow::Result<()>>)>,
+    /// Ticks since the app started, for things that change over time.
+    ticks: u64,
+    /// The terminal is below the minimum size and only the quit keys work.
+    too_small: bool,
+    show_log: bool,
     draft_view: DraftView,
+    snake: SnakeView,
     tab: Tab,
     results: Results,
     rng: ThreadRng,
@@ -38,6 +143,14 @@ pub struct DraftView {
     pub selected_tab: Pane,
     pub mark_list: MarkList,
     pub draft: DraftEditor,
+    /// The other drafts being prepared, `draft` goes between them as the
+    /// `current`th of all.
+    others: Vec<DraftEditor>,
+    current: usize,
+    /// Armed by closing a draft that has draws.
+    closing: Confirm,
+    /// Why the draft could not be run, until the next key.
+    message: Option<&'static str>,
 }
 
 impl<'a> UiState<'a> {
@@ -45,898 +158,6079 @@ impl<'a> UiState<'a> {
         library: &'a mut Library,
         terminal: &'a mut crate::Terminal,
         results: Results,
+        presets: BTreeMap<String, BTreeSet<String>>,
     ) -> Self {
-        let len = library.list.len();
-        UiState {
+        let draft_view = DraftView::new(library);
+        let mut problems = validate::check_library(library);
+        problems.extend(validate::check_results(library, results.list(), "results"));
+        let report = Report::new(problems);
+        let mut state = UiState {
             library,
             terminal,
             results,
             save_box: Prompt {
-                title: Line::raw("Save as"),
-                postfix: Span::raw(".json"),
+                title: Line::raw("Save as (.json, .json.gz, .toml, .yaml, .ron or .db)"),
+                max_width: 48,
+                completer: Some(complete_path),
+                ..Default::default()
+            },
+            calibrate_box: Prompt {
+                max_width: 40,
+                ..Default::default()
+            },
+            is_calibrating: false,
+            calibration: None,
+            player_box: Prompt {
+                title: Line::raw("Assign to player"),
+                max_width: 32,
+                ..Default::default()
+            },
+            is_naming_player: false,
+            session_box: Prompt {
+                title: Line::raw("Start a session (blank to stop grouping new drafts)"),
                 max_width: 32,
                 ..Default::default()
             },
+            is_naming_session: false,
+            draft_name_box: Prompt {
+                title: Line::raw("Name the draft (blank for none)"),
+                max_width: 32,
+                ..Default::default()
+            },
+            is_naming_draft: false,
+            budget_box: Prompt {
+                max_width: 40,
+                ..Default::default()
+            },
+            is_budgeting: false,
+            recency_box: Prompt {
+                max_width: 40,
+                ..Default::default()
+            },
+            is_setting_recency: false,
+            filter_box: Prompt {
+                max_width: 72,
+                ..Default::default()
+            },
+            is_filtering: false,
+            note_box: NoteBox::default(),
+            is_noting: false,
+            roster: None,
+            presets,
+            preset_list: None,
+            label_manager: None,
+            replay: None,
+            dry_run: None,
+            mark_detail: None,
+            export_box: None,
+            clipboard: None,
+            import_box: None,
+            diff_box: None,
+            diff: None,
+            report,
+            column_chooser: None,
+            tag_picker: None,
+            power_picker: None,
+            asking: None,
+            unsaved: false,
+            edits: 0,
+            checked: None,
             show_help: false,
+            whats_new: Vec::new(),
+            error: None,
+            file: String::new(),
+            path: None,
+            opened: None,
+            watcher: Watcher::default(),
+            status: None,
+            posting: None,
+            ticks: 0,
+            too_small: false,
+            show_log: false,
             is_saving: false,
-            draft_view: DraftView::new(len),
+            draft_view,
+            snake: SnakeView::default(),
             tab: Tab::DraftCreation,
             rng: rand::thread_rng(),
+        };
+        if let Ok(size) = state.terminal.size() {
+            state.too_small = size.width < MIN_WIDTH || size.height < MIN_HEIGHT;
         }
+        state
     }
 
-    pub fn input(&mut self, ev: KeyEvent) -> anyhow::Result<ControlFlow<()>> {
-        match ev.code {
-            KeyCode::Char('s' | 'S') => {
-                self.is_saving = true;
-            }
-            KeyCode::Char('?') => {
-                self.show_help = true;
-            }
-            KeyCode::Esc if self.show_help => {
-                self.show_help = false;
-            }
-            _ if self.is_saving => {
-                let res = self.save_box.input(ev);
-                self.is_saving = match res {
-                    ControlFlow::Continue(_) => true,
-                    ControlFlow::Break(b) => {
-                        if b {
-                            save(&self.library, &self.results, &self.save_box.text)?;
-                        }
-                        false
-                    }
-                };
-            }
-            KeyCode::Esc | KeyCode::Char('q' | 'Q') => return Ok(BREAK),
-            KeyCode::Char('d' | 'D') => {
-                self.tab = Tab::DraftCreation;
-            }
-            KeyCode::Char('r' | 'R') => {
-                self.tab = Tab::Results;
-            }
-            KeyCode::Enter
-                if self.draft_view.selected_tab == Pane::Left && self.tab == Tab::DraftCreation =>
-            {
-                let marks = self
-                    .library
-                    .exec_draw(self.draft_view.draft.draws.clone(), &mut self.rng);
-                self.results
-                    .results
-                    .push((marks, self.draft_view.draft.draws.clone()));
-                self.tab = Tab::Results;
-                self.results
-                    .state
-                    .select(Some(self.results.results.len() - 1));
-            }
-            _ if self.tab == Tab::DraftCreation => {
-                return Ok(self.draft_view.input(&mut self.library, ev))
+    /// Repaints the whole screen for the new size. The panes work out their
+    /// scroll positions from the new size when they are drawn next.
+    pub fn resize(&mut self, width: u16, height: u16) -> anyhow::Result<()> {
+        self.too_small = width < MIN_WIDTH || height < MIN_HEIGHT;
+        self.terminal.autoresize()?;
+        self.terminal.clear()?;
+        Ok(())
+    }
+
+    /// Makes a change from the [`LabelManager`] everywhere the label is used:
+    /// the marks, the draws being edited and the draws of the results.
+    fn edit_labels(&mut self, edit: LabelEdit) {
+        self.unsaved = true;
+        let (from, to) = match &edit {
+            LabelEdit::CreateCategory(name) => {
+                self.library.categories.insert(name.clone());
+                self.status = Some((format!("Added the category {name}"), Instant::now()));
+                return;
             }
-            k if self.tab == Tab::Results => {
-                self.results.input(k);
+            LabelEdit::RenameTag(from, to)
+            | LabelEdit::RenameCategory(from, to)
+            | LabelEdit::DeleteCategory(from, to) => (from.as_str(), to.as_str()),
+        };
+        let tag = matches!(edit, LabelEdit::RenameTag(..));
+        let marks = if tag {
+            self.library.rename_tag(from, to)
+        } else {
+            self.library.rename_category(from, to)
+        };
+        let draws = self
+            .results
+            .results
+            .iter_mut()
+            .flat_map(|r| &mut r.draws)
+            .chain(self.draft_view.drafts_mut().flat_map(|d| &mut d.draws));
+        for draw in draws {
+            if tag {
+                draw.rename_tag(from, to, self.library);
+            } else {
+                draw.rename_category(from, to, self.library);
             }
-            _ => {}
+        }
+        if !tag {
+            self.draft_view
+                .mark_list
+                .rename_category(self.library, from, to);
         }
 
-        Ok(CONT)
+        let status = match &edit {
+            LabelEdit::DeleteCategory(..) if to.is_empty() => {
+                format!("Deleted {from}, {marks} marks have no category now")
+            }
+            LabelEdit::DeleteCategory(..) => {
+                format!("Deleted {from}, moved its {marks} marks to {to}")
+            }
+            _ => format!("Renamed {from} to {to} on {marks} marks"),
+        };
+        self.status = Some((status, Instant::now()));
     }
 
-    pub fn draw(&mut self) -> anyhow::Result<()> {
-        let term = &mut self.terminal;
+    fn ask(&mut self, question: String, action: Pending) {
+        self.asking = Some((YesNo::new(question), action));
+    }
 
-        term.clear()?;
-        term.draw(|f| {
-            let layout = Layout::new(
-                Direction::Vertical,
-                [Constraint::Length(3), Constraint::Fill(1)],
-            )
-            .split(f.size());
-            let tabs = Tabs::new([
-                Line::default().spans(["D".underlined().red(), Span::raw("raft")]),
-                Line::default().spans(["R".underlined().red(), Span::raw("esults")]),
-            ])
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded),
-            )
-            .select(match self.tab {
-                Tab::DraftCreation => 0,
-                Tab::Results => 1,
-            });
-            f.render_widget(tabs, layout[0]);
-            let block2 = Block::new()
-                .borders(Borders::LEFT | Borders::BOTTOM | Borders::RIGHT)
-                .border_type(BorderType::Rounded);
-            let inner = block2.inner(layout[1]);
-            f.render_widget(block2, layout[1]);
+    fn save(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        save(self.library, &self.results, &self.presets, &path)?;
+        Config::remember(&path);
+        self.unsaved = false;
+        // the save may be one of the library files, that change is no news
+        self.watcher.refresh();
+        self.file = path.display().to_string();
+        self.status = Some((format!("Saved to {}", self.file), Instant::now()));
+        self.path = Some(path);
+        Ok(())
+    }
 
-            match self.tab {
-                Tab::DraftCreation => self.draft_view.draw(&*self.library, f, inner),
-                Tab::Results => self.results.draw(f, inner),
+    /// Writes the results, or the selected one for JSON, to `path`.
+    fn export(&mut self, path: PathBuf, format: export::Format) -> anyhow::Result<()> {
+        let message = match format {
+            export::Format::Csv => {
+                let rows = export::write_csv(self.results.list(), &path)?;
+                format!("Exported {rows} marks to {}", path.display())
+            }
+            export::Format::Html => {
+                export::write_html(self.library, self.results.list(), &path)?;
+                format!("Wrote the report to {}", path.display())
             }
+            export::Format::Json => match self.results.selected() {
+                Some(n) => {
+                    export::write_result(&self.results.results[n], &path)?;
+                    format!("Exported Draft #{n} to {}", path.display())
+                }
+                None => return Ok(()),
+            },
+        };
+        self.status = Some((message, Instant::now()));
+        Ok(())
+    }
 
-            if self.is_saving {
-                self.save_box.draw(f, f.size());
+    /// Saves to the remembered save, or asks where to save if there is none.
+    fn quick_save(&mut self) -> anyhow::Result<()> {
+        match self.path.clone() {
+            Some(path) => self.save(path),
+            None => {
+                self.is_saving = true;
+                self.update_save_footer();
+                Ok(())
             }
-            if self.show_help {
-                show_help_popup(f);
+        }
+    }
+
+    /// Copies the selected mark, or the selected result if no mark is
+    /// selected, to the system clipboard.
+    fn copy(&mut self) -> anyhow::Result<()> {
+        let (what, text) = match self.tab {
+            Tab::DraftCreation if self.draft_view.selected_tab == Pane::Right => {
+                match self.draft_view.mark_list.selected_mark(self.library) {
+                    Some(mark) => (mark.name.clone(), export::mark_text(mark)),
+                    None => return Ok(()),
+                }
             }
-        })?;
+            Tab::Results => match (self.results.selected_slot(), self.results.selected()) {
+                (Some((n, slot)), _) => {
+                    let mark = &self.results.results[n].marks[slot];
+                    (mark.name.clone(), export::mark_text(mark))
+                }
+                (None, Some(n)) => (
+                    format!("Draft #{n}"),
+                    export::result_text(n, &self.results.results[n]),
+                ),
+                (None, None) => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
 
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self.clipboard.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        self.status = Some((format!("Copied {what} to the clipboard"), Instant::now()));
         Ok(())
     }
-}
-
-fn show_help_popup(f: &mut Frame) {
-    static HELP_TEXT: &'static str = include_str!("help_text.txt");
-    let help_text = HELP_TEXT.trim_end();
 
-    let sections = help_text.split("\n---\n");
-    let help_width = HELP_TEXT.lines().map(|l| l.len()).max().unwrap_or(0);
-    let help_height = help_text.lines().count();
+    /// The keys that do something in the current context, for the status bar.
+    fn hints(&self) -> &'static [(&'static str, &'static str)] {
+        if self.error.is_some()
+            || !self.whats_new.is_empty()
+            || self.replay.is_some()
+            || self.dry_run.is_some()
+            || self.mark_detail.is_some()
+        {
+            &[("any key", "close")]
+        } else if self.asking.is_some() {
+            &[
+                ("y", "yes"),
+                ("n", "no"),
+                ("←→", "choose"),
+                ("Enter", "answer"),
+            ]
+        } else if self.is_saving {
+            &[("Enter", "save"), ("Tab", "complete"), ("Esc", "cancel")]
+        } else if self.tag_picker.is_some() {
+            &[("Enter", "add"), ("Tab", "complete"), ("Esc", "cancel")]
+        } else if self.tab == Tab::Results && self.results.is_searching() {
+            &[("Enter", "keep"), ("Esc", "clear")]
+        } else if self.export_box.is_some() {
+            &[("Enter", "export"), ("Esc", "cancel")]
+        } else if self.import_box.is_some() {
+            &[("Enter", "import"), ("Tab", "complete"), ("Esc", "cancel")]
+        } else if self.diff_box.is_some() {
+            &[("Enter", "compare"), ("Tab", "complete"), ("Esc", "cancel")]
+        } else if self.diff.is_some() {
+            &[("↑↓", "scroll"), ("Esc", "close")]
+        } else if self.report.is_some() {
+            &[("↑↓", "move"), ("Enter", "go to mark"), ("Esc", "close")]
+        } else if self.is_noting {
+            &[("Ctrl+S", "save"), ("Esc", "cancel")]
+        } else if self.is_calibrating
+            || self.is_naming_player
+            || self.is_naming_session
+            || self.is_naming_draft
+            || self.is_budgeting
+            || self.is_setting_recency
+            || self.is_filtering
+            || self.power_picker.is_some()
+            || self.column_chooser.is_some()
+            || self.roster.is_some()
+            || self.preset_list.is_some()
+            || self.label_manager.is_some()
+            || self.calibration.is_some()
+        {
+            &[("Enter", "ok"), ("Esc", "close")]
+        } else {
+            match (self.tab, self.draft_view.selected_tab) {
+                (Tab::DraftCreation, Pane::Left) => &[
+                    ("a", "add draw"),
+                    ("p", "power"),
+                    ("c", "category"),
+                    ("l", "library"),
+                    ("t", "tag"),
+                    ("f", "filter"),
+                    ("u", "upgrade"),
+                    ("m", "mode"),
+                    ("b", "budget"),
+                    ("h", "recency"),
+                    ("v", "dry run"),
+                    ("e", "strategy"),
+                    ("g", "no repeats"),
+                    ("Space", "collapse"),
+                    ("n/N", "next/prev draw"),
+                    ("Enter", "draft"),
+                    ("Ctrl+T/W", "new/close draft"),
+                    ("[/]", "other draft"),
+                    ("Tab", "marks"),
+                    ("s", "save as"),
+                    ("Ctrl+S", "save"),
+                    ("Ctrl+O", "open"),
+                    ("?", "help"),
+                    ("q", "quit"),
+                ],
+                (Tab::DraftCreation, Pane::Right) => &[
+                    ("Enter", "toggle"),
+                    ("Space", "pick"),
+                    ("m", "visual"),
+                    ("f", "free"),
+                    ("u", "use category"),
+                    ("x", "toggle all"),
+                    ("p", "pin"),
+                    ("b", "ban"),
+                    ("g", "group"),
+                    ("o", "sort"),
+                    ("v", "columns"),
+                    ("a", "presets"),
+                    ("t", "tags/categories"),
+                    ("h", "drafted in"),
+                    ("i", "diff file"),
+                    ("Ctrl+C", "copy"),
+                    ("Tab", "draft"),
+                    ("q", "quit"),
+                ],
+                (Tab::Results, _) if self.results.mark_filter.is_some() => &[
+                    ("Backspace", "all drafts"),
+                    ("n", "note"),
+                    ("p", "replay"),
+                    ("/", "search"),
+                    ("t", "timeline"),
+                    ("q", "quit"),
+                ],
+                (Tab::Results, _) if self.results.mark_state.selected().is_some() => &[
+                    ("Enter", "details"),
+                    ("Ctrl+C", "copy"),
+                    ("e", "re-roll"),
+                    ("u", "undo re-roll"),
+                    ("Tab", "drafts"),
+                    ("q", "quit"),
+                ],
+                (Tab::Results, _) => &[
+                    ("n", "note"),
+                    ("a", "assign"),
+                    ("b", "new session"),
+                    ("Space", "collapse"),
+                    ("m/M", "archive draft/session"),
+                    ("l", "show archived"),
+                    ("f", "filter"),
+                    ("e", "re-roll"),
+                    ("u", "undo re-roll"),
+                    ("p", "replay"),
+                    ("c", "copy draws"),
+                    ("z", "undo draft"),
+                    ("Tab", "marks"),
+                    ("v", "compare"),
+                    ("Ctrl+C", "copy"),
+                    ("w", "post"),
+                    ("x", "export"),
+                    ("j", "export draft"),
+                    ("i", "import draft"),
+                    ("h", "report"),
+                    ("/", "search"),
+                    ("PgUp/PgDn", "page"),
+                    ("t", "timeline"),
+                    ("o", "players"),
+                    ("q", "quit"),
+                ],
+                (Tab::Snake, _) => &[
+                    ("Enter", "start/pick"),
+                    ("Backspace", "abandon"),
+                    ("o", "players"),
+                    ("q", "quit"),
+                ],
+            }
+        }
+    }
 
-    let c = |len| {
-        [
-            Constraint::Fill(1),
-            Constraint::Length(len),
-            Constraint::Fill(1),
-        ]
-    };
+    /// Advances the UI's clock, returns whether anything on screen changed.
+    pub fn tick(&mut self) -> bool {
+        self.ticks += 1;
 
-    let c_h = Layout::horizontal(c(help_width as u16 + 4)).split(f.size());
-    let c_v = Layout::vertical(c(help_height as u16 + 4)).split(c_h[1]);
+        let expired = self
+            .status
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed() >= STATUS_DURATION);
+        if expired {
+            self.status = None;
+        }
+        let blink = self.ticks.is_multiple_of(BLINK_TICKS) && self.unsaved;
 
-    f.render_widget(Clear, c_v[1]);
+        // a question already open is left alone, the change is seen after it
+        let mut reload = false;
+        if self.ticks.is_multiple_of(WATCH_TICKS) && self.asking.is_none() {
+            let question = match self.watcher.changed().as_slice() {
+                [] => None,
+                [path] => Some(format!("{} changed on disk, reload it?", path.display())),
+                paths => Some(format!(
+                    "{} library files changed on disk, reload them?",
+                    paths.len()
+                )),
+            };
+            if let Some(question) = question {
+                self.ask(question, Pending::Reload);
+                reload = true;
+            }
+        }
 
-    let (areas, _) = Layout::vertical(Constraint::from_lengths(
-        sections.clone().map(|s| s.lines().count() as u16),
-    ))
-    .flex(Flex::SpaceBetween)
-    .horizontal_margin(2)
-    .vertical_margin(2)
-    .split_with_spacers(c_v[1]);
-    f.render_widget(
-        Block::bordered()
-            .title("Help".red())
-            .title_alignment(Alignment::Center),
-        c_v[1],
-    );
+        let posted = self
+            .posting
+            .as_ref()
+            .is_some_and(|(_, handle)| handle.is_finished());
+        if posted {
+            let (n, handle) = self.posting.take().unwrap();
+            match handle.join() {
+                Ok(Ok(())) => self.status = Some((format!("Posted Draft #{n}"), Instant::now())),
+                Ok(Err(e)) => self.error = Some(format!("{e:#}")),
+                Err(_) => self.error = Some(format!("Posting Draft #{n} failed")),
+            }
+        }
 
-    for (c, section) in sections.enumerate() {
-        let mut lines = section.lines();
-        let section_title = lines.next().unwrap();
+        let checked = self.check_draws();
 
-        let mut max_key = 0;
-        let mut max_desc = 0;
+        expired || blink || reload || posted || checked
+    }
 
-        let rows: Vec<_> = lines
-            .map(|l| {
-                let Some((key, desc)) = l.split_once(char::is_whitespace) else {
-                    dbg!(section_title);
-                    dbg!(l);
-                    panic!("fucked up");
-                };
+    /// Drafts the draws of the Draft tab and shows the result.
+    fn execute_draft(&mut self, name: Option<String>) {
+        let mut result = self.library.draft(
+            self.draft_view.draft.draws.clone(),
+            self.draft_view.draft.options.clone(),
+            &self.results.history(self.library.recency.window),
+            &mut self.rng,
+        );
+        result.name = name;
+        let failed: Vec<_> = result
+            .marks
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.name == NO_MARK)
+            .map(|(i, _)| (i + 1).to_string())
+            .collect();
+        if !failed.is_empty() {
+            self.error = Some(format!(
+                "No free mark matched draw {}, it got a placeholder mark instead",
+                failed.join(", ")
+            ));
+        }
+        self.results.push(result);
+        self.unsaved = true;
+        self.tab = Tab::Results;
+        self.results.select(self.results.results.len() - 1);
+    }
 
-                max_key = cmp::max(max_key, key.len() + 1);
-                max_desc = cmp::max(max_desc, desc.len() + 1);
+    /// Finds the draws no free mark fits, between key presses rather than on
+    /// each of them, and only after a key or paste that may have changed the
+    /// library or the draft. Returns whether that changed which draws are
+    /// empty.
+    fn check_draws(&mut self) -> bool {
+        if self.checked == Some(self.edits) {
+            return false;
+        }
+        self.checked = Some(self.edits);
+        let draft = &self.draft_view.draft;
 
-                let mut key_styled = Line::default();
-                for c in key.chars() {
-                    key_styled.spans.push(match c {
-                        '/' => Span::raw("/"),
-                        _ => Span::styled(String::from(c), Style::default().fg(Color::Red)),
+        let history = self.results.history(self.library.recency.window);
+        let fits = |draw: &Draw| {
+            let (eligible, waiting) = self.library.pool_preview(draw, &draft.options, &history);
+            eligible + waiting > 0
+        };
+        // a draw that can fall back to another power still gets a mark
+        let falls_back = |draw: &Draw| {
+            let Some(PowerConstraint::Exact(power)) = &draw.power else {
+                return false;
+            };
+            self.library
+                .tiers
+                .fallbacks(power)
+                .into_iter()
+                .any(|power| {
+                    fits(&Draw {
+                        power: Some(PowerConstraint::Exact(power)),
+                        ..draw.clone()
                     })
-                }
+                })
+        };
+        let empty: BTreeSet<usize> = (0..draft.draws.len())
+            .filter(|&i| !fits(&draft.draws[i]) && !falls_back(&draft.draws[i]))
+            .collect();
+        let changed = empty != draft.empty;
+        self.draft_view.draft.empty = empty;
+        changed
+    }
+
+    /// Loads the library files again, keeping which marks are free by name.
+    /// Results are kept as they are, they hold copies of their marks.
+    fn reload(&mut self) -> anyhow::Result<()> {
+        let save = crate::load_library_files(&self.watcher.paths(), false)?;
+        let free: BTreeMap<&str, bool> = self
+            .library
+            .list
+            .iter()
+            .map(|(m, free)| (m.name.as_str(), *free))
+            .collect();
+        let mut library = save.library;
+        for (mark, is_free) in &mut library.list {
+            if let Some(&was_free) = free.get(mark.name.as_str()) {
+                *is_free = was_free;
+            }
+        }
+        *self.library = library;
+        self.unsaved = true;
+        self.draft_view.mark_list.reset(self.library);
+        // the draws may name labels or tiers the files no longer have
+        for draft in self.draft_view.drafts_mut() {
+            draft.repair(self.library);
+        }
+        self.status = Some((
+            format!("Reloaded {} marks", self.library.list.len()),
+            Instant::now(),
+        ));
+        Ok(())
+    }
+
+    /// The right side of the status bar: the latest message, the file and
+    /// whether there are unsaved changes.
+    fn status_line(&self) -> Line<'static> {
+        let mut line = vec![];
+        match (&self.posting, &self.status) {
+            (Some((n, _)), _) => {
+                line.push(Span::styled(
+                    format!("Posting Draft #{n}…"),
+                    theme::current().muted,
+                ));
+                line.push(Span::raw("  "));
+            }
+            (None, Some((message, at))) if at.elapsed() < STATUS_DURATION => {
+                line.push(Span::styled(message.clone(), theme::current().success));
+                line.push(Span::raw("  "));
+            }
+            _ => {}
+        }
+        line.push(Span::raw(self.file.clone()));
+        if self.unsaved {
+            line.push(if (self.ticks / BLINK_TICKS).is_multiple_of(2) {
+                Span::styled(" [+]", theme::current().warning)
+            } else {
+                Span::styled(" [+]", theme::current().muted)
+            });
+        }
+        Line::from(line)
+    }
+
+    fn draw_status_bar(f: &mut Frame, area: Rect, hints: &[(&str, &str)], right: Line<'static>) {
+        let layout = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(right.width() as u16 + 1),
+        ])
+        .split(area);
 
-                Row::new([key_styled, Line::raw(desc.trim())])
+        let hints: Vec<_> = hints
+            .iter()
+            .flat_map(|(key, action)| {
+                [
+                    key.bold(),
+                    Span::styled(format!(" {action}  "), theme::current().muted),
+                ]
             })
             .collect();
+        f.render_widget(Line::from(hints), layout[0]);
+        f.render_widget(right.right_aligned(), layout[1]);
+    }
 
-        let table = Table::new(
-            rows,
-            Constraint::from_lengths([max_key as u16, max_desc as u16]),
-        )
-        .block(
-            Block::default()
-                .borders(Borders::TOP)
-                .title(section_title.red())
-                .title_alignment(Alignment::Center),
-        );
+    /// Inserts pasted text into whichever text box is open.
+    pub fn paste(&mut self, text: &str) {
+        self.edits += 1;
+        let prompt = if self.asking.is_some() || self.error.is_some() {
+            None
+        } else if self.is_calibrating {
+            Some(&mut self.calibrate_box)
+        } else if self.is_naming_player {
+            Some(&mut self.player_box)
+        } else if self.is_naming_session {
+            Some(&mut self.session_box)
+        } else if self.is_naming_draft {
+            Some(&mut self.draft_name_box)
+        } else if self.is_budgeting {
+            Some(&mut self.budget_box)
+        } else if self.is_setting_recency {
+            Some(&mut self.recency_box)
+        } else if self.is_filtering {
+            Some(&mut self.filter_box)
+        } else if let Some(picker) = &mut self.tag_picker {
+            Some(&mut picker.prompt)
+        } else if let Some(roster) = &mut self.roster {
+            roster.name_box.as_mut()
+        } else if let Some(list) = &mut self.preset_list {
+            list.name_box.as_mut()
+        } else if let Some(manager) = &mut self.label_manager {
+            manager.name_box.as_mut()
+        } else if self.is_noting {
+            self.note_box.paste(text);
+            None
+        } else if self.is_saving {
+            Some(&mut self.save_box)
+        } else if self.tab == Tab::Results && self.results.is_searching() {
+            self.results.paste(text);
+            None
+        } else if let Some((export_box, _)) = &mut self.export_box {
+            Some(export_box)
+        } else if let Some(import_box) = &mut self.import_box {
+            Some(import_box)
+        } else if let Some(diff_box) = &mut self.diff_box {
+            Some(diff_box)
+        } else {
+            None
+        };
 
-        f.render_widget(table, areas[c])
+        if let Some(prompt) = prompt {
+            prompt.paste(text);
+        }
+        if self.is_saving {
+            self.update_save_footer();
+        }
     }
-}
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
-pub struct Results {
-    results: Vec<(Vec<Mark>, Vec<Draw>)>,
-    #[serde(skip)]
-    state: ListState,
-}
+    /// Shows where the save prompt's name would be saved, or what is wrong
+    /// with it.
+    fn update_save_footer(&mut self) {
+        self.save_box.footer = path_footer(&save_path(&self.save_box.text));
+    }
 
-impl Results {
-    fn next_selection(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.results.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
+    /// Quits, asking first if there are unsaved changes.
+    fn quit(&mut self) -> ControlFlow<()> {
+        if !self.unsaved {
+            return BREAK;
+        }
+        self.ask(
+            "There are unsaved changes, quit anyway?".to_string(),
+            Pending::Quit,
+        );
+        CONT
     }
 
-    fn prev_selection(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.results.len() - 1
-                } else {
-                    i - 1
-                }
+    /// Lets the user pick another file, and closes the session to open it
+    /// unless they cancel.
+    fn open(&mut self) -> anyhow::Result<ControlFlow<()>> {
+        let open = |path: &Path| crate::load_library_file(path, false);
+        Ok(match picker::pick(self.terminal, "cancel", open)? {
+            Some(opened) => {
+                self.opened = Some(opened);
+                BREAK
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
+            None => CONT,
+        })
     }
 
-    pub fn input(&mut self, key: KeyCode) {
-        match key {
-            KeyCode::Up => self.prev_selection(),
-            KeyCode::Down => self.next_selection(),
-            _ => {}
+    /// Carries out an action the user said yes to.
+    fn perform(&mut self, action: Pending) -> anyhow::Result<ControlFlow<()>> {
+        match action {
+            // the draft's record of used up marks puts the availability back
+            // to how it was, older saves have no record to go back to
+            Pending::UndoDraft => {
+                if let Some(result) = self.results.pop() {
+                    self.unsaved = true;
+                    if result.seed.is_some() {
+                        for (mark, is_free) in &mut self.library.list {
+                            *is_free = result.was_free(mark);
+                        }
+                    }
+                    if let Some(bags) = result.bags {
+                        self.library.bags = bags;
+                    }
+                }
+            }
+            Pending::DeleteDraw => self.draft_view.draft.delete_current_element(),
+            Pending::Overwrite(path) => self.save(path)?,
+            Pending::Export(path, format) => self.export(path, format)?,
+            Pending::Quit => return Ok(BREAK),
+            Pending::Open => return self.open(),
+            Pending::Reload => self.reload()?,
+            Pending::Post(n) => {
+                let result = self.results.results[n].clone();
+                let tiers = self.library.tiers.clone();
+                let handle = thread::spawn(move || discord::post_result(n, &result, &tiers));
+                self.posting = Some((n, handle));
+            }
         }
-    }
 
-    pub fn draw(&mut self, f: &mut Frame, rect: Rect) {
+        Ok(CONT)
+    }
+
+    /// Handles a key, showing any error in a popup instead of giving up on
+    /// the session.
+    pub fn input(&mut self, ev: KeyEvent) -> ControlFlow<()> {
+        self.edits += 1;
+        match self.handle_key(ev) {
+            Ok(flow) => flow,
+            Err(e) => {
+                self.error = Some(format!("{e:#}"));
+                CONT
+            }
+        }
+    }
+
+    fn handle_key(&mut self, ev: KeyEvent) -> anyhow::Result<ControlFlow<()>> {
+        match ev.code {
+            KeyCode::F(12) => self.show_log = !self.show_log,
+            KeyCode::F(2) => {
+                let name = theme::cycle();
+                let mut config = Config::load();
+                config.theme = Some(name.clone());
+                if let Err(e) = config.save() {
+                    log::warn!("Could not save the config: {e}");
+                }
+                self.status = Some((format!("Theme: {name}"), Instant::now()));
+            }
+            // nothing is on screen to act on, but quitting still works
+            KeyCode::Esc | KeyCode::Char('q' | 'Q') if self.too_small && self.asking.is_none() => {
+                return Ok(self.quit());
+            }
+            _ if self.too_small && self.asking.is_none() => {}
+            _ if self.error.is_some() => self.error = None,
+            _ if !self.whats_new.is_empty() => self.whats_new.clear(),
+            _ if self.asking.is_some() => {
+                let (question, _) = self.asking.as_mut().unwrap();
+                if let ControlFlow::Break(yes) = question.input(ev) {
+                    let (_, action) = self.asking.take().unwrap();
+                    if yes {
+                        return self.perform(action);
+                    }
+                }
+            }
+            _ if self.is_calibrating => match self.calibrate_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(false) => self.is_calibrating = false,
+                ControlFlow::Break(true) => {
+                    match calibrate::parse_target(&self.calibrate_box.text, &self.library.tiers) {
+                        Ok(target) => {
+                            self.is_calibrating = false;
+                            self.calibration = Some(calibrate::calibrate(
+                                self.library,
+                                &self.draft_view.draft.draws,
+                                &self.draft_view.draft.options,
+                                &self.results.history(self.library.recency.window),
+                                target,
+                                &mut self.rng,
+                            ));
+                        }
+                        Err(e) => {
+                            self.calibrate_box.title =
+                                Line::from(Span::styled(e, theme::current().error))
+                        }
+                    }
+                }
+            },
+            _ if self.is_naming_player => match self.player_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(false) => self.is_naming_player = false,
+                ControlFlow::Break(true) => {
+                    self.is_naming_player = false;
+                    let name = self.player_box.text.trim();
+                    self.draft_view.draft.options.player = if name.is_empty() {
+                        None
+                    } else {
+                        self.unsaved |= self.library.players.iter().all(|p| p.name != name);
+                        self.library.player_mut(name);
+                        Some(name.to_string())
+                    };
+                }
+            },
+            _ if self.is_naming_session => match self.session_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(false) => self.is_naming_session = false,
+                ControlFlow::Break(true) => {
+                    self.is_naming_session = false;
+                    let name = self.session_box.text.trim();
+                    self.results
+                        .start_session((!name.is_empty()).then(|| name.to_string()));
+                    self.unsaved = true;
+                }
+            },
+            _ if self.is_naming_draft => match self.draft_name_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(false) => self.is_naming_draft = false,
+                ControlFlow::Break(true) => {
+                    self.is_naming_draft = false;
+                    let name = self.draft_name_box.text.trim();
+                    self.execute_draft((!name.is_empty()).then(|| name.to_string()));
+                }
+            },
+            _ if self.is_budgeting => match self.budget_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(false) => self.is_budgeting = false,
+                ControlFlow::Break(true) => match Budget::parse(&self.budget_box.text) {
+                    Ok(budget) => {
+                        self.is_budgeting = false;
+                        self.draft_view.draft.options.budget = budget;
+                    }
+                    Err(e) => {
+                        self.budget_box.title = Line::from(Span::styled(e, theme::current().error))
+                    }
+                },
+            },
+            _ if self.is_setting_recency => match self.recency_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(false) => self.is_setting_recency = false,
+                ControlFlow::Break(true) => {
+                    match self.library.recency.parse(&self.recency_box.text) {
+                        Ok(recency) => {
+                            self.is_setting_recency = false;
+                            self.library.recency = recency;
+                            self.unsaved = true;
+                        }
+                        Err(e) => {
+                            self.recency_box.title =
+                                Line::from(Span::styled(e, theme::current().error))
+                        }
+                    }
+                }
+            },
+            _ if self.is_filtering => match self.filter_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(false) => self.is_filtering = false,
+                ControlFlow::Break(true) => {
+                    let text = self.filter_box.text.trim();
+                    let filter = if text.is_empty() {
+                        Ok(None)
+                    } else {
+                        Filter::parse(text)
+                            .and_then(|f| f.check(&self.library.tiers).map(|_| Some(f)))
+                    };
+                    match filter {
+                        Ok(filter) => {
+                            self.is_filtering = false;
+                            self.draft_view.draft.get_selected_draw().filter = filter;
+                        }
+                        Err(e) => {
+                            self.filter_box.title =
+                                Line::from(Span::styled(e, theme::current().error))
+                        }
+                    }
+                }
+            },
+            _ if self.replay.is_some() => self.replay = None,
+            _ if self.dry_run.is_some() => self.dry_run = None,
+            _ if self.diff.is_some() => {
+                if self.diff.as_mut().unwrap().input(ev.code).is_break() {
+                    self.diff = None;
+                }
+            }
+            _ if self.report.is_some() => {
+                if let ControlFlow::Break(mark) = self.report.as_mut().unwrap().input(ev.code) {
+                    self.report = None;
+                    if let Some(mark) = mark {
+                        self.tab = Tab::DraftCreation;
+                        self.draft_view.selected_tab = Pane::Right;
+                        self.draft_view.mark_list.show_mark(self.library, &mark);
+                    }
+                }
+            }
+            _ if self.mark_detail.is_some() => self.mark_detail = None,
+            _ if self.column_chooser.is_some() => {
+                let state = self.column_chooser.as_mut().unwrap();
+                if self
+                    .draft_view
+                    .mark_list
+                    .choose_columns(self.library, state, ev.code)
+                    .is_break()
+                {
+                    self.column_chooser = None;
+                }
+            }
+            _ if self.tag_picker.is_some() => {
+                let picker = self.tag_picker.as_mut().unwrap();
+                if let ControlFlow::Break(tag) = picker.input(ev) {
+                    if let Some(tag) = tag {
+                        self.unsaved |= self.library.tags.insert(tag.clone());
+                        self.draft_view.draft.add_tag(tag);
+                    }
+                    self.tag_picker = None;
+                }
+            }
+            _ if self.power_picker.is_some() => {
+                let (state, upper) = self.power_picker.as_mut().unwrap();
+                let powers = self.library.tiers.powers();
+                match ev.code {
+                    KeyCode::Esc => self.power_picker = None,
+                    KeyCode::Up => state.select(state.selected().map(|i| i.saturating_sub(1))),
+                    KeyCode::Down => state.select(
+                        state
+                            .selected()
+                            .map(|i| cmp::min(i + 1, powers.len().saturating_sub(1))),
+                    ),
+                    KeyCode::Enter => {
+                        if let Some(power) = state.selected().and_then(|i| powers.get(i)) {
+                            let upper = upper.unwrap_or(false);
+                            let tiers = &self.library.tiers;
+                            self.draft_view.draft.set_power(power.clone(), upper, tiers);
+                        }
+                        self.power_picker = None;
+                    }
+                    _ => {}
+                }
+            }
+            _ if self.roster.is_some() => {
+                let roster = self.roster.as_mut().unwrap();
+                let player = roster
+                    .state
+                    .selected()
+                    .and_then(|i| self.library.players.get(i));
+                match player {
+                    Some(player) if ev.code == KeyCode::Enter && roster.name_box.is_none() => {
+                        let history = self.results.player_history(&player.name);
+                        let drafts = self
+                            .results
+                            .list()
+                            .iter()
+                            .filter(|r| r.options.player.as_ref() == Some(&player.name))
+                            .count();
+                        self.diff = Some(DiffView {
+                            title: format!("Drafted by {}", player.name),
+                            lines: player_history_lines(&history, drafts, &self.library.tiers),
+                            scroll: 0,
+                        });
+                    }
+                    _ => match roster.input(self.library, ev) {
+                        ControlFlow::Continue(edited) => self.unsaved |= edited,
+                        ControlFlow::Break(()) => self.roster = None,
+                    },
+                }
+            }
+            _ if self.preset_list.is_some() => {
+                let list = self.preset_list.as_mut().unwrap();
+                match list.input(&mut self.presets, self.library, ev) {
+                    ControlFlow::Continue(edited) => self.unsaved |= edited,
+                    ControlFlow::Break(edited) => {
+                        self.unsaved |= edited;
+                        self.preset_list = None;
+                    }
+                }
+            }
+            _ if self.label_manager.is_some() => {
+                match self.label_manager.as_mut().unwrap().input(self.library, ev) {
+                    ControlFlow::Continue(None) => {}
+                    ControlFlow::Continue(Some(edit)) => self.edit_labels(edit),
+                    ControlFlow::Break(_) => self.label_manager = None,
+                }
+            }
+            _ if self.tab == Tab::Results && self.results.is_searching() => {
+                self.results.search_input(ev)
+            }
+            _ if self.export_box.is_some() => {
+                let (export_box, format) = self.export_box.as_mut().unwrap();
+                let format = *format;
+                let res = export_box.input(ev);
+                let path = file_path(&export_box.text, format.extension());
+                export_box.footer = path_footer(&path);
+                match (res, path) {
+                    (ControlFlow::Continue(_), _) | (ControlFlow::Break(true), Err(_)) => {}
+                    (ControlFlow::Break(false), _) => self.export_box = None,
+                    (ControlFlow::Break(true), Ok(path)) => {
+                        self.export_box = None;
+                        if path.exists() {
+                            self.ask(
+                                format!("{} already exists, overwrite it?", path.display()),
+                                Pending::Export(path, format),
+                            );
+                        } else {
+                            self.export(path, format)?;
+                        }
+                    }
+                }
+            }
+            _ if self.import_box.is_some() => {
+                let import_box = self.import_box.as_mut().unwrap();
+                let res = import_box.input(ev);
+                let path = file_path(&import_box.text, "json");
+                import_box.footer = path_footer(&path);
+                match (res, path) {
+                    (ControlFlow::Continue(_), _) | (ControlFlow::Break(true), Err(_)) => {}
+                    (ControlFlow::Break(false), _) => self.import_box = None,
+                    (ControlFlow::Break(true), Ok(path)) => {
+                        self.import_box = None;
+                        let result = export::read_result(&path)?;
+                        self.results.push(result);
+                        self.unsaved = true;
+                        let n = self.results.list().len() - 1;
+                        self.results.select(n);
+                        self.status = Some((
+                            format!("Imported {} as Draft #{n}", path.display()),
+                            Instant::now(),
+                        ));
+                    }
+                }
+            }
+            _ if self.diff_box.is_some() => {
+                let diff_box = self.diff_box.as_mut().unwrap();
+                let res = diff_box.input(ev);
+                let path = library_path(&diff_box.text);
+                diff_box.footer = path_footer(&path);
+                match (res, path) {
+                    (ControlFlow::Continue(_), _) | (ControlFlow::Break(true), Err(_)) => {}
+                    (ControlFlow::Break(false), _) => self.diff_box = None,
+                    (ControlFlow::Break(true), Ok(path)) => {
+                        self.diff_box = None;
+                        let old = crate::load_library_file(&path, false)?;
+                        self.diff = Some(DiffView {
+                            title: format!("{} → library", path.display()),
+                            lines: LibraryDiff::new(&old.library, self.library).lines(),
+                            scroll: 0,
+                        });
+                    }
+                }
+            }
+            _ if self.is_noting => match self.note_box.input(ev) {
+                ControlFlow::Continue(_) => {}
+                ControlFlow::Break(save) => {
+                    self.is_noting = false;
+                    if let (true, Some(result)) = (save, self.results.selected_mut()) {
+                        result.note = self.note_box.text.trim_end().to_string();
+                        self.unsaved = true;
+                    }
+                }
+            },
+            KeyCode::Enter if self.calibration.is_some() => {
+                let calibration = self.calibration.take().unwrap();
+                self.library.power_weights = calibration.weights;
+                self.unsaved = true;
+            }
+            KeyCode::Esc if self.calibration.is_some() => self.calibration = None,
+            _ if self.calibration.is_some() => {}
+            KeyCode::Char('s')
+                if ev.modifiers.contains(KeyModifiers::CONTROL) && !self.is_saving =>
+            {
+                self.quick_save()?
+            }
+            KeyCode::Char('o')
+                if ev.modifiers.contains(KeyModifiers::CONTROL) && !self.is_saving =>
+            {
+                if !self.unsaved {
+                    return self.open();
+                }
+                self.ask(
+                    "There are unsaved changes, open another file anyway?".to_string(),
+                    Pending::Open,
+                );
+            }
+            KeyCode::Char('s' | 'S') if !self.is_saving => {
+                self.is_saving = true;
+                self.update_save_footer();
+            }
+            KeyCode::Char('?') if !self.is_saving => {
+                self.show_help = true;
+            }
+            KeyCode::Esc if self.show_help => {
+                self.show_help = false;
+            }
+            _ if self.is_saving => {
+                let res = self.save_box.input(ev);
+                let path = save_path(&self.save_box.text);
+                self.is_saving = match (res, path) {
+                    (ControlFlow::Continue(_), _) => true,
+                    (ControlFlow::Break(false), _) => false,
+                    // keep the prompt open so the name can be fixed
+                    (ControlFlow::Break(true), Err(_)) => true,
+                    (ControlFlow::Break(true), Ok(path)) => {
+                        // overwriting the save being worked on needs no asking
+                        if path.exists() && self.path.as_ref() != Some(&path) {
+                            self.ask(
+                                format!("{} already exists, overwrite it?", path.display()),
+                                Pending::Overwrite(path),
+                            );
+                        } else {
+                            self.save(path)?;
+                        }
+                        false
+                    }
+                };
+                self.update_save_footer();
+            }
+            KeyCode::Char('c') if ev.modifiers.contains(KeyModifiers::CONTROL) => self.copy()?,
+            KeyCode::Char('t')
+                if ev.modifiers.contains(KeyModifiers::CONTROL)
+                    && self.tab == Tab::DraftCreation =>
+            {
+                self.draft_view.new_draft()
+            }
+            KeyCode::Char('w')
+                if ev.modifiers.contains(KeyModifiers::CONTROL)
+                    && self.tab == Tab::DraftCreation =>
+            {
+                self.draft_view.close_draft(ev.code);
+            }
+            KeyCode::Char('[') if self.tab == Tab::DraftCreation => {
+                self.draft_view.switch_draft(Dir::Left);
+            }
+            KeyCode::Char(']') if self.tab == Tab::DraftCreation => {
+                self.draft_view.switch_draft(Dir::Right);
+            }
+            KeyCode::Esc | KeyCode::Char('q' | 'Q') => return Ok(self.quit()),
+            KeyCode::Char('d' | 'D') => {
+                self.tab = Tab::DraftCreation;
+            }
+            KeyCode::Char('r' | 'R') => {
+                self.tab = Tab::Results;
+            }
+            KeyCode::Char('k' | 'K') => {
+                self.tab = Tab::Snake;
+            }
+            KeyCode::Char('w' | 'W') if self.tab == Tab::DraftCreation => {
+                self.calibrate_box.title = Line::raw("Target shares (e.g. Good=1/4, Great=0.1)");
+                self.is_calibrating = true;
+            }
+            KeyCode::Char('y' | 'Y') if self.tab == Tab::DraftCreation => {
+                self.player_box.text = self
+                    .draft_view
+                    .draft
+                    .options
+                    .player
+                    .clone()
+                    .unwrap_or_default();
+                self.player_box.cursor_pos = self.player_box.text.len();
+                self.is_naming_player = true;
+            }
+            KeyCode::Char('b' | 'B')
+                if self.draft_view.selected_tab == Pane::Left && self.tab == Tab::DraftCreation =>
+            {
+                self.budget_box.title =
+                    Line::raw("Point budget (e.g. 12, 12! to never go over, blank for none)");
+                self.budget_box.text = self
+                    .draft_view
+                    .draft
+                    .options
+                    .budget
+                    .map(|b| b.to_string())
+                    .unwrap_or_default();
+                self.budget_box.cursor_pos = self.budget_box.text.len();
+                self.is_budgeting = true;
+            }
+            KeyCode::Char('h' | 'H')
+                if self.draft_view.selected_tab == Pane::Left && self.tab == Tab::DraftCreation =>
+            {
+                self.recency_box.title =
+                    Line::raw("Recent drafts and penalty (e.g. 3 0.5, blank for none)");
+                self.recency_box.text = match self.library.recency.window {
+                    0 => String::new(),
+                    _ => self.library.recency.to_string(),
+                };
+                self.recency_box.cursor_pos = self.recency_box.text.len();
+                self.is_setting_recency = true;
+            }
+            KeyCode::Char('f' | 'F')
+                if self.draft_view.selected_tab == Pane::Left
+                    && self.tab == Tab::DraftCreation
+                    && !self.draft_view.draft.draws.is_empty() =>
+            {
+                self.filter_box.title = Line::raw(
+                    "Filter (e.g. power >= Good && (tag:Fire || tag:Ice)), blank for none",
+                );
+                let draw = self.draft_view.draft.get_selected_draw();
+                self.filter_box.text = draw
+                    .filter
+                    .as_ref()
+                    .map(Filter::to_string)
+                    .unwrap_or_default();
+                self.filter_box.cursor_pos = self.filter_box.text.len();
+                self.is_filtering = true;
+            }
+            KeyCode::Char('v' | 'V')
+                if self.draft_view.selected_tab == Pane::Left && self.tab == Tab::DraftCreation =>
+            {
+                if self.library.list.is_empty() {
+                    self.draft_view.message = Some("The library has no marks to draft from");
+                } else if self.draft_view.draft.draws.is_empty() {
+                    self.draft_view.message = Some("Add a draw with A before drafting");
+                } else {
+                    let preview = self.library.preview(
+                        self.draft_view.draft.draws.clone(),
+                        &self.draft_view.draft.options,
+                        &self.results.history(self.library.recency.window),
+                        &mut self.rng,
+                    );
+                    self.dry_run = Some(DryRun { preview });
+                }
+            }
+            KeyCode::Char('v' | 'V')
+                if self.draft_view.selected_tab == Pane::Right
+                    && self.tab == Tab::DraftCreation =>
+            {
+                self.column_chooser = Some(ListState::default().with_selected(Some(0)));
+            }
+            KeyCode::Char('a' | 'A')
+                if self.draft_view.selected_tab == Pane::Right
+                    && self.tab == Tab::DraftCreation =>
+            {
+                self.preset_list = Some(PresetList::default());
+            }
+            KeyCode::Char('t' | 'T')
+                if self.draft_view.selected_tab == Pane::Right
+                    && self.tab == Tab::DraftCreation =>
+            {
+                self.label_manager = Some(LabelManager {
+                    state: ListState::default().with_selected(Some(0)),
+                    ..Default::default()
+                });
+            }
+            KeyCode::Char('h' | 'H')
+                if self.draft_view.selected_tab == Pane::Right
+                    && self.tab == Tab::DraftCreation =>
+            {
+                if let Some(mark) = self.draft_view.mark_list.selected_mark(self.library) {
+                    self.results.filter_mark(Some(mark.name.clone()));
+                    self.tab = Tab::Results;
+                }
+            }
+            KeyCode::Char('i' | 'I')
+                if self.draft_view.selected_tab == Pane::Right
+                    && self.tab == Tab::DraftCreation =>
+            {
+                self.diff_box = Some(Prompt {
+                    title: Line::raw("Compare the library with"),
+                    max_width: 48,
+                    completer: Some(complete_library),
+                    ..Default::default()
+                })
+            }
+            KeyCode::Enter
+                if self.draft_view.selected_tab == Pane::Left && self.tab == Tab::DraftCreation =>
+            {
+                if self.library.list.is_empty() {
+                    self.draft_view.message = Some("The library has no marks to draft from");
+                } else if self.draft_view.draft.draws.is_empty() {
+                    self.draft_view.message = Some("Add a draw with A before drafting");
+                } else if Config::load().name_drafts {
+                    self.draft_name_box.text = String::new();
+                    self.draft_name_box.cursor_pos = 0;
+                    self.is_naming_draft = true;
+                } else {
+                    self.execute_draft(None);
+                }
+            }
+            KeyCode::Char('t' | 'T')
+                if self.draft_view.selected_tab == Pane::Left
+                    && self.tab == Tab::DraftCreation
+                    && !self.draft_view.draft.draws.is_empty() =>
+            {
+                let draw = self.draft_view.draft.get_selected_draw();
+                let tags = tag_tree(&self.library.tags)
+                    .into_iter()
+                    .filter(|t| !draw.all_tags().any(|d| d == t))
+                    .collect();
+                self.tag_picker = Some(TagPicker::new(tags));
+            }
+            KeyCode::Char('p' | 'P')
+                if self.draft_view.selected_tab == Pane::Left
+                    && self.tab == Tab::DraftCreation
+                    && !self.draft_view.draft.draws.is_empty() =>
+            {
+                // P picks the top of a range, like Shift with the arrow keys
+                let draft = &mut self.draft_view.draft;
+                let upper = matches!(
+                    draft.get_selected_draw().power,
+                    Some(PowerConstraint::Range(..))
+                )
+                .then_some(ev.code == KeyCode::Char('P'));
+                let tiers = &self.library.tiers;
+                let power = draft.initial_power(tiers, upper.unwrap_or(false));
+                let i = power.and_then(|p| tiers.powers().iter().position(|t| t == &p));
+                let state = ListState::default().with_selected(Some(i.unwrap_or(0)));
+                self.power_picker = Some((state, upper));
+            }
+            KeyCode::Backspace | KeyCode::Char('-')
+                if self.draft_view.selected_tab == Pane::Left
+                    && self.tab == Tab::DraftCreation
+                    && self.draft_view.draft.on_draw_line() =>
+            {
+                self.ask("Delete this draw?".to_string(), Pending::DeleteDraw);
+            }
+            _ if self.tab == Tab::DraftCreation => {
+                if self.draft_view.input(self.library, ev) {
+                    self.unsaved = true;
+                }
+            }
+            KeyCode::Char('n' | 'N') if self.tab == Tab::Results => {
+                if let Some(result) = self.results.selected_mut() {
+                    self.note_box = NoteBox::new(&result.note);
+                    self.is_noting = true;
+                }
+            }
+            KeyCode::Char('a' | 'A') if self.tab == Tab::Results => {
+                self.unsaved |= self.results.selected().is_some();
+                self.results.cycle_player(&self.library.players)
+            }
+            KeyCode::Char('b' | 'B') if self.tab == Tab::Results => {
+                self.session_box.text = String::new();
+                self.session_box.cursor_pos = 0;
+                self.is_naming_session = true;
+            }
+            KeyCode::Char('p' | 'P') if self.tab == Tab::Results => {
+                if let Some(n) = self.results.selected() {
+                    let result = &self.results.results[n];
+                    let history = self.results.history_before(n, self.library.recency.window);
+                    let marks = self.library.replay(result, &history);
+                    let matches = marks.as_ref().is_ok_and(|marks| {
+                        marks
+                            .iter()
+                            .map(|m| &m.name)
+                            .eq(result.marks.iter().map(|m| &m.name))
+                    });
+                    self.replay = Some(Replay {
+                        n,
+                        seed: result.seed,
+                        available: self
+                            .library
+                            .list
+                            .iter()
+                            .filter(|(m, _)| result.was_free(m))
+                            .map(|(m, _)| m.name.clone())
+                            .collect(),
+                        marks,
+                        matches,
+                    });
+                }
+            }
+            KeyCode::Char('e' | 'E') if self.tab == Tab::Results => {
+                if let Some((n, slot)) = self.results.selected_slot() {
+                    let history = self.results.history_before(n, self.library.recency.window);
+                    let result = &mut self.results.results[n];
+                    let (mark, fallback) =
+                        self.library.reroll(result, slot, &history, &mut self.rng);
+                    // never removed, undoing the reroll may bring back the mark
+                    // it is for, and it only shows on a mark of its power
+                    if let Some(power) = fallback {
+                        result.fallbacks.insert(slot, power);
+                    }
+                    let old = std::mem::replace(&mut result.marks[slot], mark);
+                    result.rerolls.push((slot, old));
+                    result.wishlisted = self.library.wishlisted(&result.options, &result.marks);
+                    self.unsaved = true;
+                }
+            }
+            KeyCode::Char('u' | 'U') if self.tab == Tab::Results => {
+                if let Some(result) = self.results.selected_mut() {
+                    if let Some((slot, old)) = result.rerolls.pop() {
+                        result.marks[slot] = old;
+                        result.wishlisted = self.library.wishlisted(&result.options, &result.marks);
+                        self.unsaved = true;
+                    }
+                }
+            }
+            KeyCode::Char('c' | 'C') if self.tab == Tab::Results => {
+                if let Some(n) = self.results.selected() {
+                    let draws = self.results.results[n].draws.clone();
+                    self.draft_view.draft.load(draws, self.library);
+                    self.draft_view.selected_tab = Pane::Left;
+                    self.tab = Tab::DraftCreation;
+                }
+            }
+            KeyCode::Char('z' | 'Z') if self.tab == Tab::Results => {
+                if let Some(last) = self.results.list().len().checked_sub(1) {
+                    self.ask(
+                        format!("Undo Draft #{last} and restore the availability before it?"),
+                        Pending::UndoDraft,
+                    );
+                }
+            }
+            KeyCode::Enter if self.tab == Tab::Results => {
+                if let Some((n, slot)) = self.results.selected_slot() {
+                    self.mark_detail = Some(self.results.results[n].marks[slot].clone());
+                }
+            }
+            KeyCode::Char('w' | 'W') if self.tab == Tab::Results => {
+                // one post at a time, the status bar shows the one underway
+                if let (Some(n), None) = (self.results.selected(), &self.posting) {
+                    self.ask(format!("Post Draft #{n} to the webhook?"), Pending::Post(n));
+                }
+            }
+            KeyCode::Char('x' | 'X') if self.tab == Tab::Results => {
+                self.export_box = Some((
+                    Prompt {
+                        title: Line::raw("Export results as"),
+                        postfix: Span::raw(".csv"),
+                        max_width: 48,
+                        ..Default::default()
+                    },
+                    export::Format::Csv,
+                ))
+            }
+            KeyCode::Char('j' | 'J') if self.tab == Tab::Results => {
+                if let Some(n) = self.results.selected() {
+                    self.export_box = Some((
+                        Prompt {
+                            title: Line::raw(format!("Export Draft #{n} as")),
+                            postfix: Span::raw(".json"),
+                            max_width: 48,
+                            completer: Some(complete_path),
+                            ..Default::default()
+                        },
+                        export::Format::Json,
+                    ))
+                }
+            }
+            KeyCode::Char('i' | 'I') if self.tab == Tab::Results => {
+                self.import_box = Some(Prompt {
+                    title: Line::raw("Import a result from"),
+                    postfix: Span::raw(".json"),
+                    max_width: 48,
+                    completer: Some(complete_path),
+                    ..Default::default()
+                })
+            }
+            KeyCode::Char('h' | 'H') if self.tab == Tab::Results => {
+                self.export_box = Some((
+                    Prompt {
+                        title: Line::raw("Write an html report to"),
+                        postfix: Span::raw(".html"),
+                        max_width: 48,
+                        ..Default::default()
+                    },
+                    export::Format::Html,
+                ))
+            }
+            KeyCode::Char('f' | 'F') if self.tab == Tab::Results => {
+                self.results.cycle_filter(&self.library.players)
+            }
+            KeyCode::Char('o' | 'O') if self.tab != Tab::DraftCreation => {
+                self.roster = Some(Roster::default())
+            }
+            k if self.tab == Tab::Results => {
+                if self.results.input(k) {
+                    self.unsaved = true;
+                }
+            }
+            k if self.tab == Tab::Snake => self.snake_input(k),
+            _ => {}
+        }
+
+        Ok(CONT)
+    }
+
+    fn snake_input(&mut self, key: KeyCode) {
+        let view = &mut self.snake;
+        if !matches!(key, KeyCode::Backspace | KeyCode::Char('-')) {
+            view.confirm.reset();
+        }
+
+        let Some(draft) = &mut view.draft else {
+            if key == KeyCode::Enter {
+                let players: Vec<_> = self
+                    .library
+                    .players
+                    .iter()
+                    .map(|p| p.name.clone())
+                    .collect();
+                let draws = &self.draft_view.draft.draws;
+                view.message = if self.library.list.is_empty() {
+                    Some("The library has no marks to draft from")
+                } else if players.len() < 2 {
+                    Some("A snake draft needs at least two players, add them with O")
+                } else if draws.is_empty() {
+                    Some("Set up the draws for each player in the Draft tab first")
+                } else {
+                    let options = DraftOptions {
+                        player: None,
+                        ..self.draft_view.draft.options.clone()
+                    };
+                    view.draft = Some(SnakeDraft::new(
+                        self.library,
+                        players,
+                        draws.clone(),
+                        options,
+                        &self.results.history(self.library.recency.window),
+                        &mut self.rng,
+                    ));
+                    view.state.select(Some(0));
+                    None
+                };
+            }
+            return;
+        };
+
+        let len = draft.pool.len();
+        match key {
+            KeyCode::Up if len > 0 => view.state.select(Some(
+                view.state.selected().map_or(0, |i| (i + len - 1) % len),
+            )),
+            KeyCode::Down if len > 0 => view
+                .state
+                .select(Some(view.state.selected().map_or(0, |i| (i + 1) % len))),
+            KeyCode::Enter => {
+                if let Some(i) = view.state.selected() {
+                    draft.take(i);
+                    view.state
+                        .select(Some(cmp::min(i, draft.pool.len().saturating_sub(1))));
+                }
+                if draft.is_done() {
+                    let draft = view.draft.take().unwrap();
+                    for result in draft.into_results(self.library) {
+                        self.results.push(result);
+                    }
+                    self.unsaved = true;
+                    self.tab = Tab::Results;
+                    self.results.select(self.results.results.len() - 1);
+                }
+            }
+            KeyCode::Backspace | KeyCode::Char('-') if view.confirm.confirm(key) => {
+                view.draft = None
+            }
+            _ => {}
+        }
+    }
+
+    pub fn draw(&mut self) -> anyhow::Result<()> {
+        let hints = self.hints();
+        let status = self.status_line();
+        let history = self.results.history(self.library.recency.window);
+        let term = &mut self.terminal;
+
+        term.draw(|f| {
+            let size = f.size();
+            if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+                show_too_small(f);
+                if let Some((question, _)) = &self.asking {
+                    question.draw(f);
+                }
+                return;
+            }
+
+            let layout = Layout::new(
+                Direction::Vertical,
+                [
+                    Constraint::Length(3),
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                ],
+            )
+            .split(f.size());
+            Self::draw_status_bar(f, layout[2], hints, status);
+            let tabs = Tabs::new([
+                Line::default().spans([
+                    Span::styled("D", theme::current().title).underlined(),
+                    Span::raw("raft"),
+                ]),
+                Line::default().spans([
+                    Span::styled("R", theme::current().title).underlined(),
+                    Span::raw("esults"),
+                ]),
+                Line::default().spans([
+                    Span::raw("Sna"),
+                    Span::styled("k", theme::current().title).underlined(),
+                    Span::raw("e draft"),
+                ]),
+            ])
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .select(match self.tab {
+                Tab::DraftCreation => 0,
+                Tab::Results => 1,
+                Tab::Snake => 2,
+            });
+            f.render_widget(tabs, layout[0]);
+            let block2 = Block::new()
+                .borders(Borders::LEFT | Borders::BOTTOM | Borders::RIGHT)
+                .border_type(BorderType::Rounded);
+            let inner = block2.inner(layout[1]);
+            f.render_widget(block2, layout[1]);
+
+            match self.tab {
+                Tab::DraftCreation => self.draft_view.draw(&*self.library, &history, f, inner),
+                Tab::Results => self.results.draw(&*self.library, f, inner),
+                Tab::Snake => self
+                    .snake
+                    .draw(&*self.library, &self.draft_view.draft, f, inner),
+            }
+
+            if self.is_saving {
+                self.save_box.draw(f, f.size());
+            }
+            if self.is_calibrating {
+                self.calibrate_box.draw(f, f.size());
+            }
+            if self.is_naming_player {
+                self.player_box.draw(f, f.size());
+            }
+            if self.is_naming_session {
+                self.session_box.draw(f, f.size());
+            }
+            if self.is_naming_draft {
+                self.draft_name_box.draw(f, f.size());
+            }
+            if self.is_budgeting {
+                self.budget_box.draw(f, f.size());
+            }
+            if self.is_setting_recency {
+                self.recency_box.draw(f, f.size());
+            }
+            if self.is_filtering {
+                self.filter_box.draw(f, f.size());
+            }
+            if self.is_noting {
+                self.note_box.draw(f, f.size());
+            }
+            if let Some((export_box, _)) = &mut self.export_box {
+                export_box.draw(f, f.size());
+            }
+            if let Some(import_box) = &mut self.import_box {
+                import_box.draw(f, f.size());
+            }
+            if let Some(diff_box) = &mut self.diff_box {
+                diff_box.draw(f, f.size());
+            }
+            if let Some(report) = &mut self.report {
+                report.draw(f);
+            }
+            if let Some(roster) = &mut self.roster {
+                roster.draw(self.library, f);
+            }
+            if let Some(diff) = &mut self.diff {
+                diff.draw(f);
+            }
+            if let Some(list) = &mut self.preset_list {
+                list.draw(&self.presets, f);
+            }
+            if let Some(manager) = &mut self.label_manager {
+                manager.draw(self.library, f);
+            }
+            if let Some(picker) = &mut self.tag_picker {
+                picker.draw(f);
+            }
+            if let Some((state, upper)) = &mut self.power_picker {
+                show_power_picker(f, &self.library.tiers, state, *upper);
+            }
+            if let Some(replay) = &self.replay {
+                replay.draw(self.library, f);
+            }
+            if let Some(dry_run) = &self.dry_run {
+                dry_run.draw(self.library, f);
+            }
+            if let Some(mark) = &self.mark_detail {
+                show_mark_detail_popup(f, mark, self.library);
+            }
+            if let Some(state) = &mut self.column_chooser {
+                self.draft_view
+                    .mark_list
+                    .draw_column_chooser(self.library, state, f);
+            }
+            if let Some(calibration) = &self.calibration {
+                show_calibration_popup(f, calibration, self.library);
+            }
+            if self.show_help {
+                show_help_popup(f);
+            }
+            if let Some((question, _)) = &self.asking {
+                question.draw(f);
+            }
+            if !self.whats_new.is_empty() {
+                show_whats_new_popup(f, &self.whats_new);
+            }
+            if let Some(error) = &self.error {
+                show_error_popup(f, error);
+            }
+            if self.show_log {
+                show_debug_log(f);
+            }
+        })?;
+
+        Ok(())
+    }
+}
+
+fn show_too_small(f: &mut Frame) {
+    let size = f.size();
+    let text = Text::from(vec![
+        Line::from(Span::styled("Terminal too small", theme::current().error).bold()),
+        Line::raw(format!(
+            "need {MIN_WIDTH}x{MIN_HEIGHT}, have {}x{}",
+            size.width, size.height
+        )),
+    ]);
+
+    let area = centered_rect(size, size.width, 2);
+    f.render_widget(Paragraph::new(text).centered(), area);
+}
+
+fn show_help_popup(f: &mut Frame) {
+    static HELP_TEXT: &'static str = include_str!("help_text.txt");
+    let help_text = HELP_TEXT.trim_end();
+
+    let sections = help_text.split("\n---\n");
+    let help_width = HELP_TEXT.lines().map(|l| l.len()).max().unwrap_or(0);
+    let help_height = help_text.lines().count();
+
+    let c = |len| {
+        [
+            Constraint::Fill(1),
+            Constraint::Length(len),
+            Constraint::Fill(1),
+        ]
+    };
+
+    let c_h = Layout::horizontal(c(help_width as u16 + 4)).split(f.size());
+    let c_v = Layout::vertical(c(help_height as u16 + 4)).split(c_h[1]);
+
+    f.render_widget(Clear, c_v[1]);
+
+    let (areas, _) = Layout::vertical(Constraint::from_lengths(
+        sections.clone().map(|s| s.lines().count() as u16),
+    ))
+    .flex(Flex::SpaceBetween)
+    .horizontal_margin(2)
+    .vertical_margin(2)
+    .split_with_spacers(c_v[1]);
+    f.render_widget(
+        Block::bordered()
+            .title(Span::styled("Help", theme::current().title))
+            .title_alignment(Alignment::Center),
+        c_v[1],
+    );
+
+    for (c, section) in sections.enumerate() {
+        let mut lines = section.lines();
+        let Some(section_title) = lines.next() else {
+            continue;
+        };
+
+        let mut max_key = 0;
+        let mut max_desc = 0;
+
+        // A line without a description is skipped rather than taking the help down with it
+        let rows: Vec<_> = lines
+            .filter_map(|l| {
+                let (key, desc) = l.split_once(char::is_whitespace)?;
+
+                max_key = cmp::max(max_key, key.len() + 1);
+                max_desc = cmp::max(max_desc, desc.len() + 1);
+
+                let mut key_styled = Line::default();
+                for c in key.chars() {
+                    key_styled.spans.push(match c {
+                        '/' => Span::raw("/"),
+                        _ => Span::styled(String::from(c), theme::current().title),
+                    })
+                }
+
+                Some(Row::new([key_styled, Line::raw(desc.trim())]))
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            Constraint::from_lengths([max_key as u16, max_desc as u16]),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .title(Span::styled(section_title, theme::current().title))
+                .title_alignment(Alignment::Center),
+        );
+
+        f.render_widget(table, areas[c])
+    }
+}
+
+fn show_whats_new_popup(f: &mut Frame, releases: &[Release]) {
+    let mut lines = Vec::new();
+    for release in releases {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(format!("Version {}", release.version).bold()));
+        for note in &release.notes {
+            lines.push(Line::from(vec![
+                Span::styled("• ", theme::current().title),
+                Span::raw(note.as_str()),
+            ]));
+        }
+    }
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 6;
+    let area = centered_rect(f.size(), width, lines.len() as u16 + 4);
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(Span::styled("What's new", theme::current().title))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::raw("Press any key").centered())
+                .padding(Padding::uniform(1)),
+        ),
+        area,
+    );
+}
+
+/// The latest log messages over the bottom half of the screen.
+fn show_debug_log(f: &mut Frame) {
+    let size = f.size();
+    let area = Rect {
+        y: size.y + size.height / 2,
+        height: size.height - size.height / 2,
+        ..size
+    };
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .title(Span::styled("Debug log", theme::current().title))
+        .title_bottom(Line::raw("F12 to close").centered());
+    let lines = debug_log::last(block.inner(area).height as usize);
+    let text = if lines.is_empty() {
+        Text::from(Span::styled("<nothing logged>", theme::current().muted).italic())
+    } else {
+        Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+    };
+
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn show_error_popup(f: &mut Frame, error: &str) {
+    let width = cmp::min(f.size().width, 60);
+    // the text wraps inside the borders and padding
+    let lines: usize = error
+        .lines()
+        .map(|l| {
+            l.width()
+                .div_ceil((width as usize).saturating_sub(4).max(1))
+                .max(1)
+        })
+        .sum();
+    let area = centered_rect(f.size(), width, lines as u16 + 4);
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(error).wrap(Wrap { trim: true }).block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(theme::current().error)
+                .title(Span::styled("Error", theme::current().error).bold())
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::raw("Press any key").centered())
+                .padding(Padding::new(1, 1, 1, 0)),
+        ),
+        area,
+    );
+}
+
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let c = |len| {
+        [
+            Constraint::Fill(1),
+            Constraint::Length(len),
+            Constraint::Fill(1),
+        ]
+    };
+
+    let c_h = Layout::horizontal(c(width)).split(area);
+    Layout::vertical(c(height)).split(c_h[1])[1]
+}
+
+fn show_power_picker(
+    f: &mut Frame,
+    tiers: &PowerTiers,
+    state: &mut ListState,
+    upper: Option<bool>,
+) {
+    let powers = tiers.powers();
+    let title = match upper {
+        None => "Power",
+        Some(false) => "Lowest power (P for highest)",
+        Some(true) => "Highest power (p for lowest)",
+    };
+    let area = centered_rect(f.size(), 30, powers.len() as u16 + 2);
+    f.render_widget(Clear, area);
+
+    let list = List::new(powers.iter().map(|p| power_str(p, tiers)))
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(title, theme::current().title))
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_symbol(">>")
+        .highlight_spacing(HighlightSpacing::Always);
+    f.render_stateful_widget(list, area, state);
+}
+
+fn show_calibration_popup(f: &mut Frame, calibration: &Calibration, lib: &Library) {
+    let pct = |shares: &std::collections::BTreeMap<Power, f64>, p: &Power| {
+        Line::raw(format!(
+            "{:.1}%",
+            shares.get(p).copied().unwrap_or(0.0) * 100.0
+        ))
+    };
+
+    let rows: Vec<_> = lib
+        .tiers
+        .powers()
+        .into_iter()
+        .filter(|p| calibration.before.contains_key(p) || calibration.target.contains_key(p))
+        .map(|p| {
+            let target = match calibration.target.get(&p) {
+                Some(t) => Line::raw(format!("{:.1}%", t * 100.0)),
+                None => Line::from(Span::styled("-", theme::current().muted)),
+            };
+            let new_weight = calibration.weights.get(&p).copied().unwrap_or(1.0);
+            Row::new([
+                Line::from(power_str(&p, &lib.tiers)),
+                target,
+                pct(&calibration.before, &p),
+                pct(&calibration.after, &p),
+                Line::raw(format!("{:.2} -> {:.2}", lib.power_weight(&p), new_weight)),
+            ])
+        })
+        .collect();
+
+    let area = centered_rect(f.size(), 64, rows.len() as u16 + 6);
+    f.render_widget(Clear, area);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new([
+        "Power".underlined(),
+        "Target".underlined(),
+        "Now".underlined(),
+        "Calibrated".underlined(),
+        "Weight".underlined(),
+    ]))
+    .block(
+        Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(Span::styled(
+                "Calibrate power weights",
+                theme::current().title,
+            ))
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::raw("Enter to apply, Esc to discard").centered())
+            .padding(Padding::uniform(1)),
+    );
+
+    f.render_widget(table, area);
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct Results {
+    results: Vec<DraftResult>,
+    /// The session new results are drafted in, see [`DraftResult::session`].
+    #[serde(default)]
+    session: Option<String>,
+    /// Sessions listed as just their name.
+    #[serde(skip)]
+    collapsed: BTreeSet<String>,
+    /// Lists the archived results too.
+    #[serde(skip)]
+    show_archived: bool,
+    #[serde(skip)]
+    state: ListState,
+    #[serde(skip)]
+    timeline: bool,
+    #[serde(skip)]
+    timeline_scroll: usize,
+    /// Only results assigned to this player are listed.
+    #[serde(skip)]
+    filter: Option<String>,
+    /// The selected mark of the selected result, when the marks have focus.
+    #[serde(skip)]
+    mark_state: ListState,
+    /// Only results that drafted this mark are listed.
+    #[serde(skip)]
+    mark_filter: Option<String>,
+    /// Only results whose name, player, note or marks contain this are listed.
+    #[serde(skip)]
+    query: String,
+    #[serde(skip)]
+    search_box: Option<Prompt<'static>>,
+    /// The result shown next to the selected one, with the marks they share
+    /// highlighted.
+    #[serde(skip)]
+    compare: Option<usize>,
+    /// How many results fit in the list, PageUp/PageDown move by this much.
+    #[serde(skip)]
+    page: usize,
+}
+
+const TIMELINE_COLUMN: usize = 5;
+
+impl Results {
+    /// Adds the results of `other`, and its session if there is none yet.
+    pub fn extend(&mut self, other: Results) {
+        self.results.extend(other.results);
+        if self.session.is_none() {
+            self.session = other.session;
+        }
+    }
+
+    /// Labels the results that don't have a source yet with `source`.
+    pub fn set_source(&mut self, source: &str) {
+        for result in &mut self.results {
+            if result.source.is_none() {
+                result.source = Some(source.to_string());
+            }
+        }
+    }
+
+    /// Adds a result, in the current session unless it has one.
+    pub fn push(&mut self, mut result: DraftResult) {
+        if result.session.is_none() {
+            result.session = self.session.clone();
+        }
+        self.results.push(result);
+    }
+
+    pub fn set_archived(&mut self, i: usize, archived: bool) {
+        self.results[i].archived = archived;
+    }
+
+    pub fn session(&self) -> Option<&str> {
+        self.session.as_deref()
+    }
+
+    /// Starts a session the results from now on are listed under, or stops
+    /// grouping them for `None`.
+    pub fn start_session(&mut self, name: Option<String>) {
+        self.session = name;
+    }
+
+    pub fn list(&self) -> &[DraftResult] {
+        &self.results
+    }
+
+    /// Removes the most recent result, selecting the one before it.
+    fn pop(&mut self) -> Option<DraftResult> {
+        let result = self.results.pop()?;
+        self.mark_state.select(None);
+        if self.compare == Some(self.results.len()) {
+            self.compare = None;
+        }
+        match self.results.len().checked_sub(1) {
+            Some(last) => self.select(last),
+            None => self.state.select(None),
+        }
+        Some(result)
+    }
+
+    /// Indices of the results that aren't archived and pass the player and
+    /// mark filters and the search, those of a session together where its
+    /// first one is. Of a collapsed session only that first one is listed,
+    /// under its name.
+    fn visible(&self) -> Vec<usize> {
+        let shown: Vec<usize> = (0..self.results.len())
+            .filter(|&i| self.show_archived || !self.results[i].archived)
+            .filter(|&i| {
+                self.filter
+                    .as_ref()
+                    .is_none_or(|f| self.results[i].options.player.as_ref() == Some(f))
+            })
+            .filter(|&i| {
+                self.mark_filter
+                    .as_ref()
+                    .is_none_or(|f| self.results[i].marks.iter().any(|m| &m.name == f))
+            })
+            .filter(|&i| self.matches(i))
+            .collect();
+
+        let mut listed = Vec::new();
+        let mut seen = BTreeSet::new();
+        for &i in &shown {
+            match &self.results[i].session {
+                None => listed.push(i),
+                Some(session) if !seen.insert(session) => {}
+                Some(session) if self.collapsed.contains(session) => listed.push(i),
+                Some(session) => listed.extend(
+                    shown
+                        .iter()
+                        .copied()
+                        .filter(|&j| self.results[j].session.as_ref() == Some(session)),
+                ),
+            }
+        }
+        listed
+    }
+
+    /// Whether the `pos`th listed result is the first of its session, the one
+    /// the session's name is shown above.
+    fn starts_session(&self, visible: &[usize], pos: usize) -> bool {
+        let session = &self.results[visible[pos]].session;
+        session.is_some()
+            && visible[..pos]
+                .iter()
+                .all(|&i| &self.results[i].session != session)
+    }
+
+    /// Archives the selected result, or all of its session for `session`.
+    /// An archived result is brought back instead. Returns whether there was
+    /// a result to archive.
+    fn toggle_archived(&mut self, session: bool) -> bool {
+        let Some(i) = self.selected() else {
+            return false;
+        };
+        let archived = !self.results[i].archived;
+        let group = self.results[i].session.clone().filter(|_| session);
+        for (j, result) in self.results.iter_mut().enumerate() {
+            if j == i || group.is_some() && result.session == group {
+                result.archived = archived;
+            }
+        }
+
+        // the next result takes the place of those that are now hidden
+        let len = self.visible().len();
+        self.mark_state.select(None);
+        self.state
+            .select(self.state.selected().map(|s| s.min(len.saturating_sub(1))));
+        if len == 0 {
+            self.state.select(None);
+        }
+        true
+    }
+
+    /// Shows the archived results in the list too, or hides them again.
+    fn toggle_show_archived(&mut self) {
+        let selected = self.selected();
+        self.show_archived = !self.show_archived;
+        match selected {
+            Some(i) => self.select(i),
+            None => self.state.select((!self.visible().is_empty()).then_some(0)),
+        }
+    }
+
+    /// Collapses the session of the selected result, or expands it again.
+    fn toggle_collapsed(&mut self) {
+        let Some(session) = self
+            .selected()
+            .and_then(|i| self.results[i].session.clone())
+        else {
+            return;
+        };
+        if !self.collapsed.remove(&session) {
+            self.collapsed.insert(session.clone());
+        }
+        self.mark_state.select(None);
+        // stay on the session
+        let visible = self.visible();
+        let pos = visible
+            .iter()
+            .position(|&i| self.results[i].session.as_ref() == Some(&session));
+        self.state.select(pos);
+    }
+
+    /// Whether the `i`th result's name, session, player, note or one of its marks
+    /// contains the search query, ignoring case.
+    fn matches(&self, i: usize) -> bool {
+        if self.query.is_empty() {
+            return true;
+        }
+        let query = self.query.to_lowercase();
+        let result = &self.results[i];
+        let contains = |s: &str| s.to_lowercase().contains(&query);
+
+        contains(&format!("Draft #{i}"))
+            || result.name.as_deref().is_some_and(contains)
+            || result.session.as_deref().is_some_and(contains)
+            || result.options.player.as_deref().is_some_and(contains)
+            || contains(&result.note)
+            || result.source.as_deref().is_some_and(contains)
+            || result.marks.iter().any(|m| contains(&m.name))
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search_box.is_some()
+    }
+
+    /// Opens the search prompt with the current query.
+    fn start_search(&mut self) {
+        self.search_box = Some(Prompt {
+            title: Line::raw("Search drafts, sessions, players, notes and marks"),
+            prefix: Span::raw("/"),
+            text: self.query.clone(),
+            cursor_pos: self.query.len(),
+            max_width: 32,
+            ..Default::default()
+        });
+    }
+
+    /// Enter keeps the search, Esc clears it, anything else edits it and
+    /// narrows the list as it is typed.
+    pub fn search_input(&mut self, ev: KeyEvent) {
+        let Some(search_box) = &mut self.search_box else {
+            return;
+        };
+        let query = match search_box.input(ev) {
+            ControlFlow::Continue(_) => search_box.text.clone(),
+            ControlFlow::Break(true) => {
+                self.search_box = None;
+                return;
+            }
+            ControlFlow::Break(false) => {
+                self.search_box = None;
+                String::new()
+            }
+        };
+        self.set_query(query);
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        if let Some(search_box) = &mut self.search_box {
+            search_box.paste(text);
+            let query = search_box.text.clone();
+            self.set_query(query);
+        }
+    }
+
+    /// Changes the search, keeping the selected result if it still matches.
+    fn set_query(&mut self, query: String) {
+        if query == self.query {
+            return;
+        }
+        let selected = self.selected();
+        self.query = query;
+        self.mark_state.select(None);
+
+        let visible = self.visible();
+        let pos = selected.and_then(|s| visible.iter().position(|&v| v == s));
+        self.state
+            .select(pos.or((!visible.is_empty()).then_some(0)));
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.visible().get(self.state.selected()?).copied()
+    }
+
+    fn selected_mut(&mut self) -> Option<&mut DraftResult> {
+        let i = self.selected()?;
+        self.results.get_mut(i)
+    }
+
+    /// Selects the `i`th result, dropping the filters and search if they hide
+    /// it, expanding its session and showing it if it is archived.
+    pub fn select(&mut self, i: usize) {
+        if let Some(session) = &self.results[i].session {
+            self.collapsed.remove(session);
+        }
+        if self.results[i].archived {
+            self.show_archived = true;
+        }
+        if !self.visible().contains(&i) {
+            self.filter = None;
+            self.mark_filter = None;
+            self.query.clear();
+        }
+        self.state
+            .select(self.visible().iter().position(|&v| v == i));
+    }
+
+    /// Cycles the filter through no filter and then each player of `roster`.
+    fn cycle_filter(&mut self, roster: &[Player]) {
+        let next = match &self.filter {
+            None => roster.first(),
+            Some(f) => roster.iter().skip_while(|p| &p.name != f).nth(1),
+        };
+        self.filter = next.map(|p| p.name.clone());
+        self.state.select((!self.visible().is_empty()).then_some(0));
+    }
+
+    /// Lists only the results that drafted `mark`, or all of them again for
+    /// `None`.
+    pub fn filter_mark(&mut self, mark: Option<String>) {
+        self.mark_filter = mark;
+        self.mark_state.select(None);
+        self.state.select((!self.visible().is_empty()).then_some(0));
+    }
+
+    /// Cycles the player the selected result is assigned to.
+    fn cycle_player(&mut self, roster: &[Player]) {
+        let Some(i) = self.selected() else {
+            return;
+        };
+        let player = &mut self.results[i].options.player;
+        let next = match player {
+            None => roster.first(),
+            Some(name) => roster.iter().skip_while(|p| &p.name != name).nth(1),
+        };
+        *player = next.map(|p| p.name.clone());
+
+        // keep the selection on the result even if the filter now hides it
+        self.select(i);
+    }
+
+    pub fn history(&self, window: usize) -> History {
+        self.history_before(self.results.len(), window)
+    }
+
+    /// Every mark `player` drafted, archived drafts too, with the drafts it
+    /// was drafted in, in the order they were first drafted.
+    pub fn player_history(&self, player: &str) -> Vec<(&Mark, Vec<usize>)> {
+        let mut marks: Vec<(&Mark, Vec<usize>)> = Vec::new();
+        for (i, result) in self.results.iter().enumerate() {
+            if result.options.player.as_deref() != Some(player) {
+                continue;
+            }
+            for mark in &result.marks {
+                match marks.iter_mut().find(|(m, _)| m.name == mark.name) {
+                    Some((_, drafts)) => drafts.push(i),
+                    None => marks.push((mark, vec![i])),
+                }
+            }
+        }
+        marks
+    }
+
+    /// The history as it was when the `n`th result was drafted.
+    pub fn history_before(&self, n: usize, window: usize) -> History {
+        let results = &self.results[..n];
+        let names = |results: &[DraftResult]| {
+            results
+                .iter()
+                .flat_map(|r| r.marks.iter().map(|m| m.name.clone()))
+                .collect()
+        };
+
+        let mut by_player: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for result in results {
+            if let Some(player) = &result.options.player {
+                by_player
+                    .entry(player.clone())
+                    .or_default()
+                    .extend(result.marks.iter().map(|m| m.name.clone()));
+            }
+        }
+
+        History {
+            owned: names(results),
+            recent: names(&results[n.saturating_sub(window)..]),
+            by_player,
+            #[cfg(feature = "scripting")]
+            session: results
+                .iter()
+                .filter(|r| r.timestamp >= crate::started())
+                .flat_map(|r| r.marks.iter().cloned())
+                .collect(),
+        }
+    }
+
+    fn next_selection(&mut self) {
+        let len = self.visible().len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= len - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn prev_selection(&mut self) {
+        let len = self.visible().len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    len - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Moves the selection by `delta` results without wrapping around.
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.visible().len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_add_signed(delta).min(len - 1),
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    /// Returns whether a result changed, rather than only what is shown.
+    pub fn input(&mut self, key: KeyCode) -> bool {
+        let marks = self.selected().map_or(0, |i| self.results[i].marks.len());
+        match key {
+            KeyCode::Char('t' | 'T') => self.timeline = !self.timeline,
+            KeyCode::Char('l' | 'L') => self.toggle_show_archived(),
+            KeyCode::Backspace if self.mark_filter.is_some() => self.filter_mark(None),
+            _ if self.results.is_empty() => {}
+            KeyCode::Left if self.timeline => self.prev_selection(),
+            KeyCode::Right if self.timeline => self.next_selection(),
+            KeyCode::Tab if !self.timeline => match self.mark_state.selected() {
+                Some(_) => self.mark_state.select(None),
+                None if marks > 0 => self.mark_state.select(Some(0)),
+                None => {}
+            },
+            KeyCode::Up if self.mark_state.selected().is_some() => self
+                .mark_state
+                .select(self.mark_state.selected().map(|i| i.saturating_sub(1))),
+            KeyCode::Down if self.mark_state.selected().is_some() => self.mark_state.select(
+                self.mark_state
+                    .selected()
+                    .map(|i| cmp::min(i + 1, marks.saturating_sub(1))),
+            ),
+            KeyCode::Up => self.prev_selection(),
+            KeyCode::Down => self.next_selection(),
+            KeyCode::PageUp => self.move_selection(-(cmp::max(self.page, 1) as isize)),
+            KeyCode::PageDown => self.move_selection(cmp::max(self.page, 1) as isize),
+            KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::End => self.move_selection(isize::MAX),
+            KeyCode::Char('/') => self.start_search(),
+            KeyCode::Char(' ') if !self.timeline => self.toggle_collapsed(),
+            KeyCode::Char('m') => return self.toggle_archived(false),
+            KeyCode::Char('M') => return self.toggle_archived(true),
+            KeyCode::Char('v' | 'V') => {
+                self.compare = match self.compare {
+                    Some(_) => None,
+                    None => self.selected(),
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// The selected result and the slot of its selected mark.
+    fn selected_slot(&self) -> Option<(usize, usize)> {
+        let i = self.selected()?;
+        let slot = self.mark_state.selected()?;
+        (slot < self.results[i].marks.len()).then_some((i, slot))
+    }
+
+    /// The border of the draft list, titled with the filter and search and
+    /// showing where in the `len` listed results the selection is.
+    fn list_block(&self, len: usize) -> Block<'static> {
+        let mut block = Block::bordered().border_type(BorderType::Rounded);
+        if let Some(player) = &self.filter {
+            block = block.title(format!("Player: {player}"));
+        }
+        if let Some(mark) = &self.mark_filter {
+            block = block.title(format!("Mark: {mark}"));
+        }
+        if !self.query.is_empty() {
+            block = block.title(Span::styled(
+                format!("/{}", self.query),
+                theme::current().warning,
+            ));
+        }
+        if let Some(session) = &self.session {
+            block = block.title_bottom(Span::styled(
+                format!("Session: {session}"),
+                theme::current().muted,
+            ));
+        }
+        if self.show_archived {
+            block = block.title(Span::styled("With archived", theme::current().muted));
+        }
+        if let Some(i) = self.state.selected().filter(|_| len > 0) {
+            block = block.title_bottom(Line::raw(format!("{} of {len}", i + 1)).right_aligned());
+        }
+        block
+    }
+
+    fn draw_timeline(&mut self, tiers: &PowerTiers, f: &mut Frame, rect: Rect) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title("Timeline")
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(rect);
+        f.render_widget(block, rect);
+
+        let layout = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).split(inner);
+
+        let selected = self.state.selected();
+        let visible = self.visible();
+        let height = visible
+            .iter()
+            .map(|&c| self.results[c].marks.len())
+            .max()
+            .unwrap_or(0);
+        let mut lines = vec![Line::default(); height + 1];
+
+        for (pos, &c) in visible.iter().enumerate() {
+            let result = &self.results[c];
+            let label = format!("{:<TIMELINE_COLUMN$}", format!("#{c}"));
+            lines[0].spans.push(if selected == Some(pos) {
+                Span::styled(label, theme::current().highlight)
+            } else {
+                label.bold()
+            });
+
+            for (row, line) in lines[1..].iter_mut().enumerate() {
+                line.spans.push(match result.marks.get(row) {
+                    Some(m) => Span::styled("■", power_str(&m.power, tiers).style),
+                    None => Span::raw(" "),
+                });
+                line.spans.push(Span::raw(" ".repeat(TIMELINE_COLUMN - 1)));
+            }
+        }
+
+        // keep the selected draft in view
+        let visible = cmp::max(layout[0].width as usize / TIMELINE_COLUMN, 1);
+        if let Some(s) = selected {
+            if s < self.timeline_scroll {
+                self.timeline_scroll = s;
+            } else if s >= self.timeline_scroll + visible {
+                self.timeline_scroll = s + 1 - visible;
+            }
+        }
+
+        let chart =
+            Paragraph::new(lines).scroll((0, (self.timeline_scroll * TIMELINE_COLUMN) as u16));
+        f.render_widget(chart, layout[0]);
+
+        let legend: Vec<_> = tiers
+            .powers()
+            .iter()
+            .flat_map(|p| {
+                let name = power_str(p, tiers);
+                [
+                    Span::styled("■ ", name.style),
+                    Span::raw(name.content),
+                    Span::raw("  "),
+                ]
+            })
+            .collect();
+        f.render_widget(Line::from(legend), layout[1]);
+    }
+
+    pub fn draw(&mut self, lib: &Library, f: &mut Frame, rect: Rect) {
+        if self.timeline && !self.visible().is_empty() {
+            self.draw_timeline(&lib.tiers, f, rect);
+        } else {
+            self.draw_list(lib, f, rect);
+        }
+
+        if let Some(search_box) = &mut self.search_box {
+            search_box.draw(f, rect);
+        }
+    }
+
+    fn draw_list(&mut self, lib: &Library, f: &mut Frame, rect: Rect) {
+        let theme = theme::current();
+        let visible = self.visible();
+
+        // beyond the 15 columns that fit "Draft #N" and the borders
+        let extra = visible
+            .iter()
+            .map(|&c| {
+                let result = &self.results[c];
+                let name = result
+                    .name
+                    .as_ref()
+                    .map_or(0, |n| n.width().saturating_sub(10));
+                let player = result.options.player.as_ref().map_or(0, |p| p.len() + 3);
+                let source = result.source.as_ref().map_or(0, |s| s.len() + 3);
+                // drafts of a session are indented under its name
+                match &result.session {
+                    Some(session) => cmp::max(name + player + source + 2, session.len() + 2),
+                    None => name + player + source,
+                }
+            })
+            .max()
+            .unwrap_or(0);
+        let layout = Layout::new(
+            Direction::Horizontal,
+            [
+                Constraint::Length(15 + extra as u16),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .split(rect);
+        let draft_list = List::new(visible.iter().enumerate().map(|(pos, &c)| {
+            let result = &self.results[c];
+            let mut line = Line::raw(match &result.options.player {
+                Some(player) => format!("{} — {player}", result.title(c)),
+                None => result.title(c),
+            });
+            if let Some(source) = &result.source {
+                line.spans
+                    .push(Span::styled(format!(" · {source}"), theme.muted));
+            }
+            if self.compare == Some(c) {
+                line.spans.push(Span::styled(" ⇄", theme.warning));
+            }
+            if result.archived {
+                line = line.patch_style(theme.muted);
+                line.spans
+                    .push(Span::styled(" archived", theme.muted).italic());
+            }
+            let Some(session) = &result.session else {
+                return Text::from(line);
+            };
+            line.spans.insert(0, Span::raw("  "));
+            if !self.starts_session(&visible, pos) {
+                return Text::from(line);
+            }
+
+            let collapsed = self.collapsed.contains(session);
+            let drafts = self
+                .results
+                .iter()
+                .filter(|r| r.session.as_ref() == Some(session))
+                .count();
+            let header = Line::from(vec![
+                Span::raw(if collapsed { "▸ " } else { "▾ " }),
+                session.clone().bold(),
+                Span::styled(format!(" ({drafts})"), theme.muted),
+            ]);
+            if collapsed {
+                Text::from(header)
+            } else {
+                Text::from(vec![header, line])
+            }
+        }))
+        .block(self.list_block(visible.len()))
+        .highlight_symbol(">>")
+        .highlight_spacing(HighlightSpacing::Always);
+        self.page = layout[0].height.saturating_sub(2) as usize;
+
+        if draft_list.is_empty() {
+            let archived = !self.show_archived && self.results.iter().any(|r| r.archived);
+            let empty = if self.query.is_empty() && self.mark_filter.is_none() {
+                if archived {
+                    "<empty, L shows the archived drafts>"
+                } else {
+                    "<empty>"
+                }
+            } else {
+                "<no matches>"
+            };
+            f.render_widget(
+                Paragraph::new(Span::styled(empty, theme.muted).italic())
+                    .block(self.list_block(0))
+                    .centered(),
+                layout[0],
+            );
+            f.render_widget(
+                Block::bordered().border_type(BorderType::Rounded),
+                layout[1],
+            );
+        } else {
+            f.render_stateful_widget(draft_list, layout[0], &mut self.state);
+            let selected = self.selected();
+            let DraftResult {
+                marks: mark_list,
+                draws,
+                options,
+                wishlisted,
+                note,
+                rerolls,
+                fallbacks,
+                ..
+            } = match selected {
+                Some(i) => self.results[i].clone(),
+                None => DraftResult::default(),
+            };
+            let excluded: BTreeSet<usize> = lib
+                .exclusions(&mark_list)
+                .into_iter()
+                .flat_map(|(a, b)| [a, b])
+                .collect();
+            let compared = self.compare.and_then(|c| self.results.get(c));
+            let shared: BTreeSet<&str> = compared
+                .map(|c| {
+                    let names: BTreeSet<_> = c.marks.iter().map(|m| m.name.as_str()).collect();
+                    mark_list
+                        .iter()
+                        .map(|m| m.name.as_str())
+                        .filter(|m| names.contains(m))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let shared_style = Style::new().on_dark_gray();
+
+            let listing = List::new(mark_list.iter().enumerate().map(|(slot, m)| {
+                let power_span = power_str(&m.power, &lib.tiers);
+                let mut line = Line::from(m.name.as_str().set_style(power_span.style));
+                if shared.contains(m.name.as_str()) {
+                    line = line.patch_style(shared_style);
+                }
+                if wishlisted.contains(&m.name) {
+                    line.spans.push(Span::styled(" ★", theme.pinned));
+                }
+                if rerolls.iter().any(|(s, _)| *s == slot) {
+                    line.spans.push(Span::styled(" ↺", theme.muted));
+                }
+                if let Some(power) = fallbacks.get(&slot).filter(|p| **p == m.power) {
+                    line.spans.push(Span::styled(
+                        format!(" fell back to {}", power.name()),
+                        theme.muted,
+                    ));
+                }
+                if excluded.contains(&slot) {
+                    line.spans.push(Span::styled(" ✗ excluded", theme.error));
+                }
+                line
+            }))
+            .block(
+                match (compared, selected) {
+                    (Some(_), Some(i)) => Block::bordered().title(self.results[i].title(i)),
+                    _ => Block::bordered(),
+                }
+                .border_type(BorderType::Rounded)
+                .padding(Padding {
+                    left: 2,
+                    top: 1,
+                    ..Default::default()
+                }),
+            )
+            .highlight_symbol(">>")
+            .highlight_spacing(HighlightSpacing::Always);
+
+            let editor = DraftEditor {
+                draws,
+                options,
+                ..Default::default()
+            };
+            let draw = editor.draw(&lib.tiers, None).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .padding(Padding {
+                        left: 4,
+                        top: 1,
+                        ..Default::default()
+                    }),
+            );
+
+            if note.is_empty() {
+                f.render_stateful_widget(listing, layout[1], &mut self.mark_state);
+            } else {
+                let height = note.lines().count() as u16 + 2;
+                let note = Paragraph::new(note).wrap(Wrap { trim: false }).block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .title("Note")
+                        .padding(Padding::horizontal(1)),
+                );
+                let split = Layout::vertical([Constraint::Fill(1), Constraint::Length(height)])
+                    .split(layout[1]);
+                f.render_stateful_widget(listing, split[0], &mut self.mark_state);
+                f.render_widget(note, split[1]);
+            }
+
+            match (compared, self.compare) {
+                (Some(compared), Some(c)) => {
+                    let lines: Vec<_> = compared
+                        .marks
+                        .iter()
+                        .map(|m| {
+                            let power_span = power_str(&m.power, &lib.tiers);
+                            let line = Line::from(m.name.as_str().set_style(power_span.style));
+                            if shared.contains(m.name.as_str()) {
+                                line.patch_style(shared_style)
+                            } else {
+                                line
+                            }
+                        })
+                        .collect();
+                    let title = match &compared.options.player {
+                        Some(player) => format!("{} — {player}", compared.title(c)),
+                        None => compared.title(c),
+                    };
+                    let paragraph = Paragraph::new(lines).block(
+                        Block::bordered()
+                            .border_type(BorderType::Rounded)
+                            .title(Span::styled(title, theme.warning))
+                            .title_bottom(
+                                Line::raw(format!("{} shared", shared.len())).right_aligned(),
+                            )
+                            .padding(Padding {
+                                left: 4,
+                                top: 1,
+                                ..Default::default()
+                            }),
+                    );
+                    f.render_widget(paragraph, layout[2]);
+                }
+                _ => f.render_widget(draw, layout[2]),
+            }
+        }
+    }
+}
+
+impl DraftView {
+    pub fn new(lib: &Library) -> Self {
+        DraftView {
+            selected_tab: Pane::Left,
+            mark_list: MarkList::new(lib),
+            draft: DraftEditor::default(),
+            others: Vec::new(),
+            current: 0,
+            closing: Confirm::default(),
+            message: None,
+        }
+    }
+
+    /// Every draft being prepared, the one being edited too.
+    fn drafts_mut(&mut self) -> impl Iterator<Item = &mut DraftEditor> {
+        std::iter::once(&mut self.draft).chain(&mut self.others)
+    }
+
+    /// Edits the `i`th draft of all, `current` is put back between the others.
+    fn select_draft(&mut self, i: usize) {
+        self.others
+            .insert(self.current, std::mem::take(&mut self.draft));
+        self.draft = self.others.remove(i);
+        self.current = i;
+        self.closing.reset();
+    }
+
+    fn switch_draft(&mut self, dir: Dir) {
+        let len = self.others.len() + 1;
+        self.select_draft(match dir {
+            Dir::Left => (self.current + len - 1) % len,
+            Dir::Right => (self.current + 1) % len,
+        });
+    }
+
+    /// Starts an empty draft after the one being edited and edits it.
+    fn new_draft(&mut self) {
+        self.others
+            .insert(self.current, std::mem::take(&mut self.draft));
+        self.current += 1;
+        self.closing.reset();
+    }
+
+    /// Drops the draft being edited for the next one, or the previous one
+    /// if it was the last. A draft with draws has to be closed twice.
+    fn close_draft(&mut self, key: KeyCode) {
+        if self.others.is_empty() || !self.draft.draws.is_empty() && !self.closing.confirm(key) {
+            return;
+        }
+        let i = cmp::min(self.current, self.others.len() - 1);
+        self.draft = self.others.remove(i);
+        self.current = i;
+    }
+
+    /// Returns whether the library changed, rather than only the draft.
+    pub fn input(&mut self, lib: &mut Library, ev: KeyEvent) -> bool {
+        self.message = None;
+        self.closing.reset();
+
+        match ev.code {
+            KeyCode::Tab => {
+                self.selected_tab = match self.selected_tab {
+                    Pane::Left => Pane::Right,
+                    Pane::Right => Pane::Left,
+                };
+                false
+            }
+            _ if self.selected_tab == Pane::Left => {
+                self.draft.input(lib, ev);
+                false
+            }
+            KeyCode::Char('p' | 'P') => {
+                if let Some(mark) = self.mark_list.selected_mark(lib) {
+                    let options = &mut self.draft.options;
+                    options.banned.remove(&mark.name);
+                    if !options.pinned.remove(&mark.name) {
+                        options.pinned.insert(mark.name.clone());
+                    }
+                }
+                false
+            }
+            KeyCode::Char('b' | 'B') => {
+                if let Some(mark) = self.mark_list.selected_mark(lib) {
+                    let options = &mut self.draft.options;
+                    options.pinned.remove(&mark.name);
+                    if !options.banned.remove(&mark.name) {
+                        options.banned.insert(mark.name.clone());
+                    }
+                }
+                false
+            }
+            KeyCode::Char('l' | 'L') => {
+                let mark = self.mark_list.selected_mark(lib).map(|m| m.name.clone());
+                let (Some(mark), Some(player)) = (mark, &self.draft.options.player) else {
+                    return false;
+                };
+                let wishlist = &mut lib.player_mut(player).wishlist;
+                if !wishlist.remove(&mark) {
+                    wishlist.insert(mark);
+                }
+                true
+            }
+            k if self.selected_tab == Pane::Right => self.mark_list.input(lib, k),
+            _ => false,
+        }
+    }
+
+    pub fn draw(&mut self, lib: &Library, history: &History, f: &mut Frame, rect: Rect) {
+        let theme = theme::current();
+        let inactive_tab = theme.border_inactive;
+        let active_tab = theme.border_active;
+
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(&[Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rect);
+
+        let mut title = Line::raw("Draft");
+        if !self.others.is_empty() {
+            for i in 0..=self.others.len() {
+                let label = Span::raw(format!(" {} ", i + 1));
+                title.spans.push(if i == self.current {
+                    label.reversed()
+                } else {
+                    label.patch_style(theme.muted)
+                });
+            }
+        }
+        if self.draft.confirm.is_armed() {
+            title
+                .spans
+                .push(Span::styled(" (press again to delete)", theme.error));
+        }
+        if self.closing.is_armed() {
+            title
+                .spans
+                .push(Span::styled(" (press again to close)", theme.error));
+        }
+
+        // how many marks the selected draw can take, unless there is news
+        let footer = match (self.message, self.draft.selected_index()) {
+            (Some(message), _) => Line::from(Span::styled(message, theme.error)),
+            (None, Some(i)) => {
+                let options = &self.draft.options;
+                let (eligible, waiting) = lib.pool_preview(&self.draft.draws[i], options, history);
+                let mut footer = Line::from(Span::styled(
+                    format!("{eligible} marks in the pool"),
+                    theme.muted,
+                ));
+                if waiting > 0 {
+                    footer.spans.push(Span::styled(
+                        format!(", {waiting} waiting on requirements"),
+                        theme.warning,
+                    ));
+                }
+                footer
+            }
+            (None, None) => Line::default(),
+        };
+        let left_block = Block::default()
+            .title(title)
+            .title_bottom(footer)
+            .borders(Borders::ALL)
+            .border_style(match self.selected_tab {
+                Pane::Right => inactive_tab,
+                Pane::Left => active_tab,
+            })
+            .padding(Padding {
+                left: 3,
+                right: 0,
+                top: 1,
+                bottom: 0,
+            })
+            .border_type(BorderType::Rounded);
+        let rect = left_block.inner(cols[0]);
+        f.render_widget(left_block, cols[0]);
+
+        let content = self.draft.text(&lib.tiers, Some(lib.recency)).height();
+        let height = rect.height as usize;
+        self.draft.follow(height, content);
+
+        let mark_draft = self.draft.draw(&lib.tiers, Some(lib.recency));
+        f.render_widget(mark_draft, rect);
+
+        if content > height {
+            let mut scrollbar = ScrollbarState::new(content - height).position(self.draft.scroll);
+            f.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                cols[0].inner(&Margin::new(0, 1)),
+                &mut scrollbar,
+            );
+        }
+
+        let mut mark_title = Line::raw("Marks");
+        if self.mark_list.confirm.is_armed() {
+            mark_title
+                .spans
+                .push(Span::styled(" (press again to confirm)", theme.error));
+        }
+        let mark_block = Block::default()
+            .title(mark_title)
+            .borders(Borders::ALL)
+            .border_style(match self.selected_tab {
+                Pane::Left => inactive_tab,
+                Pane::Right => active_tab,
+            })
+            .border_type(ratatui::widgets::BorderType::Rounded);
+        let mark_inner = mark_block.inner(cols[1]);
+        f.render_widget(mark_block, cols[1]);
+
+        self.mark_list.draw(lib, &self.draft.options, f, mark_inner);
+    }
+}
+
+#[derive(Default)]
+pub struct DraftEditor {
+    draws: Vec<Draw>,
+    options: DraftOptions,
+    line: usize,
+    scroll: usize,
+    /// Rows the editor had when it was last drawn.
+    viewport: usize,
+    confirm: Confirm,
+    /// The power picked last, the picker starts on it for the next draw.
+    last_power: Option<Power>,
+    /// Indices of the draws shown as a one line summary.
+    collapsed: BTreeSet<usize>,
+    /// Indices of the draws no free mark fits, shown in red.
+    empty: BTreeSet<usize>,
+}
+
+fn draw_lines(draw: &Draw, collapsed: bool) -> usize {
+    if collapsed {
+        return 1;
+    }
+    1 + draw.power.is_some() as usize
+        + draw.category.is_some() as usize
+        + draw.library.is_some() as usize
+        + draw.upgrade as usize
+        + draw.tag_count()
+        + draw.filter.is_some() as usize
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Dir {
+    Left,
+    Right,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum ElementKind {
+    Mark,
+    Power,
+    Category,
+    Library,
+    Upgrade,
+    Tag(usize),
+    Filter,
+}
+
+impl DraftEditor {
+    pub fn input(&mut self, lib: &Library, ev: KeyEvent) {
+        let key = ev.code;
+        let shift = ev.modifiers.contains(KeyModifiers::SHIFT);
+
+        if !matches!(key, KeyCode::Backspace | KeyCode::Char('-')) {
+            self.confirm.reset();
+        }
+
+        let ctrl = ev.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key {
+            KeyCode::Down if ctrl => self.jump_draw(Dir::Right),
+            KeyCode::Up if ctrl => self.jump_draw(Dir::Left),
+            KeyCode::Char('n') => self.jump_draw(Dir::Right),
+            KeyCode::Char('N') => self.jump_draw(Dir::Left),
+            KeyCode::Down => self.line = cmp::min(self.max_line().saturating_sub(1), self.line + 1),
+            KeyCode::PageUp => self.line = self.line.saturating_sub(cmp::max(self.viewport, 1)),
+            KeyCode::PageDown => {
+                self.line = cmp::min(
+                    self.line + cmp::max(self.viewport, 1),
+                    self.max_line().saturating_sub(1),
+                )
+            }
+            KeyCode::Up => self.line = self.line.saturating_sub(1),
+            KeyCode::Left if !self.draws.is_empty() => {
+                self.rotate_current_element(lib, Dir::Left, shift)
+            }
+            KeyCode::Right if !self.draws.is_empty() => {
+                self.rotate_current_element(lib, Dir::Right, shift)
+            }
+            KeyCode::Backspace | KeyCode::Char('-') if !self.draws.is_empty() => {
+                if self.confirm.confirm(key) {
+                    self.delete_current_element()
+                }
+            }
+            KeyCode::Char('a' | 'A' | '+') => self.add_plain_mark(),
+            KeyCode::Char('c' | 'C') if !self.draws.is_empty() => self.add_or_modify_category(lib),
+            KeyCode::Char('l' | 'L') if !self.draws.is_empty() => self.add_or_modify_library(lib),
+            KeyCode::Char('u' | 'U') if !self.draws.is_empty() => {
+                self.get_selected_draw().upgrade = true
+            }
+            KeyCode::Char('o' | 'O') if !self.draws.is_empty() => self.toggle_tag_or(),
+            KeyCode::Char('m' | 'M') if !self.draws.is_empty() => self.cycle_power_mode(),
+            KeyCode::Char('x' | 'X') => {
+                self.options.unique_categories = !self.options.unique_categories
+            }
+            KeyCode::Char('e' | 'E') => {
+                self.options.strategy = strategy::next(self.options.strategy.as_deref())
+            }
+            KeyCode::Char('g' | 'G') => {
+                self.options.exclude_drafted = Exclusion::next(self.options.exclude_drafted)
+            }
+            KeyCode::Char(' ') if !self.draws.is_empty() => self.toggle_collapsed(),
+            KeyCode::Char('z' | 'Z') if !self.draws.is_empty() => self.toggle_all_collapsed(),
+            _ => {}
+        }
+    }
+
+    pub fn max_line(&self) -> usize {
+        (0..self.draws.len()).map(|i| self.lines_of(i)).sum()
+    }
+
+    fn lines_of(&self, i: usize) -> usize {
+        draw_lines(&self.draws[i], self.collapsed.contains(&i))
+    }
+
+    /// Moves the cursor to the header of the next or previous draw.
+    fn jump_draw(&mut self, dir: Dir) {
+        let mut starts = (0..self.draws.len()).scan(0, |start, i| {
+            let line = *start;
+            *start += self.lines_of(i);
+            Some(line)
+        });
+        let target = match dir {
+            Dir::Left => starts.take_while(|&s| s < self.line).last(),
+            Dir::Right => starts.find(|&s| s > self.line),
+        };
+        if let Some(line) = target {
+            self.line = line;
+        }
+    }
+
+    /// Collapses or expands the selected draw, keeping the cursor on it.
+    fn toggle_collapsed(&mut self) {
+        let (_, _, i) = self.get_selection();
+        if !self.collapsed.remove(&i) {
+            self.collapsed.insert(i);
+        }
+        self.line = (0..i).map(|i| self.lines_of(i)).sum();
+    }
+
+    /// Collapses every draw, or expands them all if they already are.
+    fn toggle_all_collapsed(&mut self) {
+        let (_, _, i) = self.get_selection();
+        if self.collapsed.len() == self.draws.len() {
+            self.collapsed.clear();
+        } else {
+            self.collapsed = (0..self.draws.len()).collect();
+        }
+        self.line = (0..i).map(|i| self.lines_of(i)).sum();
+    }
+
+    /// Replaces the draws being edited, e.g. with those of an earlier result,
+    /// leaving out what `lib` no longer has.
+    pub fn load(&mut self, draws: Vec<Draw>, lib: &Library) {
+        self.draws = draws;
+        self.repair(lib);
+        self.collapsed.clear();
+        self.line = 0;
+        self.scroll = 0;
+        self.confirm.reset();
+    }
+
+    /// Drops what `lib` doesn't have from the draws, see [`Draw::repair`].
+    fn repair(&mut self, lib: &Library) {
+        for draw in &mut self.draws {
+            draw.repair(lib);
+        }
+        self.line = cmp::min(self.line, self.max_line().saturating_sub(1));
+    }
+
+    pub fn add_plain_mark(&mut self) {
+        self.draws.push(Draw::default());
+    }
+
+    /// The power the picker starts on: the selected draw's own, else the
+    /// last one picked, else Supreme or the highest tier.
+    fn initial_power(&mut self, tiers: &PowerTiers, upper: bool) -> Option<Power> {
+        let own = self
+            .get_selected_draw()
+            .power
+            .as_ref()
+            .map(|p| p.bound(upper).clone());
+        own.or_else(|| self.last_power.clone())
+            .or_else(|| tiers.power("Supreme"))
+            .or_else(|| tiers.powers().pop())
+    }
+
+    /// Sets the selected draw's power, keeping "or better"/"or worse" if it
+    /// had one. Of a range only the bound picked by `upper` changes.
+    fn set_power(&mut self, power: Power, upper: bool, tiers: &PowerTiers) {
+        self.last_power = Some(power.clone());
+        match &mut self.get_selected_draw().power {
+            Some(constraint) => constraint.set_bound(upper, power, tiers),
+            constraint => *constraint = Some(PowerConstraint::Exact(power)),
+        }
+    }
+
+    fn cycle_power_mode(&mut self) {
+        if let ElementKind::Power = self.get_element_kind() {
+            let power = &mut self.get_selected_draw().power;
+            *power = power.take().map(PowerConstraint::next_mode);
+        }
+    }
+
+    /// The index of the draw the cursor is on.
+    fn selected_index(&self) -> Option<usize> {
+        let mut end = 0;
+        (0..self.draws.len()).find(|&i| {
+            end += self.lines_of(i);
+            self.line < end
+        })
+    }
+
+    pub fn get_selected_draw(&mut self) -> &mut Draw {
+        self.get_selection().0
+    }
+
+    pub fn get_selection(&mut self) -> (&mut Draw, usize, usize) {
+        assert!(
+            !self.draws.is_empty(),
+            "Tried to get selected draw with no draws in the draft"
+        );
+        let mut cur_draw = (0, self.lines_of(0));
+        let mut i = 0;
+        while !(self.line >= cur_draw.0 && self.line < cur_draw.1) {
+            i += 1;
+            cur_draw = (cur_draw.1, cur_draw.1 + self.lines_of(i));
+        }
+
+        (&mut self.draws[i], self.line - cur_draw.0, i)
+    }
+
+    fn add_or_modify_category(&mut self, lib: &Library) {
+        if let Some(category) = lib.categories.first() {
+            self.get_selected_draw().category = Some(category.clone());
+        }
+    }
+
+    fn add_or_modify_library(&mut self, lib: &Library) {
+        if let Some(library) = lib.libraries().into_iter().next() {
+            self.get_selected_draw().library = Some(library);
+        }
+    }
+
+    fn get_element_kind(&mut self) -> ElementKind {
+        let (draw, offset, _) = self.get_selection();
+        let mut v = vec![ElementKind::Mark];
+        if draw.power.is_some() {
+            v.push(ElementKind::Power);
+        }
+        if draw.category.is_some() {
+            v.push(ElementKind::Category);
+        }
+        if draw.library.is_some() {
+            v.push(ElementKind::Library);
+        }
+        if draw.upgrade {
+            v.push(ElementKind::Upgrade);
+        }
+        for c in 0..draw.tag_count() {
+            v.push(ElementKind::Tag(c));
+        }
+        if draw.filter.is_some() {
+            v.push(ElementKind::Filter);
+        }
+        v[offset]
+    }
+
+    fn rotate_current_element(&mut self, lib: &Library, dir: Dir, upper: bool) {
+        let element_kind = self.get_element_kind();
+        log::debug!("rotating {element_kind:?}");
+        let draw = self.get_selected_draw();
+
+        /// The candidate after `x` in `v`, or the first one if `x` isn't
+        /// one, e.g. a label the library no longer has.
+        fn find_and_rotate<T: PartialEq>(x: &T, mut v: Vec<T>, dir: Dir) -> Option<T> {
+            let Some(i) = v.iter().position(|y| y == x) else {
+                return v.into_iter().next();
+            };
+            let i = match dir {
+                Dir::Left => (i + 1) % v.len(),
+                Dir::Right => (i + v.len() - 1) % v.len(),
+            };
+            Some(v.swap_remove(i))
+        }
+
+        if let ElementKind::Power = element_kind {
+            let constraint = draw.power.as_mut().unwrap();
+            if let Some(p) = find_and_rotate(constraint.bound(upper), lib.tiers.powers(), dir) {
+                constraint.set_bound(upper, p, &lib.tiers);
+            }
+        }
+
+        if let ElementKind::Category = element_kind {
+            let categories: Vec<_> = lib.categories.iter().cloned().collect();
+            let category = draw.category.take().unwrap();
+
+            draw.category = find_and_rotate(&category, categories, dir);
+        }
+
+        if let ElementKind::Library = element_kind {
+            let library = draw.library.take().unwrap();
+            draw.library = find_and_rotate(&library, lib.libraries(), dir);
+        }
+
+        if let ElementKind::Tag(n) = element_kind {
+            let mut tags = lib.tags.clone();
+            let mut existing_tags: Vec<_> = draw.all_tags().cloned().collect();
+            let tag = existing_tags.remove(n);
+            for tag in existing_tags {
+                tags.remove(&tag);
+            }
+            let tags: Vec<_> = tags.into_iter().collect();
+
+            if let Some(tag) = find_and_rotate(&tag, tags, dir) {
+                *draw.tag_mut(n) = tag;
+            }
+        }
+    }
+
+    /// Whether the cursor is on the first line of a draw, where deleting
+    /// removes the whole draw.
+    fn on_draw_line(&mut self) -> bool {
+        !self.draws.is_empty() && matches!(self.get_element_kind(), ElementKind::Mark)
+    }
+
+    fn delete_current_element(&mut self) {
+        let element_kind = self.get_element_kind();
+        let (draw, _, idx) = self.get_selection();
+        if let ElementKind::Mark = element_kind {
+            let _ = draw;
+            self.draws.remove(idx);
+            // later draws move up one place
+            self.collapsed = std::mem::take(&mut self.collapsed)
+                .into_iter()
+                .filter(|&c| c != idx)
+                .map(|c| if c > idx { c - 1 } else { c })
+                .collect();
+        } else {
+            match element_kind {
+                ElementKind::Mark => {}
+                ElementKind::Power => draw.power = None,
+                ElementKind::Category => draw.category = None,
+                ElementKind::Library => draw.library = None,
+                ElementKind::Upgrade => draw.upgrade = false,
+                ElementKind::Tag(n) => draw.remove_tag(n),
+                ElementKind::Filter => draw.filter = None,
+            }
+        }
+        self.line = self.line.saturating_sub(1);
+    }
+
+    fn toggle_tag_or(&mut self) {
+        if let ElementKind::Tag(n) = self.get_element_kind() {
+            self.get_selected_draw().toggle_tag_or(n);
+        }
+    }
+
+    fn add_tag(&mut self, tag: String) {
+        let draw = self.get_selected_draw();
+        if !draw.all_tags().any(|t| t == &tag) {
+            draw.tags.push(vec![tag])
+        }
+    }
+
+    /// Scrolls so the selected line is within `height` rows, without
+    /// scrolling past the end of the `content` rows.
+    fn follow(&mut self, height: usize, content: usize) {
+        self.viewport = height;
+        if self.line < self.scroll {
+            self.scroll = self.line;
+        } else if self.line >= self.scroll + height {
+            self.scroll = self.line + 1 - height;
+        }
+        self.scroll = cmp::min(self.scroll, content.saturating_sub(height));
+    }
+
+    /// Draws the draft, showing the library's `recency` penalty with the
+    /// options if it applies to the draft.
+    pub fn draw<'a>(&'a self, tiers: &PowerTiers, recency: Option<Recency>) -> Paragraph<'a> {
+        Paragraph::new(self.text(tiers, recency)).scroll((self.scroll as u16, 0))
+    }
+
+    fn text<'a>(&'a self, tiers: &PowerTiers, recency: Option<Recency>) -> Text<'a> {
+        let theme = theme::current();
+        let highlight = theme.highlight;
+        let mut i = 0;
+        let mut style_line = || {
+            let style = if i == self.line {
+                highlight
+            } else {
+                Style::default()
+            };
+            i += 1;
+            style
+        };
+
+        let mut text = Text::from(vec![]);
+        if self.draws.is_empty() {
+            text.extend([Line::from(
+                Span::styled("<no draws, press A to add one>", theme.muted).italic(),
+            )]);
+        }
+
+        // the most the draft can cost, added up draw by draw
+        let budgeted = self.options.budget.is_some() || tiers.has_costs();
+        let mut total = 0;
+        for (c, draw) in self.draws.iter().enumerate() {
+            let mut lines = if self.collapsed.contains(&c) {
+                vec![format_collapsed_draw(draw, c, tiers).style(style_line())]
+            } else {
+                format_draw(draw, c, tiers, self.empty.contains(&c), &mut style_line)
+            };
+            if self.collapsed.contains(&c) && self.empty.contains(&c) {
+                lines[0]
+                    .spans
+                    .push(Span::styled(" (no candidates)", theme.error).bold());
+            }
+            if budgeted {
+                let most = tiers.max_cost(draw.power.as_ref());
+                total += most;
+                lines[0].spans.push(
+                    Span::styled(
+                        format!("  up to {most} points, {total} so far"),
+                        theme.muted,
+                    )
+                    .italic(),
+                );
+            }
+            text.extend(lines);
+        }
+
+        if self.options.unique_categories {
+            text.extend([
+                Line::default(),
+                Line::from("No duplicate categories".bold()),
+            ]);
+        }
+        if let Some(exclusion) = self.options.exclude_drafted {
+            let mut line = Line::from(exclusion.label().bold());
+            if exclusion == Exclusion::Player && self.options.player.is_none() {
+                line.spans
+                    .push(Span::styled(" (the draft has no player)", theme.error));
+            }
+            text.extend([Line::default(), line]);
+        }
+        if self.options.strategy.is_some() {
+            let strategy = strategy::get(self.options.strategy.as_deref());
+            text.extend([Line::default(), Line::from(strategy.label().bold())]);
+        }
+        if let Some(player) = &self.options.player {
+            text.extend([
+                Line::default(),
+                label_text_span("Player", Span::raw(player.as_str())),
+            ]);
+        }
+        if let Some(budget) = self.options.budget {
+            let strict = if budget.strict { ", never over" } else { "" };
+            let mut line = label_text_span(
+                "Budget",
+                Span::raw(format!("{} points{strict}", budget.points)),
+            );
+            if total > budget.points {
+                line.spans.push(Span::styled(
+                    format!(" (the draws may cost {total})"),
+                    theme.error,
+                ));
+            }
+            text.extend([Line::default(), line]);
+        }
+        if let Some(recency) = recency.filter(|r| r.window > 0) {
+            let drafts = if recency.window == 1 {
+                "draft"
+            } else {
+                "drafts"
+            };
+            text.extend([
+                Line::default(),
+                label_text_span(
+                    "Recency",
+                    Span::raw(format!(
+                        "last {} {drafts}, weight ×{}",
+                        recency.window,
+                        recency.penalty.max(Recency::MIN_PENALTY)
+                    )),
+                ),
+            ]);
+        }
+        for (label, marks) in [
+            ("Pinned", &self.options.pinned),
+            ("Banned", &self.options.banned),
+        ] {
+            if !marks.is_empty() {
+                let names: String = marks.iter().map(String::as_str).intersperse(", ").collect();
+                text.extend([Line::default(), label_text_span(label, Span::raw(names))]);
+            }
+        }
+
+        text
+    }
+}
+
+/// A draw on one line, e.g. "Draw 3 [Supreme, Beast, +2 tags]".
+fn format_collapsed_draw<'a>(draw: &'a Draw, n: usize, tiers: &PowerTiers) -> Line<'a> {
+    let mut parts = vec![];
+    if let Some(p) = &draw.power {
+        let mut span = power_str(p.bound(false), tiers);
+        let suffix = match p {
+            PowerConstraint::Exact(_) => String::new(),
+            PowerConstraint::AtLeast(_) => "+".to_string(),
+            PowerConstraint::AtMost(_) => "-".to_string(),
+            PowerConstraint::Range(_, hi) => format!("..{}", hi.name()),
+        };
+        span.content = format!("{}{suffix}", span.content).into();
+        parts.push(span);
+    }
+    if let Some(c) = &draw.category {
+        parts.push(Span::raw(c.as_str()));
+    }
+    if let Some(l) = &draw.library {
+        parts.push(Span::raw(l.as_str()));
+    }
+    if draw.upgrade {
+        parts.push(Span::raw("upgrade"));
+    }
+    match draw.tag_count() {
+        0 => {}
+        1 => parts.push(Span::raw("+1 tag")),
+        n => parts.push(Span::raw(format!("+{n} tags"))),
+    }
+    if let Some(f) = &draw.filter {
+        parts.push(Span::raw(f.to_string()));
+    }
+
+    let mut line = Line::from(Span::styled(
+        format!("Draw {} ", n + 1),
+        theme::current().title,
+    ));
+    line.spans.push(Span::raw("["));
+    line.spans
+        .extend(parts.into_iter().intersperse(Span::raw(", ")));
+    line.spans.push(Span::raw("]"));
+    line
+}
+
+/// A draw line by line, all in the error color if no free mark fits it
+/// (`empty`).
+fn format_draw<'a, F: FnMut() -> Style>(
+    draw: &'a Draw,
+    n: usize,
+    tiers: &PowerTiers,
+    empty: bool,
+    mut style_line: F,
+) -> Vec<Line<'a>> {
+    let mut v = vec![];
+    let mut header = Line::styled(
+        format!("Draw {}", n + 1),
+        style_line().patch(theme::current().title),
+    );
+    if empty {
+        header
+            .spans
+            .push(Span::styled(" (no candidates)", theme::current().error).bold());
+    }
+    v.push(header);
+    let mut style_line = || {
+        let style = style_line();
+        if empty {
+            style.patch(theme::current().error)
+        } else {
+            style
+        }
+    };
+    if let Some(p) = &draw.power {
+        let mut line = label_text_span(">> Power", power_str(p.bound(false), tiers));
+        match p {
+            PowerConstraint::Exact(_) => {}
+            PowerConstraint::AtLeast(_) => line.spans.push(Span::raw(" or better")),
+            PowerConstraint::AtMost(_) => line.spans.push(Span::raw(" or worse")),
+            PowerConstraint::Range(_, hi) => {
+                line.spans.push(Span::raw(" to "));
+                line.spans.push(power_str(hi, tiers));
+            }
+        }
+        v.push(line.style(style_line()));
+    }
+    if let Some(c) = &draw.category {
+        v.push(label_text_span(">> Category", Span::raw(c.as_str())).style(style_line()));
+    }
+    if let Some(l) = &draw.library {
+        v.push(label_text_span(">> Library", Span::raw(l.as_str())).style(style_line()));
+    }
+    if draw.upgrade {
+        v.push(label_text_span(">> Upgrade", Span::raw("owned mark")).style(style_line()));
+    }
+    for group in &draw.tags {
+        for (c, tag) in group.iter().enumerate() {
+            let label = if c == 0 { ">> Tag" } else { ">> or Tag" };
+            v.push(label_text_span(label, Span::raw(tag.as_str())).style(style_line()));
+        }
+    }
+    if let Some(f) = &draw.filter {
+        v.push(label_text_span(">> Filter", Span::raw(f.to_string())).style(style_line()));
+    }
+    v
+}
+
+pub struct MarkList {
+    state: TableState,
+    n_items: usize,
+    /// Rows of marks the table had when it was last drawn.
+    viewport: usize,
+    /// Shows the marks under a header per category.
+    grouped: bool,
+    collapsed: BTreeSet<String>,
+    sort: Option<SortColumn>,
+    descending: bool,
+    /// The columns shown after the name, in order.
+    columns: BTreeSet<Column>,
+    /// Library indices of the marks picked for a bulk action.
+    marked: BTreeSet<usize>,
+    /// The row visual mode started on, it marks every row up to the cursor.
+    visual: Option<usize>,
+    /// Guards the keys that change the availability of many marks at once.
+    confirm: Confirm,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Column {
+    Power,
+    Category,
+    Tags,
+    /// The library the mark was loaded from.
+    Library,
+    /// A column of the library csv the app doesn't know about.
+    Extra(String),
+}
+
+impl Column {
+    /// Every column that can be shown for `lib`.
+    fn all(lib: &Library) -> Vec<Column> {
+        let mut columns = vec![
+            Column::Power,
+            Column::Category,
+            Column::Tags,
+            Column::Library,
+        ];
+        columns.extend(lib.extra_columns().into_iter().map(Column::Extra));
+        columns
+    }
+
+    fn title(&self) -> &str {
+        match self {
+            Column::Power => "Power",
+            Column::Category => "Category",
+            Column::Tags => "Tags",
+            Column::Library => "Library",
+            Column::Extra(name) => name,
+        }
+    }
+
+    fn cell<'a>(&self, mark: &'a Mark, lib: &Library) -> Span<'a> {
+        match self {
+            Column::Power => power_str(&mark.power, &lib.tiers),
+            Column::Category => Span::raw(mark.category.as_str()),
+            Column::Tags => Span::raw(
+                mark.tags
+                    .iter()
+                    .map(|s| s.as_str())
+                    .intersperse(", ")
+                    .collect::<String>(),
+            ),
+            Column::Library => Span::raw(mark.library()),
+            Column::Extra(name) => Span::raw(mark.extra.get(name).map_or("", String::as_str)),
+        }
+    }
+
+    fn width(&self, lib: &Library) -> u16 {
+        let longest = match self {
+            Column::Power => 8,
+            Column::Category => {
+                cmp::max(lib.categories.iter().map(|c| c.len()).max().unwrap_or(0), 8)
+            }
+            Column::Tags => lib
+                .list
+                .iter()
+                .map(|(m, _)| m.tags.iter().map(|s| s.len()).intersperse(2).sum::<usize>())
+                .max()
+                .unwrap_or(0),
+            Column::Library => lib
+                .libraries()
+                .iter()
+                .map(|l| l.len())
+                .chain(["Library".len()])
+                .max()
+                .unwrap_or(0),
+            Column::Extra(name) => lib
+                .list
+                .iter()
+                .filter_map(|(m, _)| m.extra.get(name))
+                .map(|v| v.len())
+                .chain([name.len()])
+                .max()
+                .unwrap_or(0),
+        };
+        longest as u16
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SortColumn {
+    Name,
+    Power,
+    Category,
+    Availability,
+}
+
+impl SortColumn {
+    fn name(self) -> &'static str {
+        match self {
+            SortColumn::Name => "name",
+            SortColumn::Power => "power",
+            SortColumn::Category => "category",
+            SortColumn::Availability => "availability",
+        }
+    }
+
+    /// The next column to sort by, `None` being the library's own order.
+    fn next(sort: Option<SortColumn>) -> Option<SortColumn> {
+        match sort {
+            None => Some(SortColumn::Name),
+            Some(SortColumn::Name) => Some(SortColumn::Power),
+            Some(SortColumn::Power) => Some(SortColumn::Category),
+            Some(SortColumn::Category) => Some(SortColumn::Availability),
+            Some(SortColumn::Availability) => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ListRow {
+    Category(String),
+    Mark(usize),
+}
+
+impl MarkList {
+    pub fn new(lib: &Library) -> Self {
+        let mut columns = BTreeSet::from([Column::Power, Column::Category, Column::Tags]);
+        // a single library's name in every row says nothing
+        if lib.libraries().len() > 1 {
+            columns.insert(Column::Library);
+        }
+        Self {
+            state: TableState::default(),
+            n_items: lib.list.len(),
+            viewport: 0,
+            grouped: false,
+            collapsed: BTreeSet::new(),
+            sort: None,
+            descending: false,
+            columns,
+            marked: BTreeSet::new(),
+            visual: None,
+            confirm: Confirm::default(),
+        }
+    }
+
+    /// Forgets what refers to marks by index, for when the library was
+    /// replaced.
+    fn reset(&mut self, lib: &Library) {
+        self.marked.clear();
+        self.visual = None;
+        self.confirm.reset();
+        self.n_items = self.rows(lib).len();
+        if let Some(i) = self.state.selected() {
+            self.state
+                .select(Some(cmp::min(i, self.n_items.saturating_sub(1))));
+        }
+    }
+
+    /// The marks picked with Space or visual mode.
+    fn picked(&self, lib: &Library) -> BTreeSet<usize> {
+        let mut picked = self.marked.clone();
+        if let (Some(anchor), Some(cursor)) = (self.visual, self.state.selected()) {
+            let rows = self.rows(lib);
+            let (start, end) = (cmp::min(anchor, cursor), cmp::max(anchor, cursor));
+            // the rows can shrink under the anchor, e.g. when a group collapses
+            let range = rows.iter().take(end + 1).skip(start);
+            picked.extend(range.filter_map(|r| match r {
+                ListRow::Mark(i) => Some(*i),
+                ListRow::Category(_) => None,
+            }));
+        }
+        picked
+    }
+
+    /// Library indices of the marks in the order they are listed.
+    fn sorted(&self, lib: &Library) -> Vec<usize> {
+        let mut marks: Vec<usize> = (0..lib.list.len()).collect();
+        let Some(sort) = self.sort else {
+            return marks;
+        };
+
+        marks.sort_by(|&a, &b| {
+            let ((a, a_free), (b, b_free)) = (&lib.list[a], &lib.list[b]);
+            let ord = match sort {
+                SortColumn::Name => a.name.cmp(&b.name),
+                SortColumn::Power => lib.tiers.rank(&a.power).cmp(&lib.tiers.rank(&b.power)),
+                SortColumn::Category => a.category.cmp(&b.category),
+                // free marks first
+                SortColumn::Availability => b_free.cmp(a_free),
+            };
+            if self.descending {
+                ord.reverse()
+            } else {
+                ord
+            }
+        });
+        marks
+    }
+
+    /// The rows of the table, marks are referred to by their index in the
+    /// library.
+    fn rows(&self, lib: &Library) -> Vec<ListRow> {
+        let sorted = self.sorted(lib);
+        if !self.grouped {
+            return sorted.into_iter().map(ListRow::Mark).collect();
+        }
+
+        // marks without a category go under a header of their own at the end
+        let mut categories: Vec<_> = lib.categories.iter().map(String::as_str).collect();
+        if lib.list.iter().any(|(m, _)| m.category.is_empty()) {
+            categories.push("");
+        }
+
+        let mut rows = Vec::new();
+        for category in categories {
+            rows.push(ListRow::Category(category.to_string()));
+            if !self.collapsed.contains(category) {
+                rows.extend(
+                    sorted
+                        .iter()
+                        .filter(|&&i| lib.same_label(&lib.list[i].0.category, category))
+                        .map(|&i| ListRow::Mark(i)),
+                );
+            }
+        }
+        rows
+    }
+
+    fn selected_row(&self, lib: &Library) -> Option<ListRow> {
+        self.rows(lib).get(self.state.selected()?).cloned()
+    }
+
+    /// Moves the selection onto `row`, if it is shown.
+    fn select_row(&mut self, lib: &Library, row: &ListRow) {
+        if let Some(i) = self.rows(lib).iter().position(|r| r == row) {
+            self.state.select(Some(i));
+        }
+    }
+
+    /// Selects the mark named `name`, opening its category if it is collapsed.
+    fn show_mark(&mut self, lib: &Library, name: &str) {
+        let Some(i) = lib.list.iter().position(|(m, _)| m.name == name) else {
+            return;
+        };
+        self.collapsed.remove(lib.category_of(&lib.list[i].0));
+        self.n_items = self.rows(lib).len();
+        self.select_row(lib, &ListRow::Mark(i));
+    }
+
+    /// Keeps a renamed category collapsed, `lib` has the new name already.
+    fn rename_category(&mut self, lib: &Library, from: &str, to: &str) {
+        let had = self.collapsed.len();
+        self.collapsed.retain(|c| !lib.same_label(c, from));
+        if self.collapsed.len() < had {
+            self.collapsed.insert(to.to_string());
+        }
+        self.n_items = self.rows(lib).len();
+        if let Some(i) = self.state.selected() {
+            self.state
+                .select(Some(cmp::min(i, self.n_items.saturating_sub(1))));
+        }
+    }
+
+    /// Collapses or expands the category of the selected row.
+    fn set_collapsed(&mut self, lib: &Library, collapse: bool) {
+        let category = match self.selected_row(lib) {
+            Some(ListRow::Category(c)) => c,
+            Some(ListRow::Mark(i)) => lib.category_of(&lib.list[i].0).to_string(),
+            None => return,
+        };
+        if collapse {
+            self.collapsed.insert(category.clone());
+        } else {
+            self.collapsed.remove(&category);
+        }
+        self.n_items = self.rows(lib).len();
+        self.select_row(lib, &ListRow::Category(category));
+    }
+
+    /// Returns whether it freed or used up any marks.
+    pub fn input(&mut self, lib: &mut Library, code: KeyCode) -> bool {
+        self.n_items = self.rows(lib).len();
+        let last = self.n_items.saturating_sub(1);
+        let page = cmp::max(self.viewport, 1);
+        let selected = self.state.selected().unwrap_or(0);
+        if !matches!(
+            code,
+            KeyCode::Enter | KeyCode::Char('f' | 'F' | 'u' | 'U' | 'x' | 'X')
+        ) {
+            self.confirm.reset();
+        }
+        match code {
+            KeyCode::Up => self.prev_mark(),
+            KeyCode::Down => self.next_mark(),
+            KeyCode::PageUp => self.state.select(Some(selected.saturating_sub(page))),
+            KeyCode::PageDown => self.state.select(Some(cmp::min(selected + page, last))),
+            KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Char('g' | 'G') => {
+                let row = self.selected_row(lib);
+                self.grouped = !self.grouped;
+                self.n_items = self.rows(lib).len();
+                match row {
+                    Some(row @ ListRow::Mark(_)) => self.select_row(lib, &row),
+                    _ => self.state.select(Some(0)),
+                }
+            }
+            // o cycles the column to sort by, O flips the direction
+            KeyCode::Char(c @ ('o' | 'O')) => {
+                let row = self.selected_row(lib);
+                if c == 'O' {
+                    self.descending = !self.descending;
+                } else {
+                    self.sort = SortColumn::next(self.sort);
+                }
+                if let Some(row) = row {
+                    self.select_row(lib, &row);
+                }
+            }
+            KeyCode::Left if self.grouped => self.set_collapsed(lib, true),
+            KeyCode::Right if self.grouped => self.set_collapsed(lib, false),
+            KeyCode::Char(' ') => {
+                if let Some(ListRow::Mark(i)) = self.selected_row(lib) {
+                    if !self.marked.remove(&i) {
+                        self.marked.insert(i);
+                    }
+                }
+            }
+            KeyCode::Char('m' | 'M') => match self.visual {
+                Some(_) => {
+                    self.marked = self.picked(lib);
+                    self.visual = None;
+                }
+                None => self.visual = self.state.selected(),
+            },
+            KeyCode::Char('c' | 'C') => {
+                self.marked.clear();
+                self.visual = None;
+            }
+            // free the picked marks, or every mark if none are picked
+            KeyCode::Char('f' | 'F') => {
+                if !self.confirm.confirm(code) {
+                    return false;
+                }
+                let picked = self.picked(lib);
+                for (i, (_, free)) in lib.list.iter_mut().enumerate() {
+                    if picked.is_empty() || picked.contains(&i) {
+                        *free = true;
+                    }
+                }
+                self.marked.clear();
+                self.visual = None;
+                return true;
+            }
+            KeyCode::Char('u' | 'U') => {
+                let category = match self.selected_row(lib) {
+                    Some(ListRow::Category(c)) => c,
+                    Some(ListRow::Mark(i)) => lib.list[i].0.category.clone(),
+                    None => return false,
+                };
+                if !self.confirm.confirm(code) {
+                    return false;
+                }
+                let mut list = std::mem::take(&mut lib.list);
+                for (mark, free) in &mut list {
+                    if lib.same_label(&mark.category, &category) {
+                        *free = false;
+                    }
+                }
+                lib.list = list;
+                return true;
+            }
+            KeyCode::Char('x' | 'X') if self.confirm.confirm(code) => {
+                for (_, free) in &mut lib.list {
+                    *free = !*free;
+                }
+                self.marked.clear();
+                self.visual = None;
+                return true;
+            }
+            KeyCode::Enter if !self.marked.is_empty() || self.visual.is_some() => {
+                if !self.confirm.confirm(code) {
+                    return false;
+                }
+                for i in self.picked(lib) {
+                    lib.list[i].1 = !lib.list[i].1;
+                }
+                self.marked.clear();
+                self.visual = None;
+                return true;
+            }
+            KeyCode::Enter => match self.selected_row(lib) {
+                Some(ListRow::Mark(i)) => {
+                    lib.list[i].1 = !lib.list[i].1;
+                    return true;
+                }
+                Some(ListRow::Category(c)) => {
+                    let collapse = !self.collapsed.contains(&c);
+                    self.set_collapsed(lib, collapse)
+                }
+                None => {}
+            },
+            _ => {}
+        }
+        false
+    }
+
+    /// Handles a key of the popup for picking the shown columns.
+    fn choose_columns(
+        &mut self,
+        lib: &Library,
+        state: &mut ListState,
+        key: KeyCode,
+    ) -> ControlFlow<()> {
+        let all = Column::all(lib);
+        let selected = state.selected().unwrap_or(0);
+        match key {
+            KeyCode::Esc | KeyCode::Char('v' | 'V') => return BREAK,
+            KeyCode::Up => state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => state.select(Some(cmp::min(selected + 1, all.len() - 1))),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let column = &all[selected];
+                if !self.columns.remove(column) {
+                    self.columns.insert(column.clone());
+                }
+            }
+            _ => {}
+        }
+        CONT
+    }
+
+    fn draw_column_chooser(&self, lib: &Library, state: &mut ListState, f: &mut Frame) {
+        let all = Column::all(lib);
+        let area = centered_rect(f.size(), 36, all.len() as u16 + 2);
+        f.render_widget(Clear, area);
+
+        let list = List::new(all.iter().map(|c| {
+            let check = if self.columns.contains(c) {
+                "[x] "
+            } else {
+                "[ ] "
+            };
+            Line::raw(format!("{check}{}", c.title()))
+        }))
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(Span::styled("Columns", theme::current().title))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::raw("Space to toggle, Esc to close").centered()),
+        )
+        .highlight_style(theme::current().highlight);
+        f.render_stateful_widget(list, area, state);
+    }
+
+    pub fn selected_mark<'a>(&self, library: &'a Library) -> Option<&'a Mark> {
+        match self.selected_row(library)? {
+            ListRow::Mark(i) => library.list.get(i).map(|(m, _)| m),
+            ListRow::Category(_) => None,
+        }
+    }
+
+    pub fn draw(&mut self, library: &Library, options: &DraftOptions, f: &mut Frame, area: Rect) {
+        let theme = theme::current();
         let layout = Layout::new(
-            Direction::Horizontal,
-            [
-                Constraint::Length(15),
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-            ],
+            Direction::Vertical,
+            [Constraint::Percentage(60), Constraint::Percentage(40)],
         )
-        .split(rect);
-        let draft_list = List::new(
-            self.results
+        .spacing(1)
+        .split(area);
+
+        let longest_name = library
+            .list
+            .iter()
+            .map(|(m, _)| m.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Name".len());
+
+        let mut widths = vec![
+            Constraint::Length(1),
+            Constraint::Length(longest_name as u16),
+            Constraint::Length(1),
+        ];
+        widths.extend(
+            self.columns
                 .iter()
-                .enumerate()
-                .map(|(c, _)| format!("Draft #{c}")),
+                .map(|c| Constraint::Length(c.width(library))),
+        );
+
+        self.n_items = self.rows(library).len();
+        let wishlist = library.wishlist(options);
+        let picked = self.picked(library);
+        let mark_table = Table::new(
+            self.rows(library)
+                .into_iter()
+                .map(|row| {
+                    let i = match row {
+                        ListRow::Mark(i) => i,
+                        ListRow::Category(c) => {
+                            let marks = library
+                                .list
+                                .iter()
+                                .filter(|(m, _)| library.same_label(&m.category, &c));
+                            let free = marks.clone().filter(|(_, free)| *free).count();
+                            let used = marks.count() - free;
+                            let arrow = if self.collapsed.contains(&c) {
+                                "▸"
+                            } else {
+                                "▾"
+                            };
+                            let name = if c.is_empty() {
+                                "(none)".to_string()
+                            } else {
+                                c
+                            };
+                            let mut cells = vec![Span::raw(arrow), name.bold(), Span::raw("")];
+                            cells.extend(self.columns.iter().map(|_| Span::raw("")));
+                            // the counts go in the last column, which is usually the widest
+                            *cells.last_mut().unwrap() =
+                                Span::styled(format!("{free} free, {used} used"), theme.muted);
+                            return Row::new(cells);
+                        }
+                    };
+                    let (mark, free) = &library.list[i];
+                    let mut cells = vec![
+                        if options.pinned.contains(&mark.name) {
+                            Span::styled("+", theme.success)
+                        } else if options.banned.contains(&mark.name) {
+                            Span::styled("x", theme.error)
+                        } else if wishlist.is_some_and(|w| w.contains(&mark.name)) {
+                            Span::styled("★", theme.pinned)
+                        } else {
+                            Span::raw("")
+                        },
+                        Span::styled(
+                            mark.name.as_str(),
+                            if !*free {
+                                Style::default().crossed_out()
+                            } else {
+                                Style::default()
+                            },
+                        ),
+                        if mark.repeatable {
+                            "∞".cyan()
+                        } else {
+                            Span::raw("")
+                        },
+                    ];
+                    cells.extend(self.columns.iter().map(|c| c.cell(mark, library)));
+                    if picked.contains(&i) {
+                        Row::new(cells).on_dark_gray()
+                    } else {
+                        Row::new(cells)
+                    }
+                })
+                .collect::<Vec<_>>(),
+            widths,
+        )
+        .header(Row::new(
+            ["".into(), "Name".underlined(), "".into()]
+                .into_iter()
+                .chain(
+                    self.columns
+                        .iter()
+                        .map(|c| c.title().to_string().underlined()),
+                ),
+        ))
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_style(theme.highlight)
+        .highlight_symbol(">>");
+
+        let selected_mark = match self.selected_row(library) {
+            Some(ListRow::Mark(i)) => &library.list[i].0,
+            Some(ListRow::Category(c)) => {
+                let marks = library
+                    .list
+                    .iter()
+                    .filter(|(m, _)| library.same_label(&m.category, &c));
+                let free = marks.clone().filter(|(_, free)| *free).count();
+                let summary = Paragraph::new(vec![
+                    label_text_span("Marks", Span::raw(marks.count().to_string())),
+                    label_text_span("Free", Span::raw(free.to_string())),
+                ])
+                .block(
+                    Block::bordered()
+                        .title(c.bold())
+                        .border_type(BorderType::Rounded),
+                );
+                self.draw_table(mark_table, f, layout[0]);
+                return f.render_widget(summary, layout[1]);
+            }
+            None => {
+                let message = if library.list.is_empty() {
+                    "The library has no marks, start the app with a library csv or toml file"
+                } else {
+                    "No mark selected"
+                };
+                let placeholder = Paragraph::new(Span::styled(message, theme.muted).italic())
+                    .centered()
+                    .wrap(Wrap { trim: true })
+                    .block(Block::bordered().border_type(BorderType::Rounded));
+                self.draw_table(mark_table, f, layout[0]);
+                return f.render_widget(placeholder, layout[1]);
+            }
+        };
+
+        let description_box = mark_description(selected_mark, library);
+        self.draw_table(mark_table, f, layout[0]);
+        f.render_widget(description_box, layout[1])
+    }
+
+    fn draw_table(&mut self, mark_table: Table, f: &mut Frame, area: Rect) {
+        let table_layout =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).split(area);
+        let table_area = table_layout[0];
+
+        // keep the selection in view, the header takes the first row
+        self.viewport = (table_area.height as usize).saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        let offset = self.state.offset_mut();
+        if selected < *offset {
+            *offset = selected;
+        } else if selected >= *offset + self.viewport {
+            *offset = selected + 1 - cmp::max(self.viewport, 1);
+        }
+        *offset = cmp::min(*offset, self.n_items.saturating_sub(self.viewport));
+
+        f.render_stateful_widget(mark_table, table_area, &mut self.state);
+        if self.n_items > self.viewport {
+            let mut scrollbar = ScrollbarState::new(self.n_items).position(selected);
+            f.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                table_area.inner(&Margin::new(0, 1)),
+                &mut scrollbar,
+            );
+        }
+        let mut footer = match self.n_items {
+            0 => "no marks".to_string(),
+            n => format!("{} of {n}", selected + 1),
+        };
+        if self.visual.is_some() {
+            footer = format!("-- VISUAL --  {footer}");
+        } else if !self.marked.is_empty() {
+            footer = format!("{} picked  {footer}", self.marked.len());
+        }
+        if let Some(sort) = self.sort {
+            let dir = if self.descending { "▼" } else { "▲" };
+            footer = format!("sorted by {} {dir}  {footer}", sort.name());
+        }
+        f.render_widget(
+            Line::from(Span::styled(footer, theme::current().muted)).right_aligned(),
+            table_layout[1],
+        );
+    }
+
+    fn next_mark(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.n_items - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn prev_mark(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.n_items - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+}
+
+fn show_mark_detail_popup(f: &mut Frame, mark: &Mark, lib: &Library) {
+    let area = centered_rect(f.size(), 64, cmp::min(24, f.size().height));
+    f.render_widget(Clear, area);
+    f.render_widget(mark_description(mark, lib), area);
+}
+
+/// A mark's power, category, tags and other details followed by its
+/// description.
+fn mark_description<'a>(mark: &'a Mark, library: &'a Library) -> Paragraph<'a> {
+    let tag_text: String = mark
+        .tags
+        .iter()
+        .map(String::as_str)
+        .intersperse(", ")
+        .collect();
+
+    let mut lines = vec![
+        label_text_span("Power", power_str(&mark.power, &library.tiers)),
+        label_text_span("Category", mark.category.as_str().reset()),
+        label_text_span("Tags", tag_text.reset()),
+    ];
+    if mark.weight != 1.0 {
+        lines.push(label_text_span("Weight", mark.weight.to_string().reset()));
+    }
+    if mark.repeatable {
+        lines.push(label_text_span("Repeatable", "yes".reset()));
+    }
+    if let Some(upgrade) = &mark.upgrade {
+        lines.push(label_text_span("Upgrades to", upgrade.as_str().reset()));
+    }
+    for (label, names) in [("Excludes", &mark.excludes), ("Requires", &mark.requires)] {
+        if !names.is_empty() {
+            let names: String = names.iter().map(String::as_str).intersperse(", ").collect();
+            lines.push(label_text_span(label, names.reset()));
+        }
+    }
+    for (column, value) in &mark.extra {
+        lines.push(label_text_span(column, value.as_str().reset()));
+    }
+    let upgrade_of: String = library
+        .list
+        .iter()
+        .filter(|(m, _)| m.upgrade.as_ref() == Some(&mark.name))
+        .map(|(m, _)| m.name.as_str())
+        .intersperse(", ")
+        .collect();
+    if !upgrade_of.is_empty() {
+        lines.push(label_text_span("Upgrade of", upgrade_of.reset()));
+    }
+
+    lines.push(Line::styled(
+        "Description",
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .add_modifier(Modifier::UNDERLINED),
+    ));
+
+    let mut text = Text::from(lines);
+    text.extend(Text::raw(mark.description.as_str()));
+
+    Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(mark.name.clone().bold())
+                .borders(Borders::all())
+                .border_type(BorderType::Rounded),
         )
-        .block(Block::bordered().border_type(BorderType::Rounded))
-        .highlight_symbol(">>")
-        .highlight_spacing(HighlightSpacing::Always);
+        .wrap(Wrap { trim: true })
+}
 
-        if draft_list.is_empty() {
-            f.render_widget(
-                Paragraph::new("<empty>".italic().dark_gray())
-                    .block(Block::bordered().border_type(BorderType::Rounded))
-                    .centered(),
-                layout[0],
-            );
-            f.render_widget(
-                Block::bordered().border_type(BorderType::Rounded),
-                layout[1],
-            );
-        } else {
-            f.render_stateful_widget(draft_list, layout[0], &mut self.state);
-            let (mark_list, draws) = match self.state.selected() {
-                Some(i) => self.results[i].clone(),
-                None => (vec![], vec![]),
+fn label_text_span<'a>(label: &'a str, text: Span<'a>) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(label, Style::default().add_modifier(Modifier::BOLD)),
+        Span::styled(": ", Style::default().add_modifier(Modifier::BOLD)),
+        text,
+    ])
+}
+
+pub fn power_str(p: &Power, tiers: &PowerTiers) -> Span<'static> {
+    let span = Span::raw(p.name().to_string());
+    let Some(tier) = tiers.get(p) else {
+        return span;
+    };
+
+    let theme = theme::current();
+    if theme.monochrome {
+        return span;
+    }
+    if let Some(ramp) = &theme.power_ramp {
+        let rank = tiers.iter().position(|t| t == tier).unwrap_or(0);
+        return span.fg(ramp[cmp::min(rank, ramp.len() - 1)]);
+    }
+
+    // colors are checked when the tiers are loaded
+    let color = |c: &str| c.parse::<Color>().unwrap_or_default();
+    let span = span.fg(color(&tier.color));
+    match &tier.background {
+        Some(bg) => span.bg(color(bg)).bold(),
+        None => span,
+    }
+}
+
+/// How long a status bar message stays up.
+const STATUS_DURATION: Duration = Duration::from_secs(3);
+/// Ticks between the unsaved changes indicator blinking on and off.
+const BLINK_TICKS: u64 = 2;
+/// How often, in ticks, the library files are checked for changes.
+const WATCH_TICKS: u64 = 4;
+
+const CONFIRM_MIN_GAP: Duration = Duration::from_millis(150);
+const CONFIRM_WINDOW: Duration = Duration::from_secs(2);
+
+/// Guards a destructive key behind a deliberate second press. Presses that
+/// arrive faster than a human double-press (key repeat from a held key, or a
+/// burst of buffered input over a laggy connection) only keep it armed.
+#[derive(Clone, Debug, Default)]
+struct Confirm {
+    armed: Option<(KeyCode, Instant)>,
+}
+
+impl Confirm {
+    fn confirm(&mut self, key: KeyCode) -> bool {
+        let now = Instant::now();
+        match self.armed {
+            Some((k, at))
+                if k == key && (CONFIRM_MIN_GAP..=CONFIRM_WINDOW).contains(&(now - at)) =>
+            {
+                self.armed = None;
+                true
+            }
+            _ => {
+                self.armed = Some((key, now));
+                false
+            }
+        }
+    }
+
+    fn is_armed(&self) -> bool {
+        self.armed
+            .is_some_and(|(_, at)| at.elapsed() <= CONFIRM_WINDOW)
+    }
+
+    fn reset(&mut self) {
+        self.armed = None;
+    }
+}
+
+/// What a yes/no question is asked for.
+#[derive(Clone, Debug)]
+enum Pending {
+    UndoDraft,
+    DeleteDraw,
+    Overwrite(PathBuf),
+    Export(PathBuf, export::Format),
+    Quit,
+    Open,
+    Reload,
+    Post(usize),
+}
+
+/// A modal yes/no question, Y and N answer directly and Left/Right pick
+/// before Enter. It starts on No so an accidental Enter is harmless.
+#[derive(Clone, Debug)]
+struct YesNo {
+    question: String,
+    yes: bool,
+}
+
+impl YesNo {
+    fn new(question: String) -> Self {
+        YesNo {
+            question,
+            yes: false,
+        }
+    }
+
+    fn input(&mut self, ev: KeyEvent) -> ControlFlow<bool> {
+        match ev.code {
+            KeyCode::Char('y' | 'Y') => return ControlFlow::Break(true),
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => return ControlFlow::Break(false),
+            KeyCode::Enter => return ControlFlow::Break(self.yes),
+            KeyCode::Left | KeyCode::Right | KeyCode::Tab => self.yes = !self.yes,
+            _ => {}
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn draw(&self, f: &mut Frame) {
+        let width = cmp::max(self.question.len() as u16 + 4, 24);
+        let area = centered_rect(f.size(), cmp::min(width, f.size().width), 6);
+
+        let button = |label: &'static str, selected| {
+            if selected {
+                Span::raw(label).reversed()
+            } else {
+                Span::raw(label)
+            }
+        };
+        let text = vec![
+            Line::raw(self.question.as_str()),
+            Line::default(),
+            Line::default().spans([
+                button(" Yes ", self.yes),
+                Span::raw("   "),
+                button(" No ", !self.yes),
+            ]),
+        ];
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(text)
+                .centered()
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .title(Span::styled("Are you sure?", theme::current().title))
+                        .title_alignment(Alignment::Center),
+                ),
+            area,
+        );
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Prompt<'a> {
+    pub text: String,
+    pub title: Line<'a>,
+    pub prefix: Span<'a>,
+    pub postfix: Span<'a>,
+    pub cursor_pos: usize,
+    pub max_width: usize,
+    /// Shown under the text, e.g. a hint or why the text is not accepted.
+    pub footer: Line<'a>,
+    /// Suggests what the text could be completed to when Tab is pressed.
+    pub completer: Option<fn(&str) -> Vec<String>>,
+    /// The completions Tab cycles through when they share no longer prefix.
+    cycle: Option<(Vec<String>, usize)>,
+}
+
+impl<'a> Prompt<'a> {
+    fn input(&mut self, ev: KeyEvent) -> ControlFlow<bool> {
+        if ev.code != KeyCode::Tab {
+            self.cycle = None;
+        }
+
+        match ev.code {
+            KeyCode::Esc => return ControlFlow::Break(false),
+            KeyCode::Enter => return ControlFlow::Break(true),
+            KeyCode::Tab => self.complete(),
+            KeyCode::Char(c) if !c.is_control() => {
+                self.text.insert(self.cursor_pos, c);
+                self.cursor_pos += c.len_utf8();
+            }
+            KeyCode::Backspace => {
+                let start = self.prev_boundary();
+                self.text.replace_range(start..self.cursor_pos, "");
+                self.cursor_pos = start;
+            }
+            KeyCode::Delete => {
+                let end = self.next_boundary();
+                self.text.replace_range(self.cursor_pos..end, "");
+            }
+            KeyCode::Right => self.cursor_pos = self.next_boundary(),
+            KeyCode::Left => self.cursor_pos = self.prev_boundary(),
+            KeyCode::Home => self.cursor_pos = 0,
+            KeyCode::End => self.cursor_pos = self.text.len(),
+            _ => {}
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Inserts text at the cursor, leaving out line breaks and other control
+    /// characters a single line has no use for.
+    fn paste(&mut self, text: &str) {
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        self.text.insert_str(self.cursor_pos, &text);
+        self.cursor_pos += text.len();
+    }
+
+    /// The byte offset of the grapheme before the cursor, so accented
+    /// letters and the like are stepped over as one character.
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor_pos]
+            .graphemes(true)
+            .next_back()
+            .map_or(0, |g| self.cursor_pos - g.len())
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor_pos..]
+            .graphemes(true)
+            .next()
+            .map_or(self.text.len(), |g| self.cursor_pos + g.len())
+    }
+
+    /// Completes the text as far as all completions agree, after that
+    /// further presses go through the completions one by one.
+    fn complete(&mut self) {
+        if let Some((completions, i)) = &mut self.cycle {
+            *i = (*i + 1) % completions.len();
+            self.text = completions[*i].clone();
+        } else if let Some(completer) = self.completer {
+            let completions = completer(&self.text);
+            let Some(first) = completions.first() else {
+                return;
             };
+            let common = completions.iter().fold(first.as_str(), |common, c| {
+                let len = common
+                    .char_indices()
+                    .zip(c.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map_or(0, |((i, a), _)| i + a.len_utf8());
+                &common[..len]
+            });
 
-            let listing = List::new(mark_list.iter().map(|m| {
-                let power_span = power_str(m.power);
-                m.name.as_str().set_style(power_span.style)
-            }))
-            .block(
-                Block::bordered()
-                    .border_type(BorderType::Rounded)
-                    .padding(Padding {
-                        left: 4,
-                        top: 1,
-                        ..Default::default()
-                    }),
+            if common.len() > self.text.len() || completions.len() == 1 {
+                self.text = common.to_string();
+            } else {
+                self.text = first.clone();
+                self.cycle = Some((completions, 0));
+            }
+        }
+        self.cursor_pos = self.text.len();
+    }
+
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        let layout = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+        let area = layout[1];
+
+        // pad by display width, wide characters take up two cells
+        let padding = "_".repeat(self.max_width.saturating_sub(self.text.width()));
+        let par_text = Line::default().spans([
+            self.prefix.clone(),
+            Span::raw(format!("{}{padding}", self.text)),
+            self.postfix.clone(),
+        ]);
+
+        let text_width = par_text.width();
+        let width = cmp::max(text_width, self.footer.width()) + 4;
+
+        let layout = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(width as u16),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+        let area = layout[1];
+
+        let text = Text::from(par_text);
+
+        // left side + border + pad + centering + prefix + text before the cursor
+        let cursor_x = area.x
+            + 2
+            + ((width - 4 - text_width) / 2) as u16
+            + self.prefix.content.width() as u16
+            + self.text[..self.cursor_pos].width() as u16;
+        let cursor_y = area.y + 1;
+
+        f.set_cursor(cursor_x, cursor_y);
+
+        let par = Paragraph::new(text).centered().block(
+            Block::bordered()
+                .title(self.title.clone())
+                .title_bottom(self.footer.clone()),
+        );
+
+        f.render_widget(Clear, area);
+        f.render_widget(par, area);
+    }
+}
+
+#[derive(Default)]
+pub struct SnakeView {
+    draft: Option<SnakeDraft>,
+    state: ListState,
+    message: Option<&'static str>,
+    confirm: Confirm,
+}
+
+impl SnakeView {
+    fn draw(&mut self, lib: &Library, editor: &DraftEditor, f: &mut Frame, rect: Rect) {
+        let Some(draft) = &self.draft else {
+            let players: String = lib
+                .players
+                .iter()
+                .map(|p| p.name.as_str())
+                .intersperse(", ")
+                .collect();
+            let mut lines = vec![
+                label_text_span("Players", Span::raw(players)),
+                label_text_span(
+                    "Picks per player",
+                    Span::raw(editor.draws.len().to_string()),
+                ),
+                Line::default(),
+                Line::raw("Enter rolls a shared pool from the Draft tab's draws,"),
+                Line::raw("O edits the players."),
+            ];
+            if let Some(message) = self.message {
+                lines.extend([
+                    Line::default(),
+                    Line::from(Span::styled(message, theme::current().error)),
+                ]);
+            }
+            f.render_widget(
+                Paragraph::new(lines).block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .title("Snake draft")
+                        .padding(Padding::uniform(1)),
+                ),
+                rect,
             );
+            return;
+        };
 
-            let editor = DraftEditor {
-                draws,
-                line: 0,
-                scroll: 0,
-            };
-            let draw =
-                editor
-                    .draw()
-                    .block(
-                        Block::bordered()
-                            .border_type(BorderType::Rounded)
-                            .padding(Padding {
-                                left: 4,
-                                top: 1,
-                                ..Default::default()
-                            }),
-                    );
+        let layout = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).split(rect);
+
+        let current = draft.current_player();
+        let mut title = Line::raw(match current {
+            Some(p) => format!(
+                "Round {}/{}, {} to pick",
+                draft.round() + 1,
+                draft.rounds(),
+                draft.players[p]
+            ),
+            None => "Draft over".to_string(),
+        });
+        if self.confirm.is_armed() {
+            title.spans.push(Span::styled(
+                " (press again to abandon)",
+                theme::current().error,
+            ));
+        }
 
-            f.render_widget(listing, layout[1]);
-            f.render_widget(draw, layout[2]);
+        let pool = List::new(draft.pool.iter().map(|m| {
+            Line::from(vec![
+                Span::raw(format!("{:<24} ", m.name)),
+                power_str(&m.power, &lib.tiers),
+                Span::raw(format!(" {}", m.category)),
+            ])
+        }))
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(title),
+        )
+        .highlight_style(theme::current().highlight)
+        .highlight_symbol(">>")
+        .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(pool, layout[0], &mut self.state);
+
+        let mut lines = Vec::new();
+        for (i, (player, picks)) in draft.players.iter().zip(&draft.picks).enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+            lines.push(if current == Some(i) {
+                Line::from(vec![player.as_str().bold().reversed()])
+            } else {
+                Line::from(player.as_str().bold())
+            });
+            for mark in picks {
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    mark.name
+                        .as_str()
+                        .set_style(power_str(&mark.power, &lib.tiers).style),
+                ]));
+            }
         }
+        f.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Picks")
+                    .padding(Padding::horizontal(1)),
+            ),
+            layout[1],
+        );
     }
 }
 
-impl DraftView {
-    pub fn new(n_marks: usize) -> Self {
-        DraftView {
-            selected_tab: Pane::Left,
-            mark_list: MarkList::new(n_marks),
-            draft: DraftEditor::default(),
+/// A result drawn again from its seed, shown in a popup.
+struct Replay {
+    n: usize,
+    seed: Option<u64>,
+    available: Vec<String>,
+    /// The marks drawn again, or why the result can't be.
+    marks: Result<Vec<Mark>, &'static str>,
+    matches: bool,
+}
+
+impl Replay {
+    fn draw(&self, lib: &Library, f: &mut Frame) {
+        let theme = theme::current();
+        let mut lines = Vec::new();
+        match &self.marks {
+            Ok(marks) => {
+                if let Some(seed) = self.seed {
+                    lines.push(label_text_span("Seed", Span::raw(seed.to_string())));
+                }
+                lines.push(if self.matches {
+                    Line::from(Span::styled("Replay matches the result", theme.success))
+                } else {
+                    Line::from(Span::styled("Replay differs from the result", theme.error))
+                });
+                lines.push(Line::default());
+                for m in marks {
+                    lines.push(Line::from(vec![
+                        Span::raw(format!("{:<24} ", m.name)),
+                        power_str(&m.power, &lib.tiers),
+                    ]));
+                }
+                lines.push(Line::default());
+                let available: String = self
+                    .available
+                    .iter()
+                    .map(String::as_str)
+                    .intersperse(", ")
+                    .collect();
+                lines.push(label_text_span(
+                    "Pool",
+                    Span::raw(format!("{} marks", self.available.len())),
+                ));
+                lines.push(Line::raw(available));
+            }
+            Err(reason) => lines.push(Line::from(Span::styled(
+                format!("This result {reason} and can't be replayed"),
+                theme.muted,
+            ))),
         }
+
+        let area = centered_rect(
+            f.size(),
+            64,
+            cmp::min(lines.len() as u16 + 6, f.size().height),
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(Span::styled(
+                        format!("Replay of Draft #{}", self.n),
+                        theme.title,
+                    ))
+                    .title_alignment(Alignment::Center)
+                    .title_bottom(Line::raw("Press any key").centered())
+                    .padding(Padding::uniform(1)),
+            ),
+            area,
+        );
     }
+}
 
-    pub fn input(&mut self, lib: &mut Library, ev: KeyEvent) -> ControlFlow<()> {
-        let cont = ControlFlow::Continue(());
+/// A draft drawn without taking it, to check the draws before the real roll.
+struct DryRun {
+    preview: Preview,
+}
 
-        match ev.code {
-            KeyCode::Tab => {
-                self.selected_tab = match self.selected_tab {
-                    Pane::Left => Pane::Right,
-                    Pane::Right => Pane::Left,
-                };
-                cont
-            }
-            k if self.selected_tab == Pane::Left => {
-                self.draft.input(lib, k);
-                cont
+impl DryRun {
+    fn draw(&self, lib: &Library, f: &mut Frame) {
+        let theme = theme::current();
+        let Preview {
+            marks,
+            pools,
+            warnings,
+            ..
+        } = &self.preview;
+        let mut lines = Vec::new();
+        for (i, m) in marks.iter().enumerate() {
+            let mut line = Line::raw(format!("Draw {:<3} ", i + 1));
+            if m.name == NO_MARK {
+                line.spans.push(Span::styled(
+                    format!("{:<24} ", "(placeholder)"),
+                    theme.error,
+                ));
+            } else {
+                line.spans.push(Span::raw(format!("{:<24} ", m.name)));
+                line.spans.push(power_str(&m.power, &lib.tiers));
             }
-            k if self.selected_tab == Pane::Right => {
-                self.mark_list.input(lib, k);
-                cont
+            // the script may have drawn more or fewer marks than there are draws
+            match pools.get(i) {
+                Some(Some(n)) => line
+                    .spans
+                    .push(Span::styled(format!("  {n} in the pool"), theme.muted)),
+                Some(None) => line.spans.push(Span::styled("  pinned", theme.muted)),
+                None => {}
             }
-            _ => cont,
+            lines.push(line);
+        }
+        lines.push(Line::default());
+        if warnings.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "Nothing went wrong",
+                theme.success,
+            )));
+        }
+        for warning in warnings {
+            lines.push(Line::from(Span::styled(warning.clone(), theme.warning)));
         }
-    }
-
-    pub fn draw(&mut self, lib: &Library, f: &mut Frame, rect: Rect) {
-        let inactive_tab = Style::default().fg(Color::DarkGray);
-        let active_tab = Style::default();
-
-        let cols = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(&[Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(rect);
-
-        let left_block = Block::default()
-            .title("Draft")
-            .borders(Borders::ALL)
-            .border_style(match self.selected_tab {
-                Pane::Right => inactive_tab,
-                Pane::Left => active_tab,
-            })
-            .padding(Padding {
-                left: 3,
-                right: 0,
-                top: 1,
-                bottom: 0,
-            })
-            .border_type(BorderType::Rounded);
-        let rect = left_block.inner(cols[0]);
-        f.render_widget(left_block, cols[0]);
-
-        let mark_draft = self.draft.draw();
-        f.render_widget(mark_draft, rect);
-
-        let mark_block = Block::default()
-            .title("Marks")
-            .borders(Borders::ALL)
-            .border_style(match self.selected_tab {
-                Pane::Left => inactive_tab,
-                Pane::Right => active_tab,
-            })
-            .border_type(ratatui::widgets::BorderType::Rounded);
-        let mark_inner = mark_block.inner(cols[1]);
-        f.render_widget(mark_block, cols[1]);
 
-        self.mark_list.draw(lib, f, mark_inner);
+        let area = centered_rect(
+            f.size(),
+            72,
+            cmp::min(lines.len() as u16 + 6, f.size().height),
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(Span::styled("Dry run, nothing was drafted", theme.title))
+                    .title_alignment(Alignment::Center)
+                    .title_bottom(Line::raw("Press any key").centered())
+                    .padding(Padding::uniform(1)),
+            ),
+            area,
+        );
     }
 }
 
-#[derive(Default)]
-pub struct DraftEditor {
-    draws: Vec<Draw>,
-    line: usize,
+/// What changed between a library file and the loaded library, scrollable
+/// since a new version of a library can change most of its marks. Also shows
+/// the drafts of a player, which grow as long as the campaign.
+struct DiffView {
+    title: String,
+    lines: Vec<Line<'static>>,
     scroll: usize,
 }
 
-fn draw_lines(draw: &Draw) -> usize {
-    1 + draw.power.is_some() as usize + draw.category.is_some() as usize + draw.tags.len()
+impl DiffView {
+    fn input(&mut self, code: KeyCode) -> ControlFlow<()> {
+        let last = self.lines.len().saturating_sub(1);
+        match code {
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = cmp::min(self.scroll + 1, last),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = cmp::min(self.scroll + 10, last),
+            KeyCode::Home => self.scroll = 0,
+            KeyCode::End => self.scroll = last,
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q' | 'Q') => {
+                return ControlFlow::Break(())
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn draw(&self, f: &mut Frame) {
+        let size = f.size();
+        let area = centered_rect(
+            size,
+            cmp::min(80, size.width),
+            cmp::min(self.lines.len() as u16 + 4, size.height),
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(self.lines.clone())
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll as u16, 0))
+                .block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .title(Span::styled(self.title.as_str(), theme::current().title))
+                        .title_alignment(Alignment::Center)
+                        .padding(Padding::new(1, 1, 1, 0)),
+                ),
+            area,
+        );
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
-enum Dir {
-    Left,
-    Right,
+/// The lines of the drafts of a player in a [`DiffView`], repeats of marks
+/// that aren't repeatable first since those break the campaign's rules.
+fn player_history_lines(
+    history: &[(&Mark, Vec<usize>)],
+    drafts: usize,
+    tiers: &PowerTiers,
+) -> Vec<Line<'static>> {
+    let theme = theme::current();
+    let is_repeat = |(mark, drafts): &&(&Mark, Vec<usize>)| drafts.len() > 1 && !mark.repeatable;
+    let repeats = history.iter().filter(is_repeat).count();
+    if history.is_empty() {
+        return vec![Line::from(
+            Span::styled("<nothing drafted yet>", theme.muted).italic(),
+        )];
+    }
+
+    let mut summary = vec![Span::raw(format!(
+        "{} marks in {drafts} drafts",
+        history.len()
+    ))];
+    if repeats > 0 {
+        summary.push(Span::raw(", "));
+        summary.push(Span::styled(
+            format!("{repeats} drafted more than once"),
+            theme.error,
+        ));
+    }
+    let mut lines = vec![Line::from(summary), Line::default()];
+
+    let mark_line = |(mark, drafts): &(&Mark, Vec<usize>), repeat: bool| {
+        let drafts: String = drafts
+            .iter()
+            .map(|n| format!("#{n}"))
+            .intersperse(", ".to_string())
+            .collect();
+        let mut line = Line::from(vec![
+            if repeat {
+                Span::styled("!", theme.error)
+            } else {
+                Span::raw(" ")
+            },
+            Span::raw(" "),
+            Span::raw(mark.name.clone()).bold(),
+            Span::raw(" "),
+            power_str(&mark.power, tiers),
+            Span::raw(format!("  Draft {drafts}")),
+        ]);
+        if repeat {
+            line.spans.push(Span::styled(" (repeat)", theme.error));
+        }
+        line
+    };
+    lines.extend(history.iter().filter(is_repeat).map(|m| mark_line(m, true)));
+    lines.extend(
+        history
+            .iter()
+            .filter(|m| !is_repeat(m))
+            .map(|m| mark_line(m, false)),
+    );
+    lines
 }
 
-#[derive(Copy, Clone, Debug)]
-enum ElementKind {
-    Mark,
-    Power,
-    Category,
-    Tag(usize),
+/// The problems with the library, a mark a problem is about can be jumped to.
+struct Report {
+    problems: Vec<Problem>,
+    state: ListState,
 }
 
-impl DraftEditor {
-    pub fn input(&mut self, lib: &Library, key: KeyCode) {
-        match key {
-            KeyCode::Down => self.line = cmp::min(self.max_line().saturating_sub(1), self.line + 1),
-            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(1),
-            KeyCode::PageDown => self.scroll = cmp::min(self.scroll + 1, self.max_line()),
-            KeyCode::Up => self.line = self.line.saturating_sub(1),
-            KeyCode::Left if self.draws.len() > 0 => self.rotate_current_element(lib, Dir::Left),
-            KeyCode::Right if self.draws.len() > 0 => self.rotate_current_element(lib, Dir::Right),
-            KeyCode::Backspace | KeyCode::Char('-') if self.draws.len() > 0 => {
-                self.delete_current_element()
+impl Report {
+    fn new(problems: Vec<Problem>) -> Option<Report> {
+        (!problems.is_empty()).then(|| Report {
+            problems,
+            state: ListState::default().with_selected(Some(0)),
+        })
+    }
+
+    /// Breaks with the mark to go to when one is picked.
+    fn input(&mut self, code: KeyCode) -> ControlFlow<Option<String>> {
+        let selected = self.state.selected().unwrap_or(0);
+        let last = self.problems.len() - 1;
+        match code {
+            KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => self.state.select(Some(cmp::min(selected + 1, last))),
+            KeyCode::PageUp => self.state.select(Some(selected.saturating_sub(10))),
+            KeyCode::PageDown => self.state.select(Some(cmp::min(selected + 10, last))),
+            KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Enter => {
+                if let Some(mark) = &self.problems[selected].mark {
+                    return ControlFlow::Break(Some(mark.clone()));
+                }
             }
-            KeyCode::Char('a' | 'A' | '+') => self.add_plain_mark(),
-            KeyCode::Char('c' | 'C') if self.draws.len() > 0 => self.add_or_modify_category(lib),
-            KeyCode::Char('p' | 'P') if self.draws.len() > 0 => self.add_or_modify_power(),
-            KeyCode::Char('t' | 'T') if self.draws.len() > 0 => self.add_tag(lib),
+            KeyCode::Esc | KeyCode::Char('q' | 'Q') => return ControlFlow::Break(None),
             _ => {}
         }
+        ControlFlow::Continue(())
     }
 
-    pub fn max_line(&self) -> usize {
-        self.draws.iter().map(draw_lines).sum()
+    fn draw(&mut self, f: &mut Frame) {
+        let theme = theme::current();
+        let size = f.size();
+        let area = centered_rect(
+            size,
+            cmp::min(90, size.width),
+            cmp::min(self.problems.len() as u16 + 4, size.height),
+        );
+        let errors = self
+            .problems
+            .iter()
+            .filter(|p| p.severity == Severity::Error)
+            .count();
+        let title = format!(
+            "{errors} error(s), {} warning(s) in the library",
+            self.problems.len() - errors
+        );
+        let list = List::new(self.problems.iter().map(|p| {
+            Line::from(vec![
+                match p.severity {
+                    Severity::Error => Span::styled("error: ", theme.error),
+                    Severity::Warning => Span::styled("warning: ", theme.warning),
+                },
+                Span::raw(p.message.as_str()),
+            ])
+        }))
+        .highlight_style(theme.highlight)
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(Span::styled(title, theme.title))
+                .title_alignment(Alignment::Center)
+                .padding(Padding::new(1, 1, 1, 0)),
+        );
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut self.state);
     }
+}
 
-    pub fn add_plain_mark(&mut self) {
-        self.draws.push(Draw::default());
-    }
+/// Popup for adding and removing the players of the library.
+#[derive(Default)]
+struct Roster {
+    state: ListState,
+    name_box: Option<Prompt<'static>>,
+    confirm: Confirm,
+}
 
-    pub fn add_or_modify_power(&mut self) {
-        self.get_selected_draw().power = Some(Power::Supreme);
-    }
+impl Roster {
+    /// Continues with whether the roster changed, breaks when the popup
+    /// closes.
+    fn input(&mut self, lib: &mut Library, ev: KeyEvent) -> ControlFlow<(), bool> {
+        if let Some(name_box) = &mut self.name_box {
+            let mut added = false;
+            if let ControlFlow::Break(add) = name_box.input(ev) {
+                let name = name_box.text.trim();
+                if add && !name.is_empty() {
+                    added = lib.players.iter().all(|p| p.name != name);
+                    lib.player_mut(name);
+                    self.state
+                        .select(lib.players.iter().position(|p| p.name == name));
+                }
+                self.name_box = None;
+            }
+            return ControlFlow::Continue(added);
+        }
 
-    pub fn get_selected_draw(&mut self) -> &mut Draw {
-        self.get_selection().0
-    }
+        if !matches!(ev.code, KeyCode::Backspace | KeyCode::Char('-')) {
+            self.confirm.reset();
+        }
 
-    pub fn get_selection(&mut self) -> (&mut Draw, usize, usize) {
-        let mut cur_draw = (
-            0,
-            draw_lines(
-                self.draws
-                    .get(0)
-                    .expect("Tried to get selected draw with no draws in the draft"),
-            ),
-        );
-        let mut i = 0;
-        while !(self.line >= cur_draw.0 && self.line < cur_draw.1) {
-            i += 1;
-            let next_draw = &self.draws[i];
-            cur_draw = (cur_draw.1, cur_draw.1 + draw_lines(next_draw));
+        let len = lib.players.len();
+        match ev.code {
+            KeyCode::Esc | KeyCode::Char('o' | 'O') => return ControlFlow::Break(()),
+            KeyCode::Up if len > 0 => self.state.select(Some(
+                self.state.selected().map_or(0, |i| (i + len - 1) % len),
+            )),
+            KeyCode::Down if len > 0 => self
+                .state
+                .select(Some(self.state.selected().map_or(0, |i| (i + 1) % len))),
+            KeyCode::Char('a' | 'A' | '+') => {
+                self.name_box = Some(Prompt {
+                    title: Line::raw("Player name"),
+                    max_width: 32,
+                    ..Default::default()
+                })
+            }
+            KeyCode::Backspace | KeyCode::Char('-') => {
+                if let Some(i) = self.state.selected().filter(|&i| i < len) {
+                    if self.confirm.confirm(ev.code) {
+                        lib.players.remove(i);
+                        self.state
+                            .select(i.checked_sub(1).or((len > 1).then_some(0)));
+                        return ControlFlow::Continue(true);
+                    }
+                }
+            }
+            _ => {}
         }
 
-        (&mut self.draws[i], self.line - cur_draw.0, i)
+        ControlFlow::Continue(false)
     }
 
-    fn add_or_modify_category(&mut self, lib: &Library) {
-        self.get_selected_draw().category = Some(lib.categories.iter().nth(0).unwrap().clone());
-    }
+    fn draw(&mut self, lib: &Library, f: &mut Frame) {
+        let area = centered_rect(f.size(), 50, cmp::max(lib.players.len() as u16, 1) + 4);
+        f.render_widget(Clear, area);
 
-    fn get_element_kind(&mut self) -> ElementKind {
-        let (draw, offset, _) = self.get_selection();
-        let mut v = vec![ElementKind::Mark];
-        if draw.power.is_some() {
-            v.push(ElementKind::Power);
-        }
-        if draw.category.is_some() {
-            v.push(ElementKind::Category);
+        let title = if self.confirm.is_armed() {
+            Span::styled("Players (press again to delete)", theme::current().error)
+        } else {
+            Span::styled("Players", theme::current().title)
+        };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::raw("A add, Backspace remove, Enter drafts, Esc close").centered())
+            .padding(Padding::horizontal(1));
+
+        if lib.players.is_empty() {
+            f.render_widget(
+                Paragraph::new(Span::styled("<no players>", theme::current().muted).italic())
+                    .centered()
+                    .block(block),
+                area,
+            );
+        } else {
+            let list = List::new(lib.players.iter().map(|p| p.name.as_str()))
+                .block(block)
+                .highlight_symbol(">>")
+                .highlight_spacing(HighlightSpacing::Always);
+            f.render_stateful_widget(list, area, &mut self.state);
         }
-        for (c, _) in draw.tags.iter().enumerate() {
-            v.push(ElementKind::Tag(c));
+
+        if let Some(name_box) = &mut self.name_box {
+            name_box.draw(f, f.size());
         }
-        v[offset]
     }
+}
 
-    fn rotate_current_element(&mut self, lib: &Library, dir: Dir) {
-        let element_kind = self.get_element_kind();
-        eprintln!("{:?}", element_kind);
-        let draw = self.get_selected_draw();
+/// Popup for saving which marks are free under a name and restoring it later,
+/// e.g. the starting state of a campaign.
+#[derive(Default)]
+struct PresetList {
+    state: ListState,
+    name_box: Option<Prompt<'static>>,
+    confirm: Confirm,
+}
 
-        fn find_and_rotate<T: PartialEq>(x: &T, mut v: Vec<T>, dir: Dir) -> T {
-            while &v[0] != x {
-                v.rotate_right(1);
+impl PresetList {
+    /// Continues or breaks, when the popup closes, with whether the presets
+    /// or the library changed.
+    fn input(
+        &mut self,
+        presets: &mut BTreeMap<String, BTreeSet<String>>,
+        lib: &mut Library,
+        ev: KeyEvent,
+    ) -> ControlFlow<bool, bool> {
+        if let Some(name_box) = &mut self.name_box {
+            if let ControlFlow::Break(add) = name_box.input(ev) {
+                let name = name_box.text.trim();
+                if add && !name.is_empty() {
+                    let free = lib
+                        .list
+                        .iter()
+                        .filter(|(_, free)| *free)
+                        .map(|(m, _)| m.name.clone())
+                        .collect();
+                    presets.insert(name.to_string(), free);
+                    self.state.select(presets.keys().position(|p| p == name));
+                    self.name_box = None;
+                    return ControlFlow::Continue(true);
+                }
+                self.name_box = None;
+            }
+            return ControlFlow::Continue(false);
+        }
+
+        if !matches!(ev.code, KeyCode::Backspace | KeyCode::Char('-')) {
+            self.confirm.reset();
+        }
+
+        let len = presets.len();
+        let selected = self
+            .state
+            .selected()
+            .and_then(|i| presets.keys().nth(i))
+            .cloned();
+        match ev.code {
+            KeyCode::Esc | KeyCode::Char('a' | 'A') => return ControlFlow::Break(false),
+            KeyCode::Up if len > 0 => self.state.select(Some(
+                self.state.selected().map_or(0, |i| (i + len - 1) % len),
+            )),
+            KeyCode::Down if len > 0 => self
+                .state
+                .select(Some(self.state.selected().map_or(0, |i| (i + 1) % len))),
+            // defaults to the selected name so it is easy to update a preset
+            KeyCode::Char('n' | 'N' | '+') => {
+                let text = selected.unwrap_or_default();
+                self.name_box = Some(Prompt {
+                    title: Line::raw("Save availability as"),
+                    cursor_pos: text.len(),
+                    text,
+                    max_width: 32,
+                    ..Default::default()
+                });
             }
-            match dir {
-                Dir::Left => v.rotate_left(1),
-                Dir::Right => v.rotate_right(1),
+            KeyCode::Enter => {
+                if let Some(free) = selected.and_then(|name| presets.get(&name)) {
+                    for (mark, is_free) in &mut lib.list {
+                        *is_free = free.contains(&mark.name);
+                    }
+                    return ControlFlow::Break(true);
+                }
+            }
+            KeyCode::Backspace | KeyCode::Char('-') => {
+                if let Some(name) = selected {
+                    if self.confirm.confirm(ev.code) {
+                        presets.remove(&name);
+                        let i = self.state.selected().unwrap_or(0);
+                        self.state
+                            .select(i.checked_sub(1).or((len > 1).then_some(0)));
+                        return ControlFlow::Continue(true);
+                    }
+                }
             }
-            v.swap_remove(0)
+            _ => {}
         }
 
-        if let ElementKind::Power = element_kind {
-            let powers = [
-                Power::BadKarma,
-                Power::Poor,
-                Power::Moderate,
-                Power::Good,
-                Power::Great,
-                Power::Supreme,
-                Power::Unique,
-            ];
-            let p = draw.power.unwrap();
+        ControlFlow::Continue(false)
+    }
+
+    fn draw(&mut self, presets: &BTreeMap<String, BTreeSet<String>>, f: &mut Frame) {
+        let area = centered_rect(f.size(), 48, cmp::max(presets.len() as u16, 1) + 4);
+        f.render_widget(Clear, area);
+
+        let title = if self.confirm.is_armed() {
+            Span::styled(
+                "Availability presets (press again to delete)",
+                theme::current().error,
+            )
+        } else {
+            Span::styled("Availability presets", theme::current().title)
+        };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::raw("N save, Enter restore, Backspace remove").centered())
+            .padding(Padding::horizontal(1));
 
-            draw.power = Some(find_and_rotate(&p, powers.to_vec(), dir));
+        if presets.is_empty() {
+            f.render_widget(
+                Paragraph::new(Span::styled("<no presets>", theme::current().muted).italic())
+                    .centered()
+                    .block(block),
+                area,
+            );
+        } else {
+            let list = List::new(
+                presets
+                    .iter()
+                    .map(|(name, free)| format!("{name} ({} free)", free.len())),
+            )
+            .block(block)
+            .highlight_symbol(">>")
+            .highlight_spacing(HighlightSpacing::Always);
+            f.render_stateful_widget(list, area, &mut self.state);
         }
 
-        if let ElementKind::Category = element_kind {
-            let categories: Vec<_> = lib.categories.iter().cloned().collect();
-            let category = draw.category.as_ref().unwrap();
+        if let Some(name_box) = &mut self.name_box {
+            name_box.draw(f, f.size());
+        }
+    }
+}
+
+/// Whether the [`LabelManager`] lists the tags or the categories.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum LabelKind {
+    #[default]
+    Tag,
+    Category,
+}
+
+/// A change made in the [`LabelManager`].
+enum LabelEdit {
+    /// Renames a tag, merging it into the other one if that exists already.
+    RenameTag(String, String),
+    RenameCategory(String, String),
+    /// Deletes a category, its marks move to the other one or to none if it
+    /// is empty.
+    DeleteCategory(String, String),
+    CreateCategory(String),
+}
+
+/// What the next label picked in the [`LabelManager`] is for.
+enum LabelPick {
+    /// Merging this label into it.
+    Merge(String),
+    /// Moving the marks of this category, which is deleted, into it.
+    Delete(String),
+}
 
-            draw.category = Some(find_and_rotate(&category, categories, dir));
+/// Popup listing the tags or the categories with how many marks have them,
+/// to rename one or merge it into another. Categories can also be created,
+/// and deleted with their marks moved to another.
+#[derive(Default)]
+struct LabelManager {
+    kind: LabelKind,
+    state: ListState,
+    name_box: Option<Prompt<'static>>,
+    /// Whether the name box names a new category rather than a renamed label.
+    creating: bool,
+    picked: Option<LabelPick>,
+}
+
+impl LabelManager {
+    /// The labels listed, when deleting a category there is also a row for
+    /// leaving its marks without one.
+    fn rows<'l>(&self, lib: &'l Library) -> Vec<&'l str> {
+        let labels = match self.kind {
+            LabelKind::Tag => &lib.tags,
+            LabelKind::Category => &lib.categories,
+        };
+        let mut rows: Vec<_> = labels.iter().map(String::as_str).collect();
+        if let Some(LabelPick::Delete(_)) = self.picked {
+            rows.push("");
         }
+        rows
+    }
 
-        if let ElementKind::Tag(n) = element_kind {
-            let mut tags = lib.tags.clone();
-            let mut existing_tags = draw.tags.clone();
-            existing_tags.remove(n);
-            let tag = &draw.tags[n];
-            for tag in existing_tags {
-                tags.remove(&tag);
-            }
-            let tags: Vec<_> = tags.into_iter().collect();
+    /// How many marks have `label`.
+    fn marks(&self, lib: &Library, label: &str) -> usize {
+        lib.list
+            .iter()
+            .filter(|(m, _)| match self.kind {
+                LabelKind::Tag => lib.has_tag(m, label),
+                LabelKind::Category => lib.same_label(&m.category, label),
+            })
+            .count()
+    }
 
-            draw.tags[n] = find_and_rotate(tag, tags, dir);
+    fn rename(&self, from: String, to: String) -> LabelEdit {
+        match self.kind {
+            LabelKind::Tag => LabelEdit::RenameTag(from, to),
+            LabelKind::Category => LabelEdit::RenameCategory(from, to),
         }
     }
 
-    fn delete_current_element(&mut self) {
-        let element_kind = self.get_element_kind();
-        let (draw, _, idx) = self.get_selection();
-        if let ElementKind::Mark = element_kind {
-            let _ = draw;
-            self.draws.remove(idx);
-        } else {
-            match element_kind {
-                ElementKind::Mark => {}
-                ElementKind::Power => draw.power = None,
-                ElementKind::Category => draw.category = None,
-                ElementKind::Tag(n) => {
-                    draw.tags.remove(n);
-                }
+    /// Continues with a change to make once the user is done with one.
+    fn input(&mut self, lib: &Library, ev: KeyEvent) -> ControlFlow<(), Option<LabelEdit>> {
+        let rows = self.rows(lib);
+        let selected = self
+            .state
+            .selected()
+            .and_then(|i| rows.get(i))
+            .map(|l| l.to_string());
+
+        if let Some(name_box) = &mut self.name_box {
+            let ControlFlow::Break(ok) = name_box.input(ev) else {
+                return ControlFlow::Continue(None);
+            };
+            let to = name_box.text.trim().to_string();
+            self.name_box = None;
+            if !ok || to.is_empty() {
+                self.creating = false;
+                return ControlFlow::Continue(None);
+            }
+            if std::mem::take(&mut self.creating) {
+                self.select_renamed(lib, "", &to);
+                return ControlFlow::Continue(Some(LabelEdit::CreateCategory(to)));
             }
+            return ControlFlow::Continue(match selected {
+                Some(from) if to != from => {
+                    self.select_renamed(lib, &from, &to);
+                    Some(self.rename(from, to))
+                }
+                _ => None,
+            });
         }
-        self.line = self.line.saturating_sub(1);
-    }
 
-    fn add_tag(&mut self, library: &Library) {
-        let draw = self.get_selected_draw();
-        let mut tag_lib = library.tags.clone();
-        let existing_tags = draw.tags.clone();
-        for tag in existing_tags {
-            tag_lib.remove(&tag);
+        let len = rows.len();
+        let category = self.kind == LabelKind::Category;
+        match ev.code {
+            KeyCode::Esc if self.picked.is_some() => self.picked = None,
+            KeyCode::Esc | KeyCode::Char('t' | 'T' | 'q' | 'Q') => return ControlFlow::Break(()),
+            KeyCode::Tab if self.picked.is_none() => {
+                self.kind = match self.kind {
+                    LabelKind::Tag => LabelKind::Category,
+                    LabelKind::Category => LabelKind::Tag,
+                };
+                self.state.select(Some(0));
+            }
+            KeyCode::Up if len > 0 => self.state.select(Some(
+                self.state.selected().map_or(0, |i| (i + len - 1) % len),
+            )),
+            KeyCode::Down if len > 0 => self
+                .state
+                .select(Some(self.state.selected().map_or(0, |i| (i + 1) % len))),
+            KeyCode::Char('r' | 'R') if self.picked.is_none() => {
+                if let Some(label) = selected {
+                    self.name_box = Some(Prompt {
+                        title: Line::raw(format!("Rename {label} to")),
+                        cursor_pos: label.len(),
+                        text: label,
+                        max_width: 32,
+                        ..Default::default()
+                    });
+                }
+            }
+            KeyCode::Char('n' | 'N') if category && self.picked.is_none() => {
+                self.name_box = Some(Prompt {
+                    title: Line::raw("New category"),
+                    max_width: 32,
+                    ..Default::default()
+                });
+                self.creating = true;
+            }
+            KeyCode::Char('d' | 'D') if category && self.picked.is_none() => match selected {
+                // nothing to move
+                Some(label) if self.marks(lib, &label) == 0 => {
+                    self.select_renamed(lib, &label, "");
+                    return ControlFlow::Continue(Some(LabelEdit::DeleteCategory(
+                        label,
+                        String::new(),
+                    )));
+                }
+                Some(label) => self.picked = Some(LabelPick::Delete(label)),
+                None => {}
+            },
+            KeyCode::Char('m' | 'M') | KeyCode::Enter => match (self.picked.take(), selected) {
+                (None, Some(label)) => self.picked = Some(LabelPick::Merge(label)),
+                (Some(LabelPick::Merge(from)), Some(to)) if from != to => {
+                    self.select_renamed(lib, &from, &to);
+                    return ControlFlow::Continue(Some(self.rename(from, to)));
+                }
+                (Some(LabelPick::Delete(from)), Some(to)) if from != to => {
+                    self.select_renamed(lib, &from, &to);
+                    return ControlFlow::Continue(Some(LabelEdit::DeleteCategory(from, to)));
+                }
+                _ => {}
+            },
+            _ => {}
         }
-
-        if !tag_lib.is_empty() {
-            draw.tags.push(tag_lib.iter().nth(0).unwrap().clone())
+        // the row for no category is gone once a delete is done with
+        let len = self.rows(lib).len();
+        if self.state.selected().is_some_and(|i| i >= len) {
+            self.state.select(len.checked_sub(1));
         }
+        ControlFlow::Continue(None)
     }
 
-    pub fn draw(&self) -> Paragraph<'_> {
-        let mut i = 0;
-        let mut style_line = || {
-            let style = if i == self.line {
-                Style::default().add_modifier(Modifier::REVERSED)
-            } else {
-                Style::default()
-            };
-            i += 1;
-            style
-        };
+    /// Keeps the cursor on a label being renamed, `lib` still has the old
+    /// name. A label deleted without a replacement leaves it at the top.
+    fn select_renamed(&mut self, lib: &Library, from: &str, to: &str) {
+        let i = self
+            .rows(lib)
+            .into_iter()
+            .filter(|l| !l.is_empty() && *l != from && *l < to)
+            .count();
+        self.state.select(Some(i));
+    }
 
-        let mut text = Text::from(vec![]);
+    fn draw(&mut self, lib: &Library, f: &mut Frame) {
+        let theme = theme::current();
+        let rows = self.rows(lib);
+        let area = centered_rect(
+            f.size(),
+            48,
+            cmp::min(cmp::max(rows.len() as u16, 1) + 4, f.size().height),
+        );
+        f.render_widget(Clear, area);
 
-        for (c, draw) in self.draws.iter().enumerate() {
-            text.extend(format_draw(draw, c, &mut style_line))
-        }
+        let (title, footer) = match (&self.picked, self.kind) {
+            (Some(LabelPick::Merge(label)), _) => {
+                (format!("Merge {label} into..."), "Enter merge, Esc cancel")
+            }
+            (Some(LabelPick::Delete(label)), _) => (
+                format!("Delete {label}, move its marks to..."),
+                "Enter delete, Esc cancel",
+            ),
+            (None, LabelKind::Tag) => ("Tags".into(), "R rename, M merge, Tab categories"),
+            (None, LabelKind::Category) => (
+                "Categories".into(),
+                "R rename, M merge, D delete, N new, Tab tags",
+            ),
+        };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(Span::styled(title, theme.title))
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::raw(footer).centered())
+            .padding(Padding::horizontal(1));
 
-        Paragraph::new(text).scroll((self.scroll as u16, 0))
-    }
-}
+        if rows.is_empty() {
+            let empty = match self.kind {
+                LabelKind::Tag => "<no tags>",
+                LabelKind::Category => "<no categories>",
+            };
+            f.render_widget(
+                Paragraph::new(Span::styled(empty, theme.muted).italic())
+                    .centered()
+                    .block(block),
+                area,
+            );
+        } else {
+            let picked = match &self.picked {
+                Some(LabelPick::Merge(label) | LabelPick::Delete(label)) => Some(label.as_str()),
+                None => None,
+            };
+            let list = List::new(rows.iter().map(|&label| {
+                if label.is_empty() {
+                    return Line::from(Span::styled("<no category>", theme.muted).italic());
+                }
+                let line = Line::from(vec![
+                    Span::raw(label),
+                    Span::styled(format!(" ({} marks)", self.marks(lib, label)), theme.muted),
+                ]);
+                if picked == Some(label) {
+                    line.style(theme.pinned)
+                } else {
+                    line
+                }
+            }))
+            .block(block)
+            .highlight_symbol(">>")
+            .highlight_spacing(HighlightSpacing::Always);
+            f.render_stateful_widget(list, area, &mut self.state);
+        }
 
-fn format_draw<'a, F: FnMut() -> Style>(
-    draw: &'a Draw,
-    n: usize,
-    mut style_line: F,
-) -> Vec<Line<'a>> {
-    let mut v = vec![];
-    v.push(Line::styled(
-        format!("Draw {}", n + 1),
-        style_line().fg(Color::Red),
-    ));
-    if let Some(p) = &draw.power {
-        v.push(label_text_span(">> Power", power_str(*p)).style(style_line()));
-    }
-    if let Some(c) = &draw.category {
-        v.push(label_text_span(">> Category", Span::raw(c.as_str())).style(style_line()));
-    }
-    for tag in &draw.tags {
-        v.push(label_text_span(">> Tag", Span::raw(tag.as_str())).style(style_line()));
+        if let Some(name_box) = &mut self.name_box {
+            name_box.draw(f, f.size());
+        }
     }
-    v
-}
-
-pub struct MarkList {
-    state: TableState,
-    n_items: usize,
 }
 
-impl MarkList {
-    pub fn new(n_items: usize) -> Self {
-        Self {
-            state: TableState::default(),
-            n_items,
-        }
-    }
+/// Scores how well `query` matches `candidate` when its characters appear in
+/// order, ignoring case. Runs of consecutive characters and matches at the
+/// start of a word count for more. `None` if it doesn't match at all.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let mut candidate = candidate.chars().flat_map(char::to_lowercase).enumerate();
+    let mut score = 0;
+    let mut last: Option<usize> = None;
+    let mut prev = ' ';
 
-    pub fn input(&mut self, lib: &mut Library, code: KeyCode) {
-        match code {
-            KeyCode::Up => self.prev_mark(),
-            KeyCode::Down => self.next_mark(),
-            KeyCode::Enter => {
-                let Some(i) = self.state.selected() else {
-                    return;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        loop {
+            let (i, c) = candidate.next()?;
+            let word_start = !prev.is_alphanumeric();
+            prev = c;
+            if c == q {
+                score += match last {
+                    Some(l) if l + 1 == i => 5,
+                    _ if word_start => 3,
+                    _ => 1,
                 };
-                lib.list[i].1 = !lib.list[i].1;
+                last = Some(i);
+                break;
             }
-            _ => {}
+            score -= 1;
         }
     }
 
-    pub fn draw(&mut self, library: &Library, f: &mut Frame, area: Rect) {
-        let layout = Layout::new(
-            Direction::Vertical,
-            [Constraint::Percentage(60), Constraint::Percentage(40)],
-        )
-        .spacing(1)
-        .split(area);
-
-        let longest_name = library
-            .list
-            .iter()
-            .map(|(m, _)| m.name.len())
-            .max()
-            .unwrap();
-        let longest_cat = library.categories.iter().map(|c| c.len()).max().unwrap();
-        let longest_tags = library
-            .list
-            .iter()
-            .map(|(m, _)| m.tags.iter().map(|s| s.len()).intersperse(2).sum::<usize>())
-            .max()
-            .unwrap();
+    Some(score)
+}
 
-        let mark_table = Table::new(
-            library
-                .list
-                .iter()
-                .map(|(mark, free)| {
-                    Row::new([
-                        Span::styled(
-                            mark.name.as_str(),
-                            if !*free {
-                                Style::default().crossed_out()
-                            } else {
-                                Style::default()
-                            },
-                        ),
-                        power_str(mark.power),
-                        Span::raw(mark.category.clone()),
-                        Span::raw(
-                            mark.tags
-                                .iter()
-                                .map(|s| s.as_str())
-                                .intersperse(", ")
-                                .collect::<String>(),
-                        ),
-                    ])
-                })
-                .collect::<Vec<_>>(),
-            [
-                Constraint::Length(longest_name as u16),
-                Constraint::Length(8),
-                Constraint::Length(cmp::max(longest_cat as u16, 8)),
-                Constraint::Length(longest_tags as u16),
-            ],
-        )
-        .header(Row::new([
-            "Name".underlined(),
-            "Power".underlined(),
-            "Category".underlined(),
-            "Tags".underlined(),
-        ]))
-        .highlight_spacing(HighlightSpacing::Always)
-        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
-        .highlight_symbol(">>");
+/// Text entry for a tag with the library's tags matching it listed below,
+/// picking one of those or adding what was typed as a new tag. Until something
+/// is typed the tags are shown as a tree of their `/` separated parts.
+struct TagPicker {
+    prompt: Prompt<'static>,
+    tags: Vec<String>,
+    state: ListState,
+}
 
-        let selected_mark = &library.list[self.state.selected().unwrap_or(0)].0;
+impl TagPicker {
+    /// `tags` are in the order of [`tag_tree`].
+    fn new(tags: Vec<String>) -> Self {
+        TagPicker {
+            prompt: Prompt::default(),
+            tags,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
 
-        let tag_text: String = selected_mark
+    /// The tags matching the text, best first, followed by the text itself
+    /// if it would be a new tag.
+    fn rows(&self) -> Vec<(String, bool)> {
+        let text = self.prompt.text.trim();
+        let mut matches: Vec<_> = self
             .tags
             .iter()
-            .map(String::as_str)
-            .intersperse(", ")
+            .filter_map(|t| Some((fuzzy_score(text, t)?, t)))
             .collect();
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
 
-        let mut text = Text::from(vec![
-            label_text_span("Power", power_str(selected_mark.power)),
-            label_text_span("Category", selected_mark.category.as_str().reset()),
-            label_text_span("Tags", tag_text.reset()),
-            Line::styled(
-                "Description",
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-        ]);
-        text.extend(Text::raw(selected_mark.description.as_str()));
-
-        let description_box = Paragraph::new(text)
-            .block(
-                Block::default()
-                    .title(selected_mark.name.clone().bold())
-                    .borders(Borders::all())
-                    .border_type(BorderType::Rounded),
-            )
-            .wrap(Wrap { trim: true });
-        f.render_stateful_widget(mark_table, layout[0], &mut self.state);
-        f.render_widget(description_box, layout[1])
+        let mut rows: Vec<_> = matches
+            .into_iter()
+            .map(|(_, t)| (t.clone(), false))
+            .collect();
+        if !text.is_empty() && !self.tags.iter().any(|t| t == text) {
+            rows.push((text.to_string(), true));
+        }
+        rows
     }
 
-    fn next_mark(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.n_items - 1 {
-                    0
-                } else {
-                    i + 1
+    /// Breaks with the picked tag, or `None` if cancelled.
+    fn input(&mut self, ev: KeyEvent) -> ControlFlow<Option<String>> {
+        let rows = self.rows();
+        let selected = self.state.selected().and_then(|i| rows.get(i));
+        match ev.code {
+            KeyCode::Esc => return ControlFlow::Break(None),
+            KeyCode::Enter => return ControlFlow::Break(selected.map(|(t, _)| t.clone())),
+            KeyCode::Up => self.state.select(Some(
+                self.state.selected().map_or(0, |i| i.saturating_sub(1)),
+            )),
+            KeyCode::Down => self.state.select(Some(cmp::min(
+                self.state.selected().map_or(0, |i| i + 1),
+                rows.len().saturating_sub(1),
+            ))),
+            KeyCode::Tab => {
+                if let Some((tag, _)) = selected {
+                    self.prompt.text = tag.clone();
+                    self.prompt.cursor_pos = tag.len();
                 }
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
-    }
-
-    fn prev_mark(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.n_items - 1
-                } else {
-                    i - 1
-                }
+            _ => {
+                let _ = self.prompt.input(ev);
+                self.state.select(Some(0));
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
+        }
+
+        ControlFlow::Continue(())
     }
-}
 
-fn label_text_span<'a>(label: &'a str, text: Span<'a>) -> Line<'a> {
-    Line::from(vec![
-        Span::styled(label, Style::default().add_modifier(Modifier::BOLD)),
-        Span::styled(": ", Style::default().add_modifier(Modifier::BOLD)),
-        text,
-    ])
-}
+    fn draw(&mut self, f: &mut Frame) {
+        let rows = self.rows();
+        let height = cmp::min(rows.len(), 8) as u16;
+        let area = centered_rect(f.size(), 40, height + 4);
+        f.render_widget(Clear, area);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(Span::styled("Add tag", theme::current().title))
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::raw("Tab complete, Enter add, Esc cancel").centered())
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let layout = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).split(inner);
+        let text = &self.prompt.text;
+        f.render_widget(
+            Line::from(vec![Span::raw("> "), Span::raw(text.as_str())]),
+            layout[0],
+        );
+        f.set_cursor(
+            layout[0].x + 2 + text[..self.prompt.cursor_pos].width() as u16,
+            layout[0].y,
+        );
 
-fn power_str(p: Power) -> Span<'static> {
-    match p {
-        Power::Poor => "Poor".dark_gray(),
-        Power::Moderate => "Moderate".white(),
-        Power::Good => "Good".green(),
-        Power::Great => "Great".cyan(),
-        Power::Supreme => "Supreme".red(),
-        Power::Unique => "Unique".magenta(),
-        Power::BadKarma => "Bad Karma".black().on_red().bold(),
+        let tree = self.prompt.text.trim().is_empty();
+        let list = List::new(rows.into_iter().map(|(tag, new)| {
+            if new {
+                return Line::from(vec![
+                    Span::raw(tag),
+                    Span::styled(" (new)", theme::current().muted).italic(),
+                ]);
+            }
+            if !tree {
+                return Line::raw(tag);
+            }
+            let (depth, name) = match tag.rsplit_once('/') {
+                Some((parent, name)) => (parent.split('/').count(), name),
+                None => (0, tag.as_str()),
+            };
+            let indent = Span::raw("  ".repeat(depth));
+            let parent = self.tags.iter().any(|t| {
+                t.strip_prefix(tag.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+            });
+            if parent {
+                Line::from(vec![indent, format!("{name}/").blue()])
+            } else {
+                Line::from(vec![indent, Span::raw(name.to_string())])
+            }
+        }))
+        .highlight_symbol(">>")
+        .highlight_spacing(HighlightSpacing::Always);
+        f.render_stateful_widget(list, layout[1], &mut self.state);
     }
 }
 
+/// Multi-line text entry, Enter starts a new line and Ctrl+S submits.
 #[derive(Clone, Debug, Default)]
-struct Prompt<'a> {
-    pub text: String,
-    pub title: Line<'a>,
-    pub prefix: Span<'a>,
-    pub postfix: Span<'a>,
-    pub cursor_pos: usize,
-    pub max_width: usize,
+struct NoteBox {
+    text: String,
+    cursor_pos: usize,
 }
 
-impl<'a> Prompt<'a> {
+const NOTE_WIDTH: u16 = 50;
+const NOTE_HEIGHT: u16 = 8;
+
+impl NoteBox {
+    fn new(text: &str) -> Self {
+        NoteBox {
+            text: text.to_string(),
+            cursor_pos: text.len(),
+        }
+    }
+
     fn input(&mut self, ev: KeyEvent) -> ControlFlow<bool> {
+        let ctrl = ev.modifiers.contains(KeyModifiers::CONTROL);
         match ev.code {
             KeyCode::Esc => return ControlFlow::Break(false),
-            KeyCode::Enter => return ControlFlow::Break(true),
-            KeyCode::Char(c) if c.is_ascii() => {
-                self.text.insert(self.cursor_pos, c);
+            KeyCode::Char('s') if ctrl => return ControlFlow::Break(true),
+            KeyCode::Enter => {
+                self.text.insert(self.cursor_pos, '\n');
                 self.cursor_pos += 1;
             }
-            KeyCode::Backspace if self.cursor_pos > 0 && self.text.len() > 0 => {
-                self.text.remove(self.cursor_pos - 1);
-                self.cursor_pos -= 1;
+            KeyCode::Char(c) if !c.is_control() && !ctrl => {
+                self.text.insert(self.cursor_pos, c);
+                self.cursor_pos += c.len_utf8();
+            }
+            KeyCode::Backspace => {
+                let start = self.prev_boundary();
+                self.text.replace_range(start..self.cursor_pos, "");
+                self.cursor_pos = start;
             }
-            KeyCode::Right => self.cursor_pos = cmp::min(self.cursor_pos + 1, self.max_width - 1),
-            KeyCode::Left => self.cursor_pos = self.cursor_pos.saturating_sub(1),
+            KeyCode::Delete => {
+                let end = self.next_boundary();
+                self.text.replace_range(self.cursor_pos..end, "");
+            }
+            KeyCode::Right => self.cursor_pos = self.next_boundary(),
+            KeyCode::Left => self.cursor_pos = self.prev_boundary(),
             _ => {}
         }
 
         ControlFlow::Continue(())
     }
 
-    fn draw(&mut self, f: &mut Frame, area: Rect) {
-        let layout = Layout::vertical([
-            Constraint::Fill(1),
-            Constraint::Length(3),
-            Constraint::Fill(1),
-        ])
-        .split(area);
+    /// Inserts text at the cursor, keeping line breaks but dropping what the
+    /// keyboard could not have typed either.
+    fn paste(&mut self, text: &str) {
+        let text: String = text
+            .replace("\r\n", "\n")
+            .chars()
+            .filter(|&c| c == '\n' || !c.is_control())
+            .collect();
+        self.text.insert_str(self.cursor_pos, &text);
+        self.cursor_pos += text.len();
+    }
 
-        let area = layout[1];
+    /// The byte offset of the grapheme before the cursor, like
+    /// [`Prompt::prev_boundary`].
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor_pos]
+            .graphemes(true)
+            .next_back()
+            .map_or(0, |g| self.cursor_pos - g.len())
+    }
 
-        let par_text = Line::default().spans([
-            self.prefix.clone(),
-            Span::raw(format!(
-                "{content:_<width$}",
-                content = self.text,
-                width = self.max_width,
-            )),
-            self.postfix.clone(),
-        ]);
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor_pos..]
+            .graphemes(true)
+            .next()
+            .map_or(self.text.len(), |g| self.cursor_pos + g.len())
+    }
 
-        let width = par_text.width() + 4;
+    fn draw(&self, f: &mut Frame, area: Rect) {
+        let area = centered_rect(area, NOTE_WIDTH + 2, NOTE_HEIGHT + 2);
+        let inner = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: NOTE_WIDTH,
+            height: NOTE_HEIGHT,
+        };
 
-        let layout = Layout::horizontal([
-            Constraint::Fill(1),
-            Constraint::Length(width as u16),
-            Constraint::Fill(1),
-        ])
-        .split(area);
+        // lines past the bottom of the box scroll the text up
+        let before = &self.text[..self.cursor_pos];
+        let row = before.matches('\n').count() as u16;
+        let col = before[before.rfind('\n').map_or(0, |i| i + 1)..].width();
+        let scroll = row.saturating_sub(NOTE_HEIGHT - 1);
 
-        let area = layout[1];
+        f.set_cursor(
+            inner.x + cmp::min(col as u16, NOTE_WIDTH - 1),
+            inner.y + row - scroll,
+        );
 
-        let text = Text::from(par_text);
+        let par = Paragraph::new(self.text.as_str())
+            .scroll((scroll, 0))
+            .block(
+                Block::bordered()
+                    .title("Note")
+                    .title_bottom(Line::raw("Ctrl+S to save, Esc to cancel").centered()),
+            );
 
-        // left side + border + pad + prefix len + cursor_pos + one after
-        let cursor_x = area.x + 2 + self.prefix.content.len() as u16 + self.cursor_pos as u16;
-        let cursor_y = area.y + 1;
+        f.render_widget(Clear, area);
+        f.render_widget(par, area);
+    }
+}
 
-        f.set_cursor(cursor_x, cursor_y);
+/// Characters that are not allowed in file names on at least one platform.
+const ILLEGAL_FILENAME_CHARS: &[char] = &['/', '\\', '<', '>', ':', '"', '|', '?', '*'];
 
-        let par = Paragraph::new(text)
-            .centered()
-            .block(Block::bordered().title(self.title.clone()));
+/// Replaces a leading `~` with the home directory.
+fn expand_tilde(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(home).join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
 
-        f.render_widget(Clear, area);
-        f.render_widget(par, area);
+/// Splits a path as typed into its directory, including the trailing `/`,
+/// and the file name.
+fn split_path(path: &str) -> (&str, &str) {
+    match path.rfind('/') {
+        Some(i) => path.split_at(i + 1),
+        None => ("", path),
     }
 }
 
-fn save(library: &Library, results: &Results, filename: &str) -> anyhow::Result<()> {
-    let library = library.clone();
-    let results = results.clone();
-    let savefile = SaveFile { library, results };
+/// The absolute path a save named `filename` is written to. The name may be
+/// a relative or absolute path and start with `~`.
+pub fn save_path(filename: &str) -> anyhow::Result<PathBuf> {
+    // the extension of another save format picks that format
+    let format = SaveFormat::EXTENSIONS.iter().find_map(|ext| {
+        let name = filename.strip_suffix(ext)?.strip_suffix('.')?;
+        Some((name, *ext))
+    });
+    match format {
+        Some((name, ext)) => file_path(name, ext),
+        None => file_path(filename, "json"),
+    }
+}
+
+/// Where a file named by the user goes, `extension` is added to the name.
+pub fn file_path(filename: &str, extension: &str) -> anyhow::Result<PathBuf> {
+    let (dir, name) = split_path(filename);
+    if name.trim().is_empty() {
+        anyhow::bail!("The file name is empty");
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| ILLEGAL_FILENAME_CHARS.contains(c) || c.is_control())
+    {
+        anyhow::bail!("File names can't contain {c:?}");
+    }
+
+    let dir = expand_tilde(dir);
+    if !dir.as_os_str().is_empty() && !dir.is_dir() {
+        anyhow::bail!("{} is not a directory", dir.display());
+    }
+
+    Ok(std::env::current_dir()?
+        .join(dir)
+        .join(format!("{name}.{extension}")))
+}
+
+/// An existing library or save as typed, `.json` can be left off like in the
+/// save prompt.
+fn library_path(filename: &str) -> anyhow::Result<PathBuf> {
+    let filename = filename.trim();
+    if filename.is_empty() {
+        anyhow::bail!("The file name is empty");
+    }
+    let path = expand_tilde(filename);
+    if path.is_file() {
+        return Ok(path);
+    }
+    let json = expand_tilde(&format!("{filename}.json"));
+    if json.is_file() {
+        return Ok(json);
+    }
+    anyhow::bail!("{} does not exist", path.display())
+}
 
-    let save = format!("{}.json", filename);
+/// Shows under a file name prompt where the file goes, or why it can't.
+fn path_footer(path: &anyhow::Result<PathBuf>) -> Line<'static> {
+    match path {
+        Ok(path) => Line::from(Span::styled(
+            path.display().to_string(),
+            theme::current().muted,
+        )),
+        Err(e) => Line::from(Span::styled(e.to_string(), theme::current().error)),
+    }
+}
+
+/// Directories and save files that `path` could be completed to, with the
+/// `.json` left off since the save prompt adds it.
+fn complete_path(path: &str) -> Vec<String> {
+    complete_files(path, false)
+}
+
+/// Like [`complete_path`], with library csvs too.
+fn complete_library(path: &str) -> Vec<String> {
+    complete_files(path, true)
+}
+
+fn complete_files(path: &str, csv: bool) -> Vec<String> {
+    let (dir, prefix) = split_path(path);
+    let read_dir = match dir {
+        "" => std::fs::read_dir("."),
+        dir => std::fs::read_dir(expand_tilde(dir)),
+    };
+    let Ok(entries) = read_dir else {
+        return Vec::new();
+    };
 
-    let mut f = File::create(save)?;
+    let mut completions: Vec<_> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            if entry.path().is_dir() {
+                Some(format!("{dir}{name}/"))
+            } else if let Some(stem) = name.strip_suffix(".json") {
+                Some(format!("{dir}{stem}"))
+            } else if csv && name.ends_with(".csv") {
+                Some(format!("{dir}{name}"))
+            } else {
+                SaveFormat::from_path(&entry.path()).map(|_| format!("{dir}{name}"))
+            }
+        })
+        .collect();
+    completions.sort();
+    completions
+}
 
-    serde_json::to_writer(&mut f, &savefile)?;
+/// Writes a save to `path`, in the format its extension picks.
+pub fn save(
+    library: &Library,
+    results: &Results,
+    presets: &BTreeMap<String, BTreeSet<String>>,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let library = library.clone();
+    let results = results.clone();
+    let presets = presets.clone();
+    let savefile = SaveFile {
+        version: migrate::VERSION,
+        library,
+        results,
+        presets,
+    };
 
-    f.flush()?;
+    format::of(path)?.save(path, &savefile, Config::load().compact_saves)?;
 
     Ok(())
 }
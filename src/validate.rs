@@ -0,0 +1,341 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+
+use crate::{library_paths, read_library_file, script, suggest, DraftResult, Library, Mark};
+
+const USAGE: &str = "usage: upheaval-draft validate [--strict] LIBRARY...";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem with a library, printed like a compiler diagnostic so editors
+/// and CI logs can link to it.
+pub struct Problem {
+    file: String,
+    line: Option<u64>,
+    pub severity: Severity,
+    pub message: String,
+    /// The mark the problem is about, the app can jump to it.
+    pub mark: Option<String>,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, ": {severity}: {}", self.message)
+    }
+}
+
+/// `validate LIBRARY...`: checks libraries for problems and prints a report.
+/// Returns whether they passed, that is had no errors, and with `--strict` no
+/// warnings either.
+pub fn run(args: impl Iterator<Item = String>) -> anyhow::Result<bool> {
+    let mut strict = false;
+    let mut names = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--strict" => strict = true,
+            a if a.starts_with("--") => bail!("Unknown option {a}\n{USAGE}"),
+            _ => names.push(PathBuf::from(arg)),
+        }
+    }
+    if names.is_empty() {
+        bail!("{USAGE}");
+    }
+    let paths = library_paths(names)?;
+
+    let mut problems = Vec::new();
+    let mut marks: Vec<(Mark, String)> = Vec::new();
+    let mut ignore_case = false;
+    for path in &paths {
+        let file = path.display().to_string();
+        // lenient, so every malformed row is reported instead of the first
+        match read_library_file(path, true) {
+            Ok((save, errors)) => {
+                problems.extend(errors.into_iter().map(|e| Problem {
+                    file: file.clone(),
+                    line: Some(e.line),
+                    severity: Severity::Error,
+                    message: e.detail(),
+                    mark: None,
+                }));
+                ignore_case |= save.library.ignore_case;
+                if let Some(script) = &save.library.script {
+                    let script = path.parent().unwrap_or(Path::new("")).join(script);
+                    if let Err(e) = script::Rules::load(&script) {
+                        problems.push(Problem {
+                            file: file.clone(),
+                            line: None,
+                            severity: Severity::Error,
+                            message: format!("{e:#}"),
+                            mark: None,
+                        });
+                    }
+                }
+                problems.extend(check_results(&save.library, save.results.list(), &file));
+                for (mark, _) in save.library.list {
+                    if save.library.tiers.get(&mark.power).is_none() {
+                        let suggestion = suggest(mark.power.name(), save.library.tiers.names());
+                        problems.push(Problem {
+                            file: file.clone(),
+                            line: None,
+                            severity: Severity::Error,
+                            message: format!(
+                                "mark {:?} has unknown power {:?}{}",
+                                mark.name,
+                                mark.power.name(),
+                                suggestion
+                                    .map(|s| format!(" (did you mean '{s}'?)"))
+                                    .unwrap_or_default()
+                            ),
+                            mark: Some(mark.name.clone()),
+                        });
+                    }
+                    marks.push((mark, file.clone()));
+                }
+            }
+            Err(e) => problems.push(Problem {
+                file,
+                line: None,
+                severity: Severity::Error,
+                message: format!("{e:#}"),
+                mark: None,
+            }),
+        }
+    }
+    problems.extend(check_marks(&marks, ignore_case));
+
+    for problem in &problems {
+        println!("{problem}");
+    }
+    let errors = problems
+        .iter()
+        .filter(|p| p.severity == Severity::Error)
+        .count();
+    let warnings = problems.len() - errors;
+    println!(
+        "{} marks in {} file(s): {errors} error(s), {warnings} warning(s)",
+        marks.len(),
+        paths.len()
+    );
+
+    Ok(errors == 0 && (!strict || warnings == 0))
+}
+
+/// The problems of the loaded library that the app reports on start, those
+/// of the files themselves were caught loading them.
+pub fn check_library(lib: &Library) -> Vec<Problem> {
+    let marks: Vec<_> = lib
+        .list
+        .iter()
+        .map(|(m, _)| (m.clone(), m.source.clone()))
+        .collect();
+    check_marks(&marks, lib.ignore_case)
+}
+
+/// Drafts holding marks that exclude each other, which only happens when
+/// the results were edited by hand or the library changed since.
+pub fn check_results(lib: &Library, results: &[DraftResult], file: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for (i, result) in results.iter().enumerate() {
+        for (a, b) in lib.exclusions(&result.marks) {
+            let (a, b) = (&result.marks[a].name, &result.marks[b].name);
+            problems.push(Problem {
+                file: result.source.clone().unwrap_or_else(|| file.to_string()),
+                line: None,
+                severity: Severity::Warning,
+                message: format!("draft #{i} has {a:?} and {b:?}, which exclude each other"),
+                mark: Some(a.clone()),
+            });
+        }
+    }
+    problems
+}
+
+/// Problems that need every mark to spot: duplicates across files, tags and
+/// categories that only one mark has, which are usually typos of another, and
+/// tags spelled with different case unless the library ignores it.
+fn check_marks(marks: &[(Mark, String)], ignore_case: bool) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let problem = |(mark, file): &(Mark, String), severity, message| Problem {
+        file: file.to_string(),
+        line: None,
+        severity,
+        message,
+        mark: Some(mark.name.clone()),
+    };
+
+    let mut seen: HashMap<&str, &(Mark, String)> = HashMap::new();
+    for entry @ (mark, _) in marks {
+        if mark.name.trim().is_empty() {
+            problems.push(Problem {
+                mark: None,
+                ..problem(entry, Severity::Error, "mark with no name".into())
+            });
+            continue;
+        }
+        match seen.get(mark.name.as_str()) {
+            // marks of different libraries get namespaced instead of clashing
+            Some((first, first_file)) if first.library() != mark.library() => {
+                problems.push(problem(
+                    entry,
+                    Severity::Warning,
+                    format!(
+                        "mark {:?} is also defined in {first_file}, it is loaded as \"{}:{}\"",
+                        mark.name,
+                        mark.library(),
+                        mark.name
+                    ),
+                ))
+            }
+            Some((_, first_file)) => problems.push(problem(
+                entry,
+                Severity::Error,
+                format!(
+                    "duplicate mark {:?} (already defined in {first_file})",
+                    mark.name
+                ),
+            )),
+            None => {
+                seen.insert(&mark.name, entry);
+            }
+        }
+        if mark.category.trim().is_empty() {
+            problems.push(problem(
+                entry,
+                Severity::Warning,
+                format!("mark {:?} has no category", mark.name),
+            ));
+        }
+        if mark.description.trim().is_empty() {
+            problems.push(problem(
+                entry,
+                Severity::Warning,
+                format!("mark {:?} has no description", mark.name),
+            ));
+        }
+    }
+
+    let mut tags: BTreeMap<&str, Vec<&(Mark, String)>> = BTreeMap::new();
+    let mut categories: BTreeMap<&str, Vec<&(Mark, String)>> = BTreeMap::new();
+    for entry in marks {
+        for tag in &entry.0.tags {
+            tags.entry(tag).or_default().push(entry);
+        }
+        if !entry.0.category.trim().is_empty() {
+            categories.entry(&entry.0.category).or_default().push(entry);
+        }
+    }
+
+    // the spelling most marks use is taken as the right one
+    let mut spellings: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for tag in tags.keys() {
+        spellings.entry(tag.to_lowercase()).or_default().push(tag);
+    }
+    let mut miscased = BTreeSet::new();
+    for variants in spellings.values_mut().filter(|v| v.len() > 1) {
+        variants.sort_by_key(|tag| std::cmp::Reverse(tags[tag].len()));
+        let (right, wrong) = variants.split_first().unwrap();
+        for tag in wrong {
+            miscased.insert(*tag);
+            if ignore_case {
+                continue;
+            }
+            problems.push(problem(
+                tags[tag][0],
+                Severity::Warning,
+                format!(
+                    "tag {tag:?} of {:?} differs from {right:?} only in case \
+                     (set ignore_case to treat them as one)",
+                    tags[tag][0].0.name
+                ),
+            ));
+        }
+    }
+
+    let common: Vec<&str> = tags
+        .iter()
+        .filter(|(_, marks)| marks.len() > 1)
+        .map(|(tag, _)| *tag)
+        .collect();
+    for (tag, users) in &tags {
+        let [entry] = users.as_slice() else {
+            continue;
+        };
+        // a tag under a parent other tags share is meant to be specific
+        let has_siblings = tag.rsplit_once('/').is_some_and(|(parent, _)| {
+            tags.keys().any(|t| {
+                t != tag
+                    && t.strip_prefix(parent)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+        });
+        if miscased.contains(tag) || has_siblings {
+            continue;
+        }
+        let suggestion = suggest(tag, common.iter().copied())
+            .map(|s| format!(" (did you mean '{s}'?)"))
+            .unwrap_or_default();
+        problems.push(problem(
+            entry,
+            Severity::Warning,
+            format!("tag {tag:?} is only used by {:?}{suggestion}", entry.0.name),
+        ));
+    }
+
+    let names: BTreeSet<&str> = marks.iter().map(|(m, _)| m.name.as_str()).collect();
+    for entry @ (mark, _) in marks {
+        let refs = [("excludes", &mark.excludes), ("requires", &mark.requires)];
+        for (field, x) in refs
+            .iter()
+            .flat_map(|(f, xs)| xs.iter().map(move |x| (f, x)))
+        {
+            let is_tag = tags.keys().any(|t| {
+                t == x
+                    || t.strip_prefix(x.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            });
+            if names.contains(x.as_str()) || is_tag {
+                continue;
+            }
+            let suggestion = suggest(x, names.iter().chain(tags.keys()).copied())
+                .map(|s| format!(" (did you mean '{s}'?)"))
+                .unwrap_or_default();
+            problems.push(problem(
+                entry,
+                Severity::Warning,
+                format!(
+                    "mark {:?} {field} {x:?}, which is no mark or tag{suggestion}",
+                    mark.name
+                ),
+            ));
+        }
+    }
+
+    for (category, users) in &categories {
+        if let [entry] = users.as_slice() {
+            problems.push(problem(
+                entry,
+                Severity::Warning,
+                format!("category {category:?} only has {:?}", entry.0.name),
+            ));
+        }
+    }
+
+    problems
+}
@@ -0,0 +1,51 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Notices when the library files of a session change on disk, e.g. when
+/// they are edited in a spreadsheet, by comparing their modification times.
+#[derive(Default)]
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watcher {
+    pub fn new(paths: &[PathBuf]) -> Watcher {
+        Watcher {
+            files: paths.iter().map(|p| (p.clone(), modified(p))).collect(),
+        }
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.iter().map(|(p, _)| p.clone()).collect()
+    }
+
+    /// The files that changed since the last call, each change is reported
+    /// once.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, stamp) in &mut self.files {
+            let now = modified(path);
+            // a file being rewritten can be missing for a moment
+            if now.is_some() && now != *stamp {
+                *stamp = now;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /// Takes the files as they are now as unchanged, after the app wrote one
+    /// of them itself.
+    pub fn refresh(&mut self) {
+        for (path, stamp) in &mut self.files {
+            *stamp = modified(path);
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}